@@ -0,0 +1,132 @@
+use serde::Deserialize;
+use std::{borrow::Cow, collections::HashMap};
+
+/// Short names for commands sent to SMAPI, like `gg` for
+/// `player_add sword 1`.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct Aliases(HashMap<String, String>);
+
+impl Aliases {
+    /// Expands the alias at the start of a command. Arguments after the alias
+    /// replace `$1`, `$2`, and so on in the expanded command, or are added to
+    /// the end of it as they were typed if it doesn't use any. Quoted
+    /// arguments are kept together with their quotes, since SMAPI reads them
+    /// itself. Commands that don't start with an alias are returned
+    /// unchanged.
+    pub fn expand<'a>(&self, command: &'a str) -> Cow<'a, str> {
+        let command_start = command.trim_start();
+        let (name, rest) = command_start
+            .split_once(char::is_whitespace)
+            .unwrap_or((command_start, ""));
+        let Some(expanded) = self.0.get(name) else {
+            return Cow::Borrowed(command);
+        };
+
+        let rest = rest.trim();
+        let args = split_args(rest);
+        let mut result = String::with_capacity(expanded.len() + rest.len());
+        let mut used_args = false;
+        let mut remaining = expanded.as_str();
+        while let Some(dollar) = remaining.find('$') {
+            result.push_str(&remaining[..dollar]);
+            let after = &remaining[dollar + 1..];
+            let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            match after[..digits].parse::<usize>() {
+                Ok(index) if index > 0 => {
+                    used_args = true;
+                    result.push_str(args.get(index - 1).copied().unwrap_or_default());
+                }
+                _ => result.push_str(&remaining[dollar..=dollar + digits]),
+            }
+            remaining = &after[digits..];
+        }
+        result.push_str(remaining);
+
+        if !used_args && !rest.is_empty() {
+            result.push(' ');
+            result.push_str(rest);
+        }
+
+        // Missing arguments can leave spaces at the end
+        Cow::Owned(result.trim().to_string())
+    }
+}
+
+/// Splits a command's arguments on whitespace, keeping quoted arguments like
+/// `"Iridium Sprinkler"` together.
+fn split_args(args: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+    for (index, c) in args.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        match (start, c.is_whitespace() && !quoted) {
+            (None, false) => start = Some(index),
+            (Some(arg_start), true) => {
+                result.push(&args[arg_start..index]);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(arg_start) = start {
+        result.push(&args[arg_start..]);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases() -> Aliases {
+        Aliases(
+            [
+                ("gg", "player_add sword 1"),
+                ("give", "player_add $1 $2"),
+                ("tenth", "echo $10 $1"),
+                ("money", "player_setmoney"),
+            ]
+            .into_iter()
+            .map(|(name, command)| (name.to_string(), command.to_string()))
+            .collect(),
+        )
+    }
+
+    #[test]
+    fn expands_aliases() {
+        let aliases = aliases();
+        assert_eq!(aliases.expand("gg"), "player_add sword 1");
+        assert_eq!(aliases.expand("give 64 10"), "player_add 64 10");
+        assert_eq!(aliases.expand("give 64"), "player_add 64");
+        assert_eq!(aliases.expand("money 5000"), "player_setmoney 5000");
+    }
+
+    #[test]
+    fn keeps_arguments_as_typed() {
+        let aliases = aliases();
+        assert_eq!(
+            aliases.expand("give \"Iridium Sprinkler\" 5"),
+            "player_add \"Iridium Sprinkler\" 5"
+        );
+        assert_eq!(
+            aliases.expand("money  5000   \"a  b\" "),
+            "player_setmoney 5000   \"a  b\""
+        );
+        assert_eq!(aliases.expand("tenth 1 2 3 4 5 6 7 8 9 10"), "echo 10 1");
+        assert_eq!(aliases.expand("tenth"), "echo");
+    }
+
+    #[test]
+    fn leaves_other_commands_unchanged() {
+        let aliases = aliases();
+        assert!(matches!(
+            aliases.expand("debug gg"),
+            Cow::Borrowed("debug gg")
+        ));
+        assert!(matches!(aliases.expand(""), Cow::Borrowed("")));
+    }
+}
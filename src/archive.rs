@@ -0,0 +1,190 @@
+use anyhow::Context;
+use flate2::read::MultiGzDecoder;
+use std::{
+    fs::File,
+    io::{Read, Seek},
+    path::Path,
+};
+use tracing::{instrument, trace};
+use zip::ZipArchive;
+
+/// The bytes every gzip stream starts with.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Checks whether a path is a zip archive, going by its extension.
+pub fn is_zip(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"))
+}
+
+/// Checks whether some data is gzip-compressed, going by its first bytes.
+pub fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&GZIP_MAGIC)
+}
+
+/// Checks whether a file is gzip-compressed, going by its first bytes. Files
+/// that can't be read aren't gzip, so the usual error is shown when they're
+/// opened as logs.
+pub fn is_gzip_file(path: &Path) -> bool {
+    let mut magic = [0; 2];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|()| is_gzip(&magic))
+}
+
+/// Decodes the contents of a log, decompressing it first if it's gzipped.
+/// Invalid UTF-8 is replaced so a few bad bytes don't stop the log from
+/// being read.
+pub fn decode_log(data: Vec<u8>) -> anyhow::Result<String> {
+    let data = if is_gzip(&data) {
+        let mut decompressed = Vec::new();
+        MultiGzDecoder::new(data.as_slice())
+            .read_to_end(&mut decompressed)
+            .context("error decompressing gzipped log")?;
+        decompressed
+    } else {
+        data
+    };
+
+    Ok(String::from_utf8(data)
+        .unwrap_or_else(|error| String::from_utf8_lossy(error.as_bytes()).into_owned()))
+}
+
+/// Reads a log file, decompressing it if it's gzipped.
+pub fn read_gzip_log(path: &Path) -> anyhow::Result<String> {
+    let data =
+        std::fs::read(path).with_context(|| format!("error reading log: {}", path.display()))?;
+    decode_log(data).with_context(|| format!("error reading log: {}", path.display()))
+}
+
+/// A log file inside a zip archive.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ZipLogEntry {
+    /// The path of the file in the archive.
+    pub name: String,
+    /// The uncompressed size of the file, in bytes.
+    pub size: u64,
+}
+
+impl ZipLogEntry {
+    /// Checks whether the file is named like a log written by SMAPI, like
+    /// `SMAPI-latest.txt` or `SMAPI-crash.txt`.
+    pub fn is_smapi_log(&self) -> bool {
+        let file_name = self.name.rsplit(['/', '\\']).next().unwrap_or_default();
+        file_name
+            .get(..6)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("SMAPI-"))
+            && is_text_file(file_name)
+    }
+}
+
+/// Checks whether a file in an archive has a `.txt` extension.
+fn is_text_file(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("txt"))
+}
+
+/// Opens a zip archive.
+pub fn open(path: &Path) -> anyhow::Result<ZipArchive<File>> {
+    let file = File::open(path)
+        .with_context(|| format!("error opening zip archive: {}", path.display()))?;
+    ZipArchive::new(file).with_context(|| format!("error reading zip archive: {}", path.display()))
+}
+
+/// Lists the text files in a zip archive that could be logs. Files named
+/// like SMAPI logs come first, then the rest, with larger files first.
+#[instrument(level = "trace", skip_all)]
+pub fn find_logs<R: Read + Seek>(archive: &mut ZipArchive<R>) -> anyhow::Result<Vec<ZipLogEntry>> {
+    let mut logs = Vec::new();
+    for index in 0..archive.len() {
+        let file = archive
+            .by_index(index)
+            .context("error reading zip archive entry")?;
+        let entry = ZipLogEntry {
+            name: file.name().to_string(),
+            size: file.size(),
+        };
+        if file.is_file() && is_text_file(&entry.name) {
+            trace!(?entry, "found log in zip archive");
+            logs.push(entry);
+        }
+    }
+
+    logs.sort_by_key(|entry| (!entry.is_smapi_log(), std::cmp::Reverse(entry.size)));
+    Ok(logs)
+}
+
+/// Reads a log out of a zip archive.
+pub fn read_log<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> anyhow::Result<String> {
+    let mut file = archive
+        .by_name(name)
+        .with_context(|| format!("error finding {name} in zip archive"))?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)
+        .with_context(|| format!("error reading {name} from zip archive"))?;
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::{Cursor, Write};
+    use zip::{write::FileOptions, ZipWriter};
+
+    fn create_archive(files: &[(&str, &str)]) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn detects_zip_paths() {
+        assert!(is_zip(Path::new("logs.zip")));
+        assert!(is_zip(Path::new("bug report/LOGS.ZIP")));
+        assert!(!is_zip(Path::new("SMAPI-latest.txt")));
+        assert!(!is_zip(Path::new("zip")));
+    }
+
+    #[test]
+    fn decodes_gzipped_logs() {
+        let contents = "[10:00:00 INFO  SMAPI] Hello\n";
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(contents.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert!(is_gzip(&compressed));
+        assert_eq!(decode_log(compressed).unwrap(), contents);
+        assert!(!is_gzip(contents.as_bytes()));
+        assert_eq!(decode_log(contents.as_bytes().to_vec()).unwrap(), contents);
+    }
+
+    #[test]
+    fn finds_smapi_logs_first() {
+        let mut archive = create_archive(&[
+            ("readme.txt", "A much longer file that isn't a log at all"),
+            ("ErrorLogs/SMAPI-crash.txt", "[10:00:00 INFO  SMAPI] Hi"),
+            ("ErrorLogs/SMAPI-latest.txt", "[10:00:00 INFO  SMAPI] Hello"),
+            ("screenshot.png", "not a log"),
+        ]);
+        let logs = find_logs(&mut archive).unwrap();
+        let names: Vec<_> = logs.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "ErrorLogs/SMAPI-latest.txt",
+                "ErrorLogs/SMAPI-crash.txt",
+                "readme.txt"
+            ]
+        );
+        assert!(logs[0].is_smapi_log());
+        assert!(!logs[2].is_smapi_log());
+
+        let contents = read_log(&mut archive, &logs[0].name).unwrap();
+        assert_eq!(contents, "[10:00:00 INFO  SMAPI] Hello");
+    }
+}
@@ -1,9 +1,15 @@
+use anyhow::{bail, Context};
+use chrono::{NaiveDate, TimeDelta};
+use serde::{Deserialize, Serialize};
 use std::{
     borrow::Cow,
     fmt::{Display, Formatter},
+    str::FromStr,
+    time::Duration,
 };
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Level {
     Trace,
     Debug,
@@ -11,6 +17,10 @@ pub enum Level {
     Alert,
     Warn,
     Error,
+    /// A level pufferwatch doesn't know about, like one added in a newer
+    /// version of SMAPI. These can't be used in the config or state files.
+    #[serde(skip)]
+    Other(LevelName),
 }
 
 impl Level {
@@ -25,6 +35,28 @@ impl Level {
     ];
 }
 
+impl Level {
+    /// Gets the level with a name from a message header. Unknown names become
+    /// [`Level::Other`] if they're short enough.
+    pub fn from_header(name: &str) -> Option<Self> {
+        match name {
+            "TRACE" => Some(Level::Trace),
+            "DEBUG" => Some(Level::Debug),
+            "INFO" => Some(Level::Info),
+            "ALERT" => Some(Level::Alert),
+            "WARN" => Some(Level::Warn),
+            "ERROR" => Some(Level::Error),
+            _ => LevelName::new(name).map(Level::Other),
+        }
+    }
+
+    /// Checks if messages with this level are errors. Alerts count as errors
+    /// so jumping to errors doesn't skip them.
+    pub fn is_error(self) -> bool {
+        matches!(self, Level::Error | Level::Alert)
+    }
+}
+
 impl Display for Level {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -34,12 +66,63 @@ impl Display for Level {
             Level::Alert => "ALERT".fmt(f),
             Level::Warn => "WARN".fmt(f),
             Level::Error => "ERROR".fmt(f),
+            Level::Other(name) => name.as_str().fmt(f),
         }
     }
 }
 
+/// The name of an unknown level. Names are stored inline so levels can still
+/// be copied.
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct LevelName {
+    bytes: [u8; LevelName::MAX_LEN],
+    len: u8,
+}
+
+impl LevelName {
+    /// The longest name that is recognized as a level.
+    pub const MAX_LEN: usize = 8;
+
+    /// Creates a level name, if the name is short enough and only has ASCII
+    /// letters.
+    pub fn new(name: &str) -> Option<Self> {
+        if name.is_empty()
+            || name.len() > Self::MAX_LEN
+            || !name.bytes().all(|b| b.is_ascii_alphabetic())
+        {
+            return None;
+        }
+
+        let mut bytes = [0; Self::MAX_LEN];
+        bytes[..name.len()].copy_from_slice(name.as_bytes());
+        Some(LevelName {
+            bytes,
+            len: name.len() as u8,
+        })
+    }
+
+    /// Gets the name as a string.
+    pub fn as_str(&self) -> &str {
+        // Names are always ASCII
+        std::str::from_utf8(&self.bytes[..usize::from(self.len)]).unwrap_or_default()
+    }
+}
+
+impl FromStr for Level {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Level::ALL
+            .into_iter()
+            .find(|level| level.to_string().eq_ignore_ascii_case(s))
+            .with_context(|| format!("unknown level '{s}'"))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
 pub struct Timestamp {
+    /// The date, if the log includes it in the header.
+    pub date: Option<NaiveDate>,
     pub hour: u8,
     pub minute: u8,
     pub second: u8,
@@ -57,6 +140,251 @@ impl Display for Timestamp {
     }
 }
 
+/// A problem with the user's mod setup that SMAPI reported.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum Problem {
+    /// A mod was skipped because a mod it depends on isn't installed.
+    MissingDependency,
+    /// A mod was skipped for another reason, like being outdated.
+    SkippedMod,
+    /// No mods were loaded at all.
+    NoMods,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::MissingDependency => "Missing dependency".fmt(f),
+            Problem::SkippedMod => "Skipped mod".fmt(f),
+            Problem::NoMods => "No mods".fmt(f),
+        }
+    }
+}
+
+/// Something that happened in the game that SMAPI logged, used to build a
+/// timeline of the session.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum GameEvent {
+    /// The game was launched.
+    Launched,
+    /// A save was loaded.
+    SaveLoaded,
+    /// A new day started after the game was saved.
+    DayStarted,
+    /// The player returned to the title screen.
+    ReturnedToTitle,
+    /// Pufferwatch started recording the log with `--mark-sessions`.
+    RecordingStarted,
+}
+
+impl GameEvent {
+    /// The start of the messages SMAPI logs for each event, other than the
+    /// launch banner.
+    const MARKERS: [(&'static str, GameEvent); 3] = [
+        ("Context: loaded save", GameEvent::SaveLoaded),
+        ("Context: after save, starting", GameEvent::DayStarted),
+        ("Context: returning to title", GameEvent::ReturnedToTitle),
+    ];
+
+    /// The source of the messages pufferwatch adds to recorded logs.
+    pub const RECORDING_SOURCE: &'static str = "pufferwatch";
+
+    /// The start of the message pufferwatch adds to recorded logs when it
+    /// starts recording.
+    pub const RECORDING_MARKER: &'static str = "Recording started";
+
+    /// Gets the start of the message SMAPI logs for this event, if it's
+    /// recognized by a marker.
+    pub fn marker(self) -> Option<&'static str> {
+        if self == GameEvent::RecordingStarted {
+            return Some(Self::RECORDING_MARKER);
+        }
+
+        Self::MARKERS
+            .iter()
+            .find(|&&(_, event)| event == self)
+            .map(|&(marker, _)| marker)
+    }
+}
+
+impl Display for GameEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameEvent::Launched => "Game launched".fmt(f),
+            GameEvent::SaveLoaded => "Save loaded".fmt(f),
+            GameEvent::DayStarted => "Day started".fmt(f),
+            GameEvent::ReturnedToTitle => "Returned to title".fmt(f),
+            GameEvent::RecordingStarted => "Recording started".fmt(f),
+        }
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = anyhow::Error;
+
+    /// Parses a time of day written as `HH:MM:SS`, without a date.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.trim().split(':').collect();
+        let [hour, minute, second] = parts[..] else {
+            bail!("expected a time like 12:34:56, got '{s}'");
+        };
+        let parse = |part: &str, max: u8| {
+            part.parse::<u8>()
+                .ok()
+                .filter(|&value| value <= max)
+                .with_context(|| format!("invalid time '{s}'"))
+        };
+        Ok(Timestamp {
+            date: None,
+            hour: parse(hour, 23)?,
+            minute: parse(minute, 59)?,
+            second: parse(second, 59)?,
+        })
+    }
+}
+
+impl Timestamp {
+    /// Checks whether the time of day is between two times, inclusive. Dates
+    /// are ignored. If `start` is after `end`, the range wraps past midnight.
+    pub fn is_between(self, start: Timestamp, end: Timestamp) -> bool {
+        let time = |timestamp: Timestamp| (timestamp.hour, timestamp.minute, timestamp.second);
+        let (time, start, end) = (time(self), time(start), time(end));
+        if start <= end {
+            start <= time && time <= end
+        } else {
+            start <= time || time <= end
+        }
+    }
+
+    /// Shifts the timestamp by an offset. Since most timestamps have no date,
+    /// this also returns how many days the shifted timestamp moved forward
+    /// (or backward if negative). The date is shifted too, if there is one.
+    pub fn offset_by(self, offset: TimeOffset) -> (Timestamp, i32) {
+        const SECONDS_PER_DAY: i32 = 24 * 60 * 60;
+        let seconds = i32::from(self.hour) * 3600
+            + i32::from(self.minute) * 60
+            + i32::from(self.second)
+            + offset.minutes * 60;
+        let days = seconds.div_euclid(SECONDS_PER_DAY);
+        let seconds = seconds.rem_euclid(SECONDS_PER_DAY);
+        let timestamp = Timestamp {
+            date: self
+                .date
+                .and_then(|date| date.checked_add_signed(TimeDelta::days(days.into()))),
+            hour: (seconds / 3600) as u8,
+            minute: (seconds / 60 % 60) as u8,
+            second: (seconds % 60) as u8,
+        };
+        (timestamp, days)
+    }
+
+    /// Gets how long after an earlier timestamp this one is. If either
+    /// timestamp has no date, a timestamp before the earlier one is assumed
+    /// to be on the next day.
+    pub fn duration_since(self, earlier: Timestamp) -> Duration {
+        const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+        let seconds = |timestamp: Timestamp| {
+            u64::from(timestamp.hour) * 3600
+                + u64::from(timestamp.minute) * 60
+                + u64::from(timestamp.second)
+        };
+        let days = match (self.date, earlier.date) {
+            (Some(date), Some(earlier_date)) if date >= earlier_date => {
+                (date - earlier_date).num_days().unsigned_abs()
+            }
+            _ => {
+                return Duration::from_secs(
+                    (seconds(self) + SECONDS_PER_DAY - seconds(earlier)) % SECONDS_PER_DAY,
+                );
+            }
+        };
+        Duration::from_secs(
+            (days * SECONDS_PER_DAY + seconds(self)).saturating_sub(seconds(earlier)),
+        )
+    }
+}
+
+/// How the timestamps in the headers of a log are written. SMAPI uses the
+/// system's time format, so logs from other locales can differ.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum TimestampFormat {
+    /// Detect the format from the first messages in the log.
+    #[default]
+    Auto,
+    /// 24-hour time separated by colons, like "13:05:09".
+    Colons,
+    /// 24-hour time separated by dots, like "13.05.09".
+    Dots,
+    /// 12-hour time with AM or PM, like "1:05:09 PM".
+    TwelveHour,
+}
+
+impl TimestampFormat {
+    /// The formats that can be detected, in order of preference.
+    pub const DETECTABLE: [TimestampFormat; 3] = [
+        TimestampFormat::Colons,
+        TimestampFormat::Dots,
+        TimestampFormat::TwelveHour,
+    ];
+
+    /// Gets the `strftime` pattern for writing timestamps in this format.
+    /// Auto uses SMAPI's default format.
+    pub fn strftime(self) -> &'static str {
+        match self {
+            TimestampFormat::Auto | TimestampFormat::Colons => "%H:%M:%S",
+            TimestampFormat::Dots => "%H.%M.%S",
+            TimestampFormat::TwelveHour => "%-I:%M:%S %p",
+        }
+    }
+}
+
+impl FromStr for TimestampFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(TimestampFormat::Auto),
+            "colons" | "hh:mm:ss" => Ok(TimestampFormat::Colons),
+            "dots" | "hh.mm.ss" => Ok(TimestampFormat::Dots),
+            "12-hour" | "h:mm:ss tt" => Ok(TimestampFormat::TwelveHour),
+            _ => bail!(
+                "unknown timestamp format '{s}', expected one of: auto, colons, dots, 12-hour"
+            ),
+        }
+    }
+}
+
+/// An offset to shift timestamps by when displaying them, like "+05:30".
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct TimeOffset {
+    minutes: i32,
+}
+
+impl FromStr for TimeOffset {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (sign, rest) = match s.strip_prefix('-') {
+            Some(rest) => (-1, rest),
+            None => (1, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+        let hours: u8 = hours
+            .parse()
+            .with_context(|| format!("expected an offset like +05:30, but found '{s}'"))?;
+        let minutes: u8 = minutes
+            .parse()
+            .with_context(|| format!("expected an offset like +05:30, but found '{s}'"))?;
+        if hours > 23 || minutes > 59 {
+            bail!("offset must be between -23:59 and +23:59, but found '{s}'");
+        }
+
+        Ok(TimeOffset {
+            minutes: sign * (i32::from(hours) * 60 + i32::from(minutes)),
+        })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Message<'a> {
     pub timestamp: Timestamp,
@@ -64,3 +392,106 @@ pub struct Message<'a> {
     pub source: Cow<'a, str>,
     pub contents: Cow<'a, str>,
 }
+
+impl Display for Message<'_> {
+    /// Formats the message the same way SMAPI writes it to the log.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.header(true), self.contents)
+    }
+}
+
+impl Message<'_> {
+    /// Copies the message so it doesn't borrow from the log it was parsed
+    /// from.
+    pub fn into_owned(self) -> Message<'static> {
+        Message {
+            timestamp: self.timestamp,
+            level: self.level,
+            source: self.source.into_owned().into(),
+            contents: self.contents.into_owned().into(),
+        }
+    }
+
+    /// Formats the header of the message the same way SMAPI writes it to the
+    /// log, for example "[12:00:00 INFO  SMAPI]". The timestamp can be left
+    /// out.
+    pub fn header(&self, include_timestamp: bool) -> String {
+        let timestamp = if include_timestamp {
+            format!("{} ", self.timestamp)
+        } else {
+            String::new()
+        };
+        format!("[{timestamp}{:5} {}]", self.level, self.source)
+    }
+
+    /// Gets the contents of the message with its lines joined by a separator,
+    /// so the whole message fits on one line. The contents are unchanged if
+    /// there's no separator.
+    pub fn joined_contents(&self, separator: Option<&str>) -> Cow<'_, str> {
+        match separator {
+            Some(separator) if self.contents.contains('\n') => self
+                .contents
+                .lines()
+                .collect::<Vec<_>>()
+                .join(separator)
+                .into(),
+            _ => Cow::Borrowed(&self.contents),
+        }
+    }
+
+    /// Checks whether this is the banner SMAPI logs each time the game is
+    /// launched, for example "SMAPI 3.13.0 with Stardew Valley 1.5.5 on ...".
+    pub fn is_launch_banner(&self) -> bool {
+        self.source == "SMAPI"
+            && self.contents.starts_with("SMAPI ")
+            && self.contents.contains(" with Stardew Valley ")
+    }
+
+    /// Checks whether this message marks an event in the game, like loading a
+    /// save or starting a new day, or the start of a recording session.
+    pub fn game_event(&self) -> Option<GameEvent> {
+        if self.is_launch_banner() {
+            return Some(GameEvent::Launched);
+        }
+        if self.source == GameEvent::RECORDING_SOURCE
+            && self.contents.starts_with(GameEvent::RECORDING_MARKER)
+        {
+            return Some(GameEvent::RecordingStarted);
+        }
+        if self.source != "SMAPI" {
+            return None;
+        }
+
+        GameEvent::MARKERS
+            .iter()
+            .find(|(marker, _)| self.contents.starts_with(marker))
+            .map(|&(_, event)| event)
+    }
+
+    /// Checks whether this message reports a problem with the user's mod
+    /// setup. SMAPI lists skipped mods as "- Name 1.0 because ...".
+    pub fn problem(&self) -> Option<Problem> {
+        if self.source != "SMAPI" {
+            return None;
+        }
+
+        let contents = self.contents.trim();
+        if contents == "Loaded 0 mods." || contents.starts_with("No mods found") {
+            return Some(Problem::NoMods);
+        }
+        if self.level == Level::Error
+            && contents.starts_with("- ")
+            && contents.contains(" because ")
+        {
+            let missing_dependency =
+                contents.contains("which aren't installed") || contents.contains("isn't installed");
+            return Some(if missing_dependency {
+                Problem::MissingDependency
+            } else {
+                Problem::SkippedMod
+            });
+        }
+
+        None
+    }
+}
@@ -0,0 +1,96 @@
+use crate::ast::Message;
+use std::fmt::Debug;
+use tui::style::Color;
+
+/// A category a message was tagged with by a classifier.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Category {
+    /// The name of the category, which is listed in the filters.
+    pub name: String,
+    /// The color to show messages in this category with.
+    pub color: Color,
+}
+
+/// Tags messages with a category based on their source or contents. The first
+/// classifier that returns a category for a message wins.
+pub trait MessageClassifier: Debug {
+    fn classify(&self, message: &Message) -> Option<Category>;
+}
+
+/// Classifies messages from a source or containing some text. At least one
+/// of them must be set for the classifier to match anything.
+#[derive(Clone, Debug)]
+pub struct PatternClassifier {
+    /// The category to tag matching messages with.
+    pub category: Category,
+    /// The source matching messages must come from.
+    pub source: Option<String>,
+    /// The text matching messages must contain.
+    pub contains: Option<String>,
+}
+
+impl MessageClassifier for PatternClassifier {
+    fn classify(&self, message: &Message) -> Option<Category> {
+        if self.source.is_none() && self.contains.is_none() {
+            return None;
+        }
+
+        let source_matches = self
+            .source
+            .as_ref()
+            .is_none_or(|source| source == message.source.as_ref());
+        let contents_match = self
+            .contains
+            .as_ref()
+            .is_none_or(|text| message.contents.contains(text.as_str()));
+        (source_matches && contents_match).then(|| self.category.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::Log;
+
+    fn classifier(source: Option<&str>, contains: Option<&str>) -> PatternClassifier {
+        PatternClassifier {
+            category: Category {
+                name: "Patches".to_string(),
+                color: Color::Magenta,
+            },
+            source: source.map(String::from),
+            contains: contains.map(String::from),
+        }
+    }
+
+    #[test]
+    fn classifies_by_source_and_contents() {
+        let log = Log::parse(
+            "[10:00:00 INFO  Content Patcher] Applied patch\n\
+             [10:00:01 INFO  Content Patcher] Loaded pack\n\
+             [10:00:02 INFO  SMAPI] Applied patch\n"
+                .to_string(),
+        )
+        .unwrap();
+        let categorized = |classifier: PatternClassifier| {
+            log.messages()
+                .iter()
+                .map(|message| classifier.classify(message).is_some())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            categorized(classifier(Some("Content Patcher"), None)),
+            [true, true, false]
+        );
+        assert_eq!(
+            categorized(classifier(None, Some("Applied"))),
+            [true, false, true]
+        );
+        assert_eq!(
+            categorized(classifier(Some("Content Patcher"), Some("Applied"))),
+            [true, false, false]
+        );
+        assert_eq!(categorized(classifier(None, None)), [false, false, false]);
+    }
+}
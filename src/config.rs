@@ -1,6 +1,11 @@
+use crate::{
+    ast::{Level, TimeOffset, TimestampFormat},
+    widgets::SelectedTab,
+};
+use anyhow::{bail, Context};
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use reqwest::Url;
-use std::{ffi::OsString, path::PathBuf};
+use std::{ffi::OsString, path::PathBuf, str::FromStr};
 
 /// A CLI application for filtering and monitoring SMAPI logs.
 ///
@@ -23,9 +28,80 @@ pub struct App {
     #[command(subcommand)]
     pub command: AppCommand,
     /// The path to output this application's logs to (not SMAPI logs). Set
-    /// the RUST_LOG environment variable to configure the output.
+    /// the `RUST_LOG` environment variable to configure the output.
     #[arg(long)]
     pub output_log: Option<PathBuf>,
+    /// Record the log to this file as it is received. The log is appended to
+    /// the file if it already exists.
+    #[arg(long)]
+    pub record: Option<PathBuf>,
+    /// Add a line to the recording each time pufferwatch starts recording,
+    /// with the time and pufferwatch version, so sessions recorded to the
+    /// same file can be told apart. They're listed in the timeline.
+    #[arg(long, requires = "record")]
+    pub mark_sessions: bool,
+    /// Write each new message to this file as it arrives, in the same format
+    /// as SMAPI's log. This can be a named pipe read by another program.
+    #[arg(long, value_name = "PATH", conflicts_with = "stream_command")]
+    pub stream_to: Option<PathBuf>,
+    /// Start this shell command and write each new message to its stdin as
+    /// it arrives, like a script that sends a notification for errors.
+    #[arg(long, value_name = "COMMAND")]
+    pub stream_command: Option<String>,
+    /// Only stream messages with this level. This can be specified multiple
+    /// times.
+    #[arg(long = "stream-level", value_name = "LEVEL")]
+    pub stream_levels: Vec<Level>,
+    /// Only stream messages from this source. This can be specified multiple
+    /// times.
+    #[arg(long = "stream-source", value_name = "SOURCE")]
+    pub stream_sources: Vec<String>,
+    /// Append each command sent to SMAPI to this file, along with the time
+    /// it was sent.
+    #[arg(long)]
+    pub command_log: Option<PathBuf>,
+    /// The theme to use when displaying the log.
+    #[arg(long, value_enum, default_value_t = ThemeName::Default)]
+    pub theme: ThemeName,
+    /// Show the current line number and the total number of lines on the
+    /// bottom border of the log.
+    #[arg(long)]
+    pub line_count: bool,
+    /// The maximum number of times per second to redraw the screen. Updates
+    /// that arrive faster than this are combined into a single frame. Set to
+    /// 0 to redraw on every update.
+    #[arg(long, default_value_t = 30)]
+    pub max_fps: u32,
+    /// Capture the mouse to scroll with the mouse wheel, hover over
+    /// truncated sources, and click filters to toggle them. While the mouse
+    /// is captured, the terminal can't select and copy text.
+    #[arg(long)]
+    pub mouse: bool,
+    /// Shift displayed timestamps by this offset, like +05:30 or -8. This
+    /// helps compare timestamps with other tools that use another time zone.
+    /// Timestamps shifted to another day are marked with +1 or -1.
+    #[arg(long, allow_hyphen_values = true)]
+    pub time_offset: Option<TimeOffset>,
+    /// Only check the size and modification time of followed log files for
+    /// changes instead of reading their contents each time they're polled.
+    /// This uses much less CPU for large logs, but edits that don't change
+    /// either might be missed until the file changes again.
+    #[arg(long)]
+    pub no_compare_contents: bool,
+    /// How the timestamps in the log are written: auto, colons (13:05:09),
+    /// dots (13.05.09), or 12-hour (1:05:09 PM). Auto detects the format
+    /// from the first messages in the log. Headers that don't match the
+    /// format are treated as part of the previous message.
+    #[arg(long, value_name = "FORMAT", default_value = "auto")]
+    pub timestamp_format: TimestampFormat,
+    /// The tab to select when pufferwatch starts, either log, raw, or summary. This
+    /// overrides the startup-tab setting.
+    #[arg(long)]
+    pub tab: Option<SelectedTab>,
+    /// Start at the first error or alert in the log instead of the top or
+    /// end. The log starts as usual if it has no errors.
+    #[arg(long)]
+    pub jump_to_error: bool,
 }
 
 /// A command to execute.
@@ -34,21 +110,34 @@ pub enum AppCommand {
     /// Read or monitor a local log file.
     ///
     /// If no log file is specified, pufferwatch will search for it. On Windows,
-    /// it checks %APPDATA%. On Linux, it checks $XDG_DATA_HOME or
-    /// $HOME/.config. On Mac, it checks $HOME/.config.
+    /// it checks %APPDATA%. On Linux, it checks `$XDG_DATA_HOME` or
+    /// $HOME/.config. On Mac, it checks $HOME/.config. If there is no log
+    /// there and the stardewvalley.targets file in the user's home directory
+    /// points to a Steam installation, it also checks Proton's data directory
+    /// for that installation.
     Monitor(MonitorCommand),
     /// Read from stdin.
     ///
-    /// The log will be parsed directly from stdin as it is received. On
+    /// The log will be parsed directly from stdin as it is received, so it is
+    /// always followed. On
     /// Windows, many applications used from Powershell and cmd buffer their
     /// output, so you might not see any logs until the application is closed.
     Stdin(StdinCommand),
     /// Read from a remote log file.
     ///
-    /// Logs from https://smapi.io/log/ are supported, but the URL must have
+    /// Logs from <https://smapi.io/log/> are supported, but the URL must have
     /// ?format=RawDownload added to the end of it. In other words, those URLs
-    /// should be in the format https://smapi.io/log/123456?format=RawDownload.
+    /// should be in the format <https://smapi.io/log/123456?format=RawDownload>.
+    ///
+    /// With --follow, the log is downloaded again periodically to check for
+    /// new messages.
     Remote(RemoteCommand),
+    /// Read a log copied to the system clipboard.
+    ///
+    /// This is useful for viewing part of a log that was copied from
+    /// somewhere else without saving it to a file first. The clipboard is only
+    /// read once when pufferwatch starts.
+    Clipboard,
     /// Run SMAPI and monitor the logs.
     ///
     /// If no path to SMAPI is specified, pufferwatch will search for it. On
@@ -63,28 +152,74 @@ pub enum AppCommand {
     /// The rules for searching for the log file are specified in the monitor
     /// command.
     Run(RunCommand),
+    /// Print the messages that match a pattern without opening the UI.
+    ///
+    /// Messages are printed in the same format SMAPI uses, so the output can
+    /// be read by pufferwatch again. The log file is found the same way as in
+    /// the monitor command.
+    Grep(GrepCommand),
+    /// Print the parsed log as JSON without opening the UI.
+    ///
+    /// The output is a single JSON document with each message and a summary
+    /// of the number of messages with each level and from each source. The
+    /// log file is found the same way as in the monitor command.
+    Export(ExportCommand),
+}
+
+impl AppCommand {
+    /// Checks whether the log is followed for new messages after it's first
+    /// read. Local files and remote logs are only followed with --follow.
+    /// Logs from stdin and from running SMAPI are always followed, and logs
+    /// from the clipboard never are.
+    pub fn is_following(&self) -> bool {
+        match self {
+            AppCommand::Monitor(MonitorCommand { follow, .. })
+            | AppCommand::Remote(RemoteCommand { follow, .. }) => *follow,
+            AppCommand::Stdin(_) | AppCommand::Run(_) => true,
+            AppCommand::Clipboard | AppCommand::Grep(_) | AppCommand::Export(_) => false,
+        }
+    }
 }
 
 /// Read or monitor a local log file.
 #[derive(Clone, Debug, Args)]
 pub struct MonitorCommand {
-    // The path to the log file.
+    /// The path to the log file, or to a zip archive containing it. Gzipped
+    /// logs are decompressed.
     #[arg(short, long)]
     pub log: Option<PathBuf>,
     /// Watch the log file for changes.
     #[arg(short, long)]
     pub follow: bool,
+    /// Skip everything in the log file before this byte offset. If the
+    /// offset is in the middle of a message, the log starts at the next
+    /// message instead.
+    #[arg(long, value_name = "BYTES", default_value_t = 0)]
+    pub from_offset: u64,
+    /// Send commands to SMAPI by writing them to this file, like a named pipe
+    /// that SMAPI reads its console input from. Commands are written as
+    /// UTF-8.
+    #[arg(long, value_name = "PATH")]
+    pub command_pipe: Option<PathBuf>,
 }
 
 /// Read the log from stdin.
 #[derive(Clone, Debug, Args)]
-pub struct StdinCommand;
+pub struct StdinCommand {
+    /// Also follow this log file, merging its messages with the messages
+    /// from stdin by timestamp.
+    #[arg(long, value_name = "LOG")]
+    pub merge: Option<PathBuf>,
+}
 
 /// Download the log from a remote source.
 #[derive(Clone, Debug, Args)]
 pub struct RemoteCommand {
     /// The URL of the log file.
     pub url: Url,
+    /// Download the log again periodically to check for changes.
+    #[arg(short, long)]
+    pub follow: bool,
 }
 
 /// Run SMAPI and watches the output.
@@ -92,6 +227,15 @@ pub struct RemoteCommand {
 pub struct RunCommand {
     /// The path to the SMAPI executable.
     pub smapi_path: Option<PathBuf>,
+    /// Don't start SMAPI, and follow the log of the SMAPI that's already
+    /// running instead. On Linux, commands can still be sent if SMAPI reads
+    /// its console input from a pipe.
+    #[arg(long, conflicts_with_all = ["smapi_path", "install_index", "cwd", "env_vars"])]
+    pub attach: bool,
+    /// When several Stardew Valley installs are found, run SMAPI from the
+    /// Nth one (starting at 1) instead of asking which one to use.
+    #[arg(long, value_name = "N", conflicts_with = "smapi_path")]
+    pub install_index: Option<usize>,
     /// The arguments to pass to SMAPI.
     #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     pub smapi_args: Vec<OsString>,
@@ -103,6 +247,116 @@ pub struct RunCommand {
     #[cfg_attr(windows, arg(default_value_t = CommandEncoding::Utf16Be))]
     #[cfg_attr(not(windows), arg(default_value_t = CommandEncoding::Utf8))]
     pub encoding: CommandEncoding,
+    /// An environment variable to set for SMAPI, in the format KEY=VALUE.
+    /// This can be specified multiple times.
+    #[arg(long = "env", value_name = "KEY=VALUE")]
+    pub env_vars: Vec<EnvVar>,
+    /// The working directory to start SMAPI in. Defaults to the directory
+    /// containing the SMAPI executable.
+    #[arg(long)]
+    pub cwd: Option<PathBuf>,
+    /// Watch the directory containing the log file and follow whichever log
+    /// file SMAPI writes to, rather than only the log file itself.
+    #[arg(long)]
+    pub watch_dir: bool,
+    /// Exit after this many seconds pass without any new log lines. This is
+    /// useful for capturing the log SMAPI writes while starting up.
+    #[arg(long, value_name = "SECONDS")]
+    pub exit_when_idle: Option<u64>,
+    /// Also stop SMAPI when exiting because the log was idle.
+    #[arg(long, requires = "exit_when_idle")]
+    pub kill_when_idle: bool,
+    /// Print the log to stdout when exiting.
+    #[arg(long)]
+    pub print_log: bool,
+}
+
+/// Print the messages that match a pattern.
+#[derive(Clone, Debug, Args)]
+pub struct GrepCommand {
+    /// The text to search for in each message.
+    pub pattern: String,
+    /// The path to the log file, or to a zip archive containing it. Gzipped
+    /// logs are decompressed. Use - to read the log from stdin.
+    #[arg(short, long)]
+    pub log: Option<PathBuf>,
+    /// The URL of a remote log to search instead of a local file.
+    #[arg(long, conflicts_with = "log")]
+    pub url: Option<Url>,
+    /// Only print messages with this level. This can be specified multiple
+    /// times.
+    #[arg(long = "level", value_name = "LEVEL")]
+    pub levels: Vec<Level>,
+    /// Only print messages from this source. This can be specified multiple
+    /// times.
+    #[arg(long = "source", value_name = "SOURCE")]
+    pub sources: Vec<String>,
+    /// Ignore case when matching the pattern.
+    #[arg(short, long)]
+    pub ignore_case: bool,
+    /// Join the lines of multi-line messages with this separator, so each
+    /// message is printed on one line. Defaults to the join-lines setting.
+    #[arg(long, value_name = "SEPARATOR")]
+    pub join_lines: Option<String>,
+}
+
+/// Print the parsed log as JSON.
+#[derive(Clone, Debug, Args)]
+pub struct ExportCommand {
+    /// The path to the log file, or to a zip archive containing it. Gzipped
+    /// logs are decompressed. Use - to read the log from stdin.
+    #[arg(short, long)]
+    pub log: Option<PathBuf>,
+    /// The URL of a remote log to export instead of a local file.
+    #[arg(long, conflicts_with = "log")]
+    pub url: Option<Url>,
+    /// Format the JSON to be easier to read.
+    #[arg(long)]
+    pub pretty: bool,
+    /// Join the lines of multi-line messages with this separator, so the
+    /// contents of each message are a single line. Defaults to the join-lines
+    /// setting.
+    #[arg(long, value_name = "SEPARATOR")]
+    pub join_lines: Option<String>,
+}
+
+/// An environment variable to set for a spawned process.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct EnvVar {
+    /// The name of the variable.
+    pub key: String,
+    /// The value of the variable.
+    pub value: String,
+}
+
+impl FromStr for EnvVar {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .with_context(|| format!("expected KEY=VALUE, but found '{s}'"))?;
+        if key.is_empty() {
+            bail!("missing variable name in '{s}'");
+        }
+        if key.contains('\0') || value.contains('\0') {
+            bail!("environment variables cannot contain null characters");
+        }
+
+        Ok(EnvVar {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// A built-in theme.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, ValueEnum)]
+pub enum ThemeName {
+    /// The default theme.
+    Default,
+    /// A theme that doesn't rely on color alone to distinguish log levels.
+    ColorBlind,
 }
 
 /// The encoding to use when sending commands.
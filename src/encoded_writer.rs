@@ -1,4 +1,4 @@
-use std::io::Write;
+use std::{fs::File, io::Write, process::ChildStdin};
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
 pub enum ByteOrder {
@@ -46,3 +46,29 @@ where
         }
     }
 }
+
+/// Where commands for SMAPI are written.
+#[derive(Debug)]
+pub enum CommandPipe {
+    /// The stdin of a SMAPI process pufferwatch started.
+    Child(ChildStdin),
+    /// A pipe that a SMAPI process that's already running reads its console
+    /// input from.
+    File(File),
+}
+
+impl Write for CommandPipe {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            CommandPipe::Child(stdin) => stdin.write(buf),
+            CommandPipe::File(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            CommandPipe::Child(stdin) => stdin.flush(),
+            CommandPipe::File(file) => file.flush(),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{
+    ast::{Level, Message},
+    config::ExportCommand,
+    log::Log,
+};
+use anyhow::Context;
+use serde::Serialize;
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    io::{stdout, BufWriter, Write},
+};
+use tracing::instrument;
+
+/// Prints a log as a single JSON document, then exits. The lines of each
+/// message are joined with `join_lines` if it's set.
+#[instrument(skip_all)]
+pub fn export(command: &ExportCommand, log: &Log, join_lines: Option<&str>) -> anyhow::Result<()> {
+    let exported = ExportedLog::new(log, join_lines);
+    let mut output = BufWriter::new(stdout().lock());
+    if command.pretty {
+        serde_json::to_writer_pretty(&mut output, &exported)
+    } else {
+        serde_json::to_writer(&mut output, &exported)
+    }
+    .context("error writing log")?;
+    writeln!(output)
+        .and_then(|()| output.flush())
+        .context("error writing log")
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedLog<'a> {
+    messages: Vec<ExportedMessage<'a>>,
+    summary: Summary<'a>,
+}
+
+impl<'a> ExportedLog<'a> {
+    fn new(log: &'a Log, join_lines: Option<&str>) -> Self {
+        let messages = log.messages();
+        let counts = log.count_by_level();
+        let count = |level| counts.get(&level).copied().unwrap_or(0);
+        ExportedLog {
+            messages: messages
+                .iter()
+                .map(|message| ExportedMessage::new(message, join_lines))
+                .collect(),
+            summary: Summary {
+                messages: messages.len(),
+                levels: LevelCounts {
+                    trace: count(Level::Trace),
+                    debug: count(Level::Debug),
+                    info: count(Level::Info),
+                    alert: count(Level::Alert),
+                    warn: count(Level::Warn),
+                    error: count(Level::Error),
+                },
+                sources: log
+                    .sources()
+                    .map(|source| (source, log.messages_by_source(source).len()))
+                    .collect(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedMessage<'a> {
+    timestamp: String,
+    level: String,
+    source: &'a str,
+    contents: Cow<'a, str>,
+}
+
+impl<'a> ExportedMessage<'a> {
+    fn new(message: &'a Message<'a>, join_lines: Option<&str>) -> Self {
+        ExportedMessage {
+            timestamp: message.timestamp.to_string(),
+            level: message.level.to_string(),
+            source: &message.source,
+            contents: message.joined_contents(join_lines),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Summary<'a> {
+    /// The total number of messages.
+    messages: usize,
+    /// The number of messages with each level.
+    levels: LevelCounts,
+    /// The number of messages from each source.
+    sources: BTreeMap<&'a str, usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LevelCounts {
+    trace: usize,
+    debug: usize,
+    info: usize,
+    alert: usize,
+    warn: usize,
+    error: usize,
+}
@@ -0,0 +1,119 @@
+use crate::{
+    ast::Message,
+    config::GrepCommand,
+    log::Log,
+    widgets::{LogFilters, Theme},
+};
+use anyhow::Context;
+use crossterm::style::{Color, Stylize};
+use std::io::{stdout, BufWriter, ErrorKind, IsTerminal, Write};
+use tracing::instrument;
+use tui::style::Color as TuiColor;
+
+/// Prints the messages in a log that match a search, then exits. The lines of
+/// each message are joined with `join_lines` if it's set.
+#[instrument(skip_all)]
+pub fn grep(
+    command: &GrepCommand,
+    log: &Log,
+    theme: &Theme,
+    join_lines: Option<&str>,
+) -> anyhow::Result<()> {
+    let filters = LogFilters::matching(log, &command.levels, &command.sources);
+    let pattern = if command.ignore_case {
+        command.pattern.to_lowercase()
+    } else {
+        command.pattern.clone()
+    };
+
+    // Only colorize the output when it's being read by a person
+    let stdout = stdout();
+    let colorize = stdout.is_terminal();
+    let mut output = BufWriter::new(stdout.lock());
+    let matches = filters.apply(log).into_iter().filter(|message| {
+        if command.ignore_case {
+            message.contents.to_lowercase().contains(&pattern)
+        } else {
+            message.contents.contains(&pattern)
+        }
+    });
+    for message in matches {
+        let result = if colorize {
+            write_colored(&mut output, message, theme, join_lines)
+        } else {
+            write_plain(&mut output, message, join_lines)
+        };
+
+        // Stop quietly if the output was closed early, like when piped to head
+        match result {
+            Err(error) if error.kind() == ErrorKind::BrokenPipe => return Ok(()),
+            result => result.context("error writing message")?,
+        }
+    }
+
+    match output.flush() {
+        Err(error) if error.kind() == ErrorKind::BrokenPipe => Ok(()),
+        result => result.context("error writing message"),
+    }
+}
+
+/// Writes a message in the same format SMAPI uses so the output can be parsed
+/// again. Joined lines are parsed as a single line.
+fn write_plain(
+    output: &mut impl Write,
+    message: &Message,
+    join_lines: Option<&str>,
+) -> std::io::Result<()> {
+    writeln!(
+        output,
+        "{} {}",
+        message.header(true),
+        message.joined_contents(join_lines)
+    )
+}
+
+fn write_colored(
+    output: &mut impl Write,
+    message: &Message,
+    theme: &Theme,
+    join_lines: Option<&str>,
+) -> std::io::Result<()> {
+    let level_color = to_crossterm_color(theme.level_color(message.level));
+    let source_color = to_crossterm_color(theme.source_color);
+    writeln!(
+        output,
+        "[{} {} {}] {}",
+        message.timestamp,
+        format!("{:5}", message.level).with(level_color),
+        message.source.as_ref().with(source_color),
+        message
+            .joined_contents(join_lines)
+            .as_ref()
+            .with(level_color),
+    )
+}
+
+/// Converts a color used by the UI to one that can be printed directly.
+pub fn to_crossterm_color(color: TuiColor) -> Color {
+    match color {
+        TuiColor::Reset => Color::Reset,
+        TuiColor::Black => Color::Black,
+        TuiColor::Red => Color::DarkRed,
+        TuiColor::Green => Color::DarkGreen,
+        TuiColor::Yellow => Color::DarkYellow,
+        TuiColor::Blue => Color::DarkBlue,
+        TuiColor::Magenta => Color::DarkMagenta,
+        TuiColor::Cyan => Color::DarkCyan,
+        TuiColor::Gray => Color::Grey,
+        TuiColor::DarkGray => Color::DarkGrey,
+        TuiColor::LightRed => Color::Red,
+        TuiColor::LightGreen => Color::Green,
+        TuiColor::LightYellow => Color::Yellow,
+        TuiColor::LightBlue => Color::Blue,
+        TuiColor::LightMagenta => Color::Magenta,
+        TuiColor::LightCyan => Color::Cyan,
+        TuiColor::White => Color::White,
+        TuiColor::Rgb(r, g, b) => Color::Rgb { r, g, b },
+        TuiColor::Indexed(index) => Color::AnsiValue(index),
+    }
+}
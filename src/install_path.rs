@@ -1,5 +1,7 @@
+use itertools::Itertools;
 use serde::Deserialize;
 use std::{
+    fmt::{Display, Formatter},
     fs::File,
     io::BufReader,
     path::{Path, PathBuf},
@@ -28,11 +30,72 @@ pub fn get_install_paths() -> impl IntoIterator<Item = PathBuf> {
         .into_iter()
         .chain(default_paths)
         .filter_map(|path| path.canonicalize().ok())
+        .unique()
         .inspect(|path| trace!(?path, "possible SDV path"))
         .filter(|path| path.join("Stardew Valley.dll").is_file())
         .inspect(|path| trace!(?path, "looks like SDV path"))
 }
 
+/// Where an installation of Stardew Valley came from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum InstallKind {
+    Steam,
+    Gog,
+    Xbox,
+    Other,
+}
+
+impl InstallKind {
+    /// Guesses where an installation came from by its path.
+    pub fn of(path: &Path) -> Self {
+        let path = path.to_string_lossy().to_ascii_lowercase();
+        if path.contains("steamapps") {
+            InstallKind::Steam
+        } else if path.contains("gog") || path.contains("galaxyclient") {
+            InstallKind::Gog
+        } else if path.contains("modifiablewindowsapps") || path.contains("xboxgames") {
+            InstallKind::Xbox
+        } else {
+            InstallKind::Other
+        }
+    }
+}
+
+impl Display for InstallKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            InstallKind::Steam => "Steam",
+            InstallKind::Gog => "GOG",
+            InstallKind::Xbox => "Xbox",
+            InstallKind::Other => "Other",
+        };
+        f.pad(name)
+    }
+}
+
+/// Gets the possible paths to the SMAPI log for a custom installation of
+/// Stardew Valley configured in the stardewvalley.targets file.
+#[instrument(level = "trace")]
+pub fn get_custom_log_paths() -> impl IntoIterator<Item = PathBuf> {
+    let game_path = dirs::home_dir().and_then(|home| get_custom_install_path(&home));
+    game_path
+        .into_iter()
+        .filter_map(|game_path| {
+            // Steam's Proton keeps the game's data in a Wine prefix next to
+            // the installation
+            let steamapps = game_path
+                .parent()
+                .filter(|common| common.ends_with("common"))
+                .and_then(Path::parent);
+            steamapps.map(|steamapps| {
+                steamapps.join(
+                    "compatdata/413150/pfx/drive_c/users/steamuser/AppData/Roaming/StardewValley/ErrorLogs/SMAPI-latest.txt",
+                )
+            })
+        })
+        .inspect(|path| trace!(?path, "possible log path"))
+}
+
 fn get_custom_install_path(home: &Path) -> Option<PathBuf> {
     let targets_file = home.join("stardewvalley.targets");
     let targets_file = File::open(targets_file).ok()?;
@@ -130,3 +193,27 @@ fn get_default_install_paths(_home: Option<&Path>) -> impl IntoIterator<Item = P
 
     paths
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_install_kinds() {
+        let kinds = [
+            (
+                r"C:\Program Files (x86)\Steam\steamapps\common\Stardew Valley",
+                InstallKind::Steam,
+            ),
+            ("/home/user/GOG Games/Stardew Valley/game", InstallKind::Gog),
+            (
+                r"C:\Program Files\ModifiableWindowsApps\Stardew Valley",
+                InstallKind::Xbox,
+            ),
+            ("/opt/stardew", InstallKind::Other),
+        ];
+        for (path, kind) in kinds {
+            assert_eq!(InstallKind::of(Path::new(path)), kind, "{path}");
+        }
+    }
+}
@@ -1,8 +1,40 @@
-use crate::{ast::Message, parse::parse};
+use crate::{
+    ast::{Level, Message, Timestamp, TimestampFormat},
+    parse::{detect_timestamp_format, parse},
+};
 use anyhow::Context;
 use itertools::Itertools;
 use ouroboros::self_referencing;
-use std::{collections::HashMap, fs::File, io::Read, path::Path};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+/// Groups messages by their source.
+fn group_by_source<'a>(messages: &'a [Message<'a>]) -> HashMap<&'a str, Vec<&'a Message<'a>>> {
+    messages
+        .iter()
+        .into_group_map_by(|message| message.source.as_ref())
+}
+
+/// Gets the byte offset of a slice within the string it was borrowed from.
+/// Returns [`None`] if the text isn't part of the string.
+fn offset_in(raw: &str, text: &str) -> Option<usize> {
+    let offset = (text.as_ptr() as usize).checked_sub(raw.as_ptr() as usize)?;
+    (offset + text.len() <= raw.len()).then_some(offset)
+}
+
+/// Moves a slice of one string to the same position in another string that
+/// starts with the same contents. Copied text stays copied.
+fn rebase<'a>(text: &str, old_raw: &str, new_raw: &'a str) -> Cow<'a, str> {
+    match offset_in(old_raw, text) {
+        Some(offset) => Cow::Borrowed(&new_raw[offset..offset + text.len()]),
+        None => Cow::Owned(text.to_string()),
+    }
+}
 
 /// A parsed SMAPI log.
 #[self_referencing]
@@ -28,26 +60,24 @@ impl Log {
         .build()
     }
 
-    /// Parses a log from a string.
+    /// Parses a log from a string, detecting how its timestamps are written.
     pub fn parse(raw: String) -> anyhow::Result<Self> {
+        Log::parse_as(raw, TimestampFormat::Auto)
+    }
+
+    /// Parses a log from a string with timestamps in the given format.
+    pub fn parse_as(raw: String, format: TimestampFormat) -> anyhow::Result<Self> {
         // Log is self-referential because the messages borrow from the raw string
         LogTryBuilder {
             raw,
-            messages_builder: |source| parse(source).context("error parsing log file"),
-            by_source_builder: |messages| {
-                Ok(messages
-                    .iter()
-                    .group_by(|message| message.source.as_ref())
-                    .into_iter()
-                    .map(|(source, messages)| (source, messages.collect_vec()))
-                    .collect())
-            },
+            messages_builder: |source| parse(source, format).context("error parsing log file"),
+            by_source_builder: |messages| Ok(group_by_source(messages)),
         }
         .try_build()
     }
 
     /// Parses a log from a file.
-    pub fn parse_file(path: &Path) -> anyhow::Result<Self> {
+    pub fn parse_file(path: &Path, format: TimestampFormat) -> anyhow::Result<Self> {
         // Read log file
         let mut log_file = File::open(path)
             .with_context(|| format!("Failed to open log file: {}", path.display()))?;
@@ -57,7 +87,118 @@ impl Log {
             .context("Failed to read log file")?;
 
         // Parse log
-        Log::parse(log_contents)
+        Log::parse_as(log_contents, format)
+    }
+
+    /// Parses a log from a file, starting at a byte offset. If the offset is
+    /// in the middle of a message, the log starts at the next message instead.
+    pub fn parse_file_from(
+        path: &Path,
+        offset: u64,
+        format: TimestampFormat,
+    ) -> anyhow::Result<Self> {
+        let (log_contents, _) = Log::read_file_from(path, offset)?;
+        Log::parse_as(log_contents, format)
+    }
+
+    /// Reads a log file from a byte offset the same way as
+    /// [`Log::parse_file_from`]. Also returns the byte offset in the file
+    /// where the contents end.
+    pub fn read_file_from(path: &Path, offset: u64) -> anyhow::Result<(String, u64)> {
+        if offset == 0 {
+            let mut log_file = File::open(path)
+                .with_context(|| format!("Failed to open log file: {}", path.display()))?;
+            let mut log_contents = String::new();
+            log_file
+                .read_to_string(&mut log_contents)
+                .context("Failed to read log file")?;
+            let end = log_contents.len() as u64;
+            return Ok((log_contents, end));
+        }
+
+        // Read from the byte before the offset to check if it starts a line
+        let mut log_file = File::open(path)
+            .with_context(|| format!("Failed to open log file: {}", path.display()))?;
+        log_file
+            .seek(SeekFrom::Start(offset - 1))
+            .context("Failed to seek in log file")?;
+        let mut log_contents = Vec::new();
+        log_file
+            .read_to_end(&mut log_contents)
+            .context("Failed to read log file")?;
+
+        // Skip to the start of the next message
+        let mut remaining = log_contents.as_slice();
+        loop {
+            let Some(newline) = remaining.iter().position(|&b| b == b'\n') else {
+                remaining = &[];
+                break;
+            };
+            remaining = &remaining[newline + 1..];
+            if remaining.starts_with(b"[") {
+                break;
+            }
+        }
+        let end = offset - 1 + log_contents.len() as u64;
+        let log_contents =
+            String::from_utf8(remaining.to_vec()).context("Failed to read log file")?;
+        Ok((log_contents, end))
+    }
+
+    /// Gets the text added to the end of the log since an earlier version of
+    /// it. If the log doesn't start with the earlier text, it was replaced,
+    /// so all of its text is new.
+    pub fn text_added_since(&self, earlier: &Log) -> &str {
+        let raw = self.raw();
+        raw.strip_prefix(earlier.raw()).unwrap_or(raw)
+    }
+
+    /// Creates a copy of the log with more text added to the end. Only the
+    /// last message and the new text are parsed, since the text might
+    /// continue that message. The other messages are reused as-is.
+    pub fn append(&self, appended: &str, format: TimestampFormat) -> anyhow::Result<Self> {
+        let old_raw = self.raw();
+        let mut raw = String::with_capacity(old_raw.len() + appended.len());
+        raw.push_str(old_raw);
+        raw.push_str(appended);
+
+        // Find the line the last message starts on
+        let messages = self.messages();
+        let (kept, tail_start) = match messages.last() {
+            None => (0, 0),
+            Some(last) => {
+                let Some(source_start) = offset_in(old_raw, &last.source) else {
+                    // The messages were copied, so there's nothing to reuse
+                    return Log::parse_as(raw, format);
+                };
+                let line_start = old_raw[..source_start].rfind('\n').map_or(0, |i| i + 1);
+                (messages.len() - 1, line_start)
+            }
+        };
+
+        // The tail might not have enough messages to detect the format
+        let format = match format {
+            TimestampFormat::Auto => detect_timestamp_format(&raw),
+            format => format,
+        };
+        LogTryBuilder {
+            raw,
+            messages_builder: |raw| {
+                let mut new_messages: Vec<Message<'_>> = messages[..kept]
+                    .iter()
+                    .map(|message| Message {
+                        source: rebase(&message.source, old_raw, raw),
+                        contents: rebase(&message.contents, old_raw, raw),
+                        ..message.clone()
+                    })
+                    .collect();
+                new_messages
+                    .extend(parse(&raw[tail_start..], format).context("error parsing log file")?);
+                anyhow::Ok(new_messages)
+            },
+            by_source_builder: |messages| Ok(group_by_source(messages)),
+        }
+        .try_build()
     }
 
     /// Gets the raw log contents.
@@ -66,12 +207,236 @@ impl Log {
     }
 
     /// Gets the messages in the log.
-    pub fn messages(&self) -> &[Message] {
+    pub fn messages(&self) -> &[Message<'_>] {
         self.borrow_messages()
     }
 
+    /// Gets the index of the message that started the most recent game launch
+    /// in this log, if any.
+    pub fn last_launch(&self) -> Option<usize> {
+        self.messages().iter().rposition(Message::is_launch_banner)
+    }
+
     /// Gets the log sources in the log.
     pub fn sources(&self) -> impl Iterator<Item = &str> {
         self.borrow_by_source().keys().copied()
     }
+
+    /// Gets the messages in the log from the given source, in order.
+    pub fn messages_by_source(&self, source: &str) -> &[&Message<'_>] {
+        self.borrow_by_source()
+            .get(source)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Gets the levels that can appear in the log. The known levels come
+    /// first, followed by any unknown levels in the order they appear.
+    pub fn levels(&self) -> impl Iterator<Item = Level> + '_ {
+        let other_levels = self
+            .messages()
+            .iter()
+            .map(|message| message.level)
+            .filter(|level| matches!(level, Level::Other(_)))
+            .unique();
+        Level::ALL.into_iter().chain(other_levels)
+    }
+
+    /// Gets the messages in the log with the given level.
+    pub fn messages_by_level(&self, level: Level) -> impl Iterator<Item = &Message<'_>> {
+        self.messages()
+            .iter()
+            .filter(move |message| message.level == level)
+    }
+
+    /// Gets the messages in the log logged between two times of day,
+    /// inclusive. Dates are ignored, and if `start` is after `end`, the range
+    /// wraps past midnight.
+    #[allow(dead_code)]
+    pub fn messages_in_range(
+        &self,
+        start: Timestamp,
+        end: Timestamp,
+    ) -> impl Iterator<Item = &Message<'_>> {
+        self.messages()
+            .iter()
+            .filter(move |message| message.timestamp.is_between(start, end))
+    }
+
+    /// Counts the number of messages in the log with each level. Levels with
+    /// no messages are not included.
+    pub fn count_by_level(&self) -> HashMap<Level, usize> {
+        self.messages().iter().counts_by(|message| message.level)
+    }
+}
+
+impl Clone for Log {
+    /// Copies the log. The messages are copied instead of being parsed again,
+    /// so the copy doesn't need to know how the timestamps are written.
+    fn clone(&self) -> Self {
+        let messages: Vec<Message<'static>> = self
+            .messages()
+            .iter()
+            .cloned()
+            .map(Message::into_owned)
+            .collect();
+        LogBuilder {
+            raw: self.raw().to_string(),
+            messages_builder: |_| messages,
+            by_source_builder: |messages| group_by_source(messages),
+        }
+        .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\
+[23:59:58 INFO  SMAPI] Before midnight
+[23:59:59 ERROR Json Assets] This mod failed
+[00:00:01 INFO  SMAPI] After midnight
+[00:00:02 WARN  SMAPI] Missing item
+";
+
+    fn contents<'a>(messages: impl IntoIterator<Item = &'a Message<'a>>) -> Vec<&'a str> {
+        messages
+            .into_iter()
+            .map(|message| message.contents.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn gets_messages_by_level() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        assert_eq!(
+            contents(log.messages_by_level(Level::Info)),
+            ["Before midnight", "After midnight"]
+        );
+        assert_eq!(
+            contents(log.messages_by_level(Level::Error)),
+            ["This mod failed"]
+        );
+        assert!(contents(log.messages_by_level(Level::Trace)).is_empty());
+    }
+
+    #[test]
+    fn gets_messages_in_range() {
+        // The range can span midnight
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let time = |time: &str| time.parse::<Timestamp>().unwrap();
+        assert_eq!(
+            contents(log.messages_in_range(time("23:59:59"), time("00:00:01"))),
+            ["This mod failed", "After midnight"]
+        );
+        assert_eq!(
+            contents(log.messages_in_range(time("00:00:01"), time("00:00:02"))),
+            ["After midnight", "Missing item"]
+        );
+
+        // Nothing was logged in the range
+        assert!(contents(log.messages_in_range(time("12:00:00"), time("13:00:00"))).is_empty());
+    }
+
+    #[test]
+    fn counts_messages_by_level() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let counts = log.count_by_level();
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&Level::Info], 2);
+        assert_eq!(counts[&Level::Error], 1);
+        assert_eq!(counts[&Level::Warn], 1);
+        assert!(!counts.contains_key(&Level::Trace));
+
+        let empty = Log::parse(String::new()).unwrap();
+        assert!(empty.count_by_level().is_empty());
+    }
+
+    #[test]
+    fn gets_added_text() {
+        let log = Log::parse("[10:00:00 INFO  SMAPI] Starting\n".to_string()).unwrap();
+        let appended = log.append("[10:00:01 INFO  SMAPI] Done\n", TimestampFormat::Auto);
+        assert_eq!(
+            appended.unwrap().text_added_since(&log),
+            "[10:00:01 INFO  SMAPI] Done\n"
+        );
+
+        // A longer log that replaced it is new from the start
+        let replaced = "[11:00:00 INFO  SMAPI] Starting again\n";
+        let replaced_log = Log::parse(replaced.to_string()).unwrap();
+        assert_eq!(replaced_log.text_added_since(&log), replaced);
+    }
+
+    #[test]
+    fn parses_file_from_offset() {
+        let contents = "\
+[10:00:00 INFO  SMAPI] Starting
+[10:00:01 ERROR Json Assets] This mod failed
+System.NullReferenceException
+[10:00:02 INFO  SMAPI] Done
+";
+        let path =
+            std::env::temp_dir().join(format!("pufferwatch-{}-parse-from.txt", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        let parse_from = |offset: usize| {
+            let log = Log::parse_file_from(&path, offset as u64, TimestampFormat::Auto).unwrap();
+            log.messages()
+                .iter()
+                .map(|message| message.contents.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Starting at a message keeps it
+        let error_start = contents.find("[10:00:01").unwrap();
+        assert_eq!(
+            parse_from(error_start),
+            ["This mod failed\nSystem.NullReferenceException", "Done"]
+        );
+
+        // Starting in the middle of a message skips to the next one, even if
+        // it's partway through a line or on a continued line
+        assert_eq!(parse_from(error_start + 5), ["Done"]);
+        assert_eq!(parse_from(contents.find("System").unwrap()), ["Done"]);
+
+        // Nothing is left after the last message starts
+        assert!(parse_from(contents.find("Done").unwrap()).is_empty());
+        assert!(parse_from(contents.len()).is_empty());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn appends_to_the_last_message() {
+        let start = "[10:00:00 INFO  SMAPI] Starting\n[10:00:01 ERROR Json Assets] This mod";
+        let appended = " failed\nSystem.NullReferenceException\n[10:00:02 INFO  SMAPI] Done\n";
+        let log = Log::parse(start.to_string()).unwrap();
+        let appended_log = log.append(appended, TimestampFormat::Auto).unwrap();
+        let parsed_log = Log::parse(format!("{start}{appended}")).unwrap();
+
+        let summarize = |log: &Log| {
+            log.messages()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(appended_log.raw(), parsed_log.raw());
+        assert_eq!(summarize(&appended_log), summarize(&parsed_log));
+        assert_eq!(appended_log.messages_by_source("SMAPI").len(), 2);
+
+        // Earlier messages still borrow from the log
+        let first = &appended_log.messages()[0];
+        assert!(matches!(first.source, Cow::Borrowed(_)));
+        assert_eq!(offset_in(appended_log.raw(), &first.source), Some(16));
+
+        // Messages with several lines aren't copied either
+        let appended_log = appended_log
+            .append("[10:00:03 INFO  SMAPI] Exiting\n", TimestampFormat::Auto)
+            .unwrap();
+        let error = &appended_log.messages()[1];
+        assert_eq!(
+            error.contents,
+            "This mod failed\nSystem.NullReferenceException"
+        );
+        assert!(offset_in(appended_log.raw(), &error.contents).is_some());
+    }
 }
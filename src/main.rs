@@ -6,18 +6,28 @@
     clippy::cast_sign_loss,
     clippy::cast_precision_loss,
     clippy::cast_lossless,
-    clippy::type_complexity
+    clippy::type_complexity,
+    clippy::struct_excessive_bools
 )]
 
+mod alias;
+mod archive;
 mod ast;
+mod classify;
 mod config;
 mod encoded_writer;
 mod events;
+mod export;
+mod grep;
 mod install_path;
 mod log;
+mod notes;
 mod parse;
+mod screenshot;
+mod settings;
 mod source;
 mod startup;
+mod state_file;
 mod widgets;
 
 fn main() -> anyhow::Result<()> {
@@ -0,0 +1,173 @@
+use crate::{ast::Message, log::Log};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+use tracing::{instrument, trace};
+
+/// Notes attached to the messages of a log, saved between runs of
+/// pufferwatch.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct NotesFile {
+    /// The notes, in the order of the messages they're attached to.
+    pub notes: Vec<Note>,
+}
+
+/// A note attached to a message.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Note {
+    /// The message the note is attached to.
+    #[serde(flatten)]
+    pub message: MessageKey,
+    /// The text of the note.
+    pub text: String,
+}
+
+/// Identifies a message without relying on its position in the log, so notes
+/// stay on the same message when the log changes.
+#[derive(Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MessageKey {
+    /// When the message was logged, including the date if the log has one.
+    pub timestamp: String,
+    pub source: String,
+    /// A hash of the contents of the message, in hexadecimal.
+    pub contents: String,
+}
+
+impl MessageKey {
+    pub fn new(message: &Message) -> Self {
+        let timestamp = match message.timestamp.date {
+            Some(date) => format!("{date} {}", message.timestamp),
+            None => message.timestamp.to_string(),
+        };
+        MessageKey {
+            timestamp,
+            source: message.source.to_string(),
+            contents: format!("{:016x}", fnv1a(&message.contents)),
+        }
+    }
+}
+
+impl NotesFile {
+    /// Gets the path to the notes file for a log. Logs are identified by a
+    /// hash of their first line, which has the time SMAPI started, so the
+    /// same file is used as the log grows. Sessions started at the same time
+    /// on different days share a file, but notes are matched to messages by
+    /// their contents rather than their position. Logs without a complete
+    /// first line don't have a notes file.
+    pub fn path(log: &Log) -> Option<PathBuf> {
+        let (first_line, _) = log.raw().split_once('\n')?;
+        let mut path = dirs::data_dir()?;
+        path.push(format!("pufferwatch/notes/{:016x}.toml", fnv1a(first_line)));
+        Some(path)
+    }
+
+    /// Reads a notes file. A missing file has no notes.
+    fn read(path: &Path) -> anyhow::Result<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                trace!(?path, "notes file not found");
+                return Ok(NotesFile::default());
+            }
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("error reading notes file: {}", path.display()))
+            }
+        };
+        toml::from_str(&contents)
+            .with_context(|| format!("error parsing notes file: {}", path.display()))
+    }
+
+    /// Loads the notes for a log, keyed by the index of the message they're
+    /// attached to. Notes on messages that aren't in the log are skipped. If
+    /// the log has no notes file, no notes are returned.
+    #[instrument(level = "trace", skip_all)]
+    pub fn load(log: &Log) -> anyhow::Result<HashMap<usize, String>> {
+        let Some(path) = Self::path(log) else {
+            return Ok(HashMap::new());
+        };
+        let mut notes: HashMap<_, _> = Self::read(&path)?
+            .notes
+            .into_iter()
+            .map(|note| (note.message, note.text))
+            .collect();
+        Ok(log
+            .messages()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, message)| Some((index, notes.remove(&MessageKey::new(message))?)))
+            .collect())
+    }
+
+    /// Saves the notes for a log, creating the notes directory if needed.
+    /// Notes in the file on messages that aren't in the log are kept, since
+    /// logs from different sessions can share a file.
+    #[instrument(level = "trace", skip_all)]
+    pub fn save(log: &Log, notes: &HashMap<usize, String>) -> anyhow::Result<()> {
+        let path = Self::path(log).context("unable to find notes file path")?;
+        if let Some(parent_dir) = path.parent() {
+            std::fs::create_dir_all(parent_dir).with_context(|| {
+                format!("error creating notes directory: {}", parent_dir.display())
+            })?;
+        }
+
+        // Replace the notes on this log's messages
+        let keys: HashSet<_> = log.messages().iter().map(MessageKey::new).collect();
+        let mut saved = Self::read(&path)?.notes;
+        saved.retain(|note| !keys.contains(&note.message));
+        let mut indexes: Vec<_> = notes.keys().copied().collect();
+        indexes.sort_unstable();
+        saved.extend(indexes.into_iter().filter_map(|index| {
+            Some(Note {
+                message: MessageKey::new(log.messages().get(index)?),
+                text: notes[&index].clone(),
+            })
+        }));
+        let notes = saved;
+        let contents =
+            toml::to_string(&NotesFile { notes }).context("error serializing notes file")?;
+        std::fs::write(&path, contents)
+            .with_context(|| format!("error writing notes file: {}", path.display()))
+    }
+}
+
+/// Moves notes to the same messages in a new version of a log. Notes on
+/// messages that aren't in the new log, like when the log was replaced by a
+/// new session, are dropped.
+pub fn move_notes(
+    old_log: &Log,
+    new_log: &Log,
+    notes: HashMap<usize, String>,
+) -> HashMap<usize, String> {
+    notes
+        .into_iter()
+        .filter_map(|(index, text)| {
+            let key = MessageKey::new(old_log.messages().get(index)?);
+            let is_same = |message: &Message| MessageKey::new(message) == key;
+
+            // Messages usually keep their index, so check there first
+            let new_messages = new_log.messages();
+            let new_index = if new_messages.get(index).is_some_and(is_same) {
+                index
+            } else {
+                new_messages.iter().position(is_same)?
+            };
+            Some((new_index, text))
+        })
+        .collect()
+}
+
+/// Hashes text with 64-bit FNV-1a. Unlike the standard library's hasher, the
+/// hash is stable between versions of Rust, so it can be used in file names.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
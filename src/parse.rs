@@ -1,42 +1,140 @@
-use crate::ast::{Level, Message, Timestamp};
-use anyhow::Context;
+use crate::ast::{Level, Message, Timestamp, TimestampFormat};
+use anyhow::{bail, Context};
+use chrono::NaiveDate;
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_till, take_till1},
-    character::complete::{digit1, space0, space1},
-    combinator::{complete, map, map_res},
-    error::{FromExternalError, ParseError},
+    bytes::complete::{tag, tag_no_case, take_till, take_till1, take_while1},
+    character::complete::{char, digit1, space0, space1},
+    combinator::{complete, map, map_opt, map_res, opt, peek},
+    error::{ErrorKind, FromExternalError, ParseError},
     multi::fold_many0,
     sequence::{delimited, preceded, separated_pair, terminated, tuple},
     IResult,
 };
+use std::{
+    borrow::Cow,
+    fmt::{Display, Formatter, Write as _},
+};
 
-fn parse_message<'i, E>(i: &'i str) -> IResult<&'i str, Message<'i>, E>
+fn parse_timestamp<'i, E>(
+    format: TimestampFormat,
+) -> impl FnMut(&'i str) -> IResult<&'i str, Timestamp, E>
 where
     E: ParseError<&'i str> + FromExternalError<&'i str, anyhow::Error>,
 {
-    let ts = map_res(
-        tuple((digit1, tag(":"), digit1, tag(":"), digit1)),
-        |(hh, _, mm, _, ss): (&str, &str, &str, &str, &str)| {
-            let hour = hh.parse().context("invalid hour")?;
+    let separator = if format == TimestampFormat::Dots {
+        "."
+    } else {
+        ":"
+    };
+    let period = move |i: &'i str| -> IResult<&'i str, Option<&'i str>, E> {
+        if format == TimestampFormat::TwelveHour {
+            let am_pm = alt((tag_no_case("AM"), tag_no_case("PM")));
+            map(preceded(space1, am_pm), Some)(i)
+        } else {
+            Ok((i, None))
+        }
+    };
+
+    // Any format can have a date before the time
+    let date = opt(map_res(
+        terminated(
+            tuple((digit1, char('-'), digit1, char('-'), digit1)),
+            char(' '),
+        ),
+        |(yyyy, _, mm, _, dd): (&str, char, &str, char, &str)| {
+            let year = yyyy.parse().context("invalid year")?;
+            let month = mm.parse().context("invalid month")?;
+            let day = dd.parse().context("invalid day")?;
+            NaiveDate::from_ymd_opt(year, month, day).context("invalid date")
+        },
+    ));
+
+    map_res(
+        tuple((
+            date,
+            digit1,
+            tag(separator),
+            digit1,
+            tag(separator),
+            digit1,
+            period,
+        )),
+        |(date, hh, _, mm, _, ss, period): (
+            Option<NaiveDate>,
+            &str,
+            &str,
+            &str,
+            &str,
+            &str,
+            Option<&str>,
+        )| {
+            let hour: u8 = hh.parse().context("invalid hour")?;
             let minute = mm.parse().context("invalid minute")?;
             let second = ss.parse().context("invalid second")?;
+            let hour = match period {
+                None => hour,
+                Some(_) if !(1..=12).contains(&hour) => bail!("invalid hour"),
+                Some(period) if period.eq_ignore_ascii_case("PM") => hour % 12 + 12,
+                Some(_) => hour % 12,
+            };
             Ok(Timestamp {
+                date,
                 hour,
                 minute,
                 second,
             })
         },
+    )
+}
+
+fn parse_message<'i, E>(
+    format: TimestampFormat,
+) -> impl FnMut(&'i str) -> IResult<&'i str, Message<'i>, E>
+where
+    E: ParseError<&'i str> + FromExternalError<&'i str, anyhow::Error>,
+{
+    move |i| parse_message_with(i, format)
+}
+
+fn parse_message_with<'i, E>(
+    i: &'i str,
+    format: TimestampFormat,
+) -> IResult<&'i str, Message<'i>, E>
+where
+    E: ParseError<&'i str> + FromExternalError<&'i str, anyhow::Error>,
+{
+    let ts = parse_timestamp(format);
+    // Unknown levels are kept so messages from newer versions of SMAPI still
+    // parse
+    let level = map_opt(
+        take_while1(|c: char| c.is_ascii_uppercase()),
+        Level::from_header,
     );
-    let level = alt((
-        map(tag("TRACE"), |_| Level::Trace),
-        map(tag("DEBUG"), |_| Level::Debug),
-        map(tag("INFO"), |_| Level::Info),
-        map(tag("ALERT"), |_| Level::Alert),
-        map(tag("WARN"), |_| Level::Warn),
-        map(tag("ERROR"), |_| Level::Error),
-    ));
-    let source = take_till1(|c: char| c == ']');
+    // Content packs log under their own names, which often have brackets in
+    // them like "[CP] Pack". Verbose logging adds many of these messages, so
+    // the header ends at the first bracket that isn't part of the source.
+    let source = |i: &'i str| -> IResult<&'i str, &'i str, E> {
+        let mut depth = 0_usize;
+        let end = i
+            .find(|c: char| match c {
+                '[' => {
+                    depth += 1;
+                    false
+                }
+                ']' if depth > 0 => {
+                    depth -= 1;
+                    false
+                }
+                ']' | '\n' => true,
+                _ => false,
+            })
+            .unwrap_or(i.len());
+        if end == 0 {
+            return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::TakeTill1)));
+        }
+        Ok((&i[end..], &i[..end]))
+    };
     let contents = take_till(|c: char| c == '\n');
 
     let header = delimited(
@@ -48,7 +146,8 @@ where
         )),
         tag("]"),
     );
-    let message = separated_pair(header, tag(" "), contents);
+    // Some logs have no space or several spaces between the header and contents
+    let message = separated_pair(header, space0, contents);
 
     map(
         message,
@@ -61,7 +160,7 @@ where
     )(i)
 }
 
-fn parse_log<'i, E>(i: &'i str) -> IResult<&'i str, Vec<Message<'i>>, E>
+fn parse_log<'i, E>(i: &'i str, format: TimestampFormat) -> IResult<&'i str, Vec<Message<'i>>, E>
 where
     E: ParseError<&'i str> + FromExternalError<&'i str, anyhow::Error>,
 {
@@ -70,45 +169,438 @@ where
         Continued(&'i str),
     }
 
-    let parse_line_or_continuation = alt((
-        map(parse_message, ParsedLine::Start),
-        map(take_till(|c: char| c == '\n'), ParsedLine::Continued),
-    ));
-    let parse_log = fold_many0(
-        terminated(parse_line_or_continuation, tag("\n")),
-        || Ok(Vec::new()),
-        |acc, cur| {
-            let mut acc = acc?;
-            match cur {
-                ParsedLine::Start(message) => {
-                    acc.push(message);
-                    Ok(acc)
-                }
-                ParsedLine::Continued(continued_contents) => {
-                    let mut last = acc.pop().context("no message to continue")?;
-                    let mut contents = last.contents.into_owned();
-                    contents.push('\n');
-                    contents.push_str(continued_contents);
-                    last.contents = contents.into();
-                    acc.push(last);
-                    Ok(acc)
-                }
+    // Continued lines directly follow the line before them, so the contents
+    // can borrow all of the lines from the input instead of being copied
+    let join = move |contents: Cow<'i, str>, continued: &'i str| -> Cow<'i, str> {
+        let offset = |text: &str| (text.as_ptr() as usize).checked_sub(i.as_ptr() as usize);
+        if let (Cow::Borrowed(contents), Some(start), Some(continued_start)) =
+            (&contents, offset(&contents), offset(continued))
+        {
+            let end = continued_start + continued.len();
+            if continued_start == start + contents.len() + 1 && end <= i.len() {
+                return Cow::Borrowed(&i[start..end]);
+            }
+        }
+
+        let mut contents = contents.into_owned();
+        contents.push('\n');
+        contents.push_str(continued);
+        contents.into()
+    };
+    let add_line = move |acc: anyhow::Result<Vec<Message<'i>>>, cur: ParsedLine<'i>| {
+        let mut acc = acc?;
+        match cur {
+            ParsedLine::Start(message) => {
+                acc.push(message);
+                Ok(acc)
+            }
+            ParsedLine::Continued(continued_contents) => {
+                let mut last = acc.pop().context("no message to continue")?;
+                last.contents = join(last.contents, continued_contents);
+                acc.push(last);
+                Ok(acc)
             }
+        }
+    };
+
+    let parse_line_or_continuation = || {
+        alt((
+            map(parse_message(format), ParsedLine::Start),
+            map(take_till(|c: char| c == '\n'), ParsedLine::Continued),
+        ))
+    };
+    let parse_lines = fold_many0(
+        terminated(parse_line_or_continuation(), tag("\n")),
+        || Ok(Vec::new()),
+        add_line,
+    );
+
+    // The last line might not end with a newline, like in logs copied from
+    // the clipboard. If there's no message for it to continue, it's ignored
+    // since it could be the start of a message that's still being written.
+    let parse_last_line = preceded(
+        peek(take_till1(|c: char| c == '\n')),
+        parse_line_or_continuation(),
+    );
+    let parse_log = map(
+        tuple((parse_lines, opt(parse_last_line))),
+        move |(acc, last_line)| match (acc, last_line) {
+            (Ok(acc), Some(ParsedLine::Continued(_))) if acc.is_empty() => Ok(acc),
+            (acc, Some(last_line)) => add_line(acc, last_line),
+            (acc, None) => acc,
         },
     );
 
     map_res(parse_log, |messages| messages)(i)
 }
 
-fn parse_log_complete<'i, E>(i: &'i str) -> IResult<&'i str, Vec<Message<'i>>, E>
+fn parse_log_complete<'i, E>(
+    i: &'i str,
+    format: TimestampFormat,
+) -> IResult<&'i str, Vec<Message<'i>>, E>
 where
     E: ParseError<&'i str> + FromExternalError<&'i str, anyhow::Error>,
 {
-    complete(parse_log)(i)
+    complete(|i| parse_log(i, format))(i)
+}
+
+/// Detects how timestamps are written from the first messages in a log.
+/// Logs without any messages are assumed to use colons.
+pub fn detect_timestamp_format(contents: &str) -> TimestampFormat {
+    const SAMPLE_LINES: usize = 20;
+
+    let headers: Vec<&str> = contents
+        .lines()
+        .filter(|line| line.starts_with('['))
+        .take(SAMPLE_LINES)
+        .collect();
+    let matches = |format: TimestampFormat| {
+        headers
+            .iter()
+            .filter(|line| parse_message_with::<nom::error::Error<&str>>(line, format).is_ok())
+            .count()
+    };
+
+    // Prefer the earlier formats when several match equally well
+    TimestampFormat::DETECTABLE
+        .into_iter()
+        .rev()
+        .max_by_key(|&format| matches(format))
+        .unwrap_or(TimestampFormat::Colons)
 }
 
-pub fn parse<'i>(contents: &'i str) -> anyhow::Result<Vec<Message<'i>>> {
-    let (_, messages) = parse_log_complete::<nom::error::Error<&'i str>>(contents)
-        .map_err(|error| anyhow::anyhow!("{}", error))?;
+/// Parses the messages in a log. Headers with timestamps that don't match
+/// the format are treated as continuations of the previous message.
+pub fn parse(contents: &str, format: TimestampFormat) -> anyhow::Result<Vec<Message<'_>>> {
+    let format = match format {
+        TimestampFormat::Auto => detect_timestamp_format(contents),
+        format => format,
+    };
+    let (_, messages) =
+        parse_log_complete::<LogError<'_>>(contents, format).map_err(|error| match error {
+            nom::Err::Error(error) | nom::Err::Failure(error) => {
+                ParseFailure::new(contents, error.input, error.message)
+            }
+            nom::Err::Incomplete(_) => ParseFailure::new(contents, "", "unexpected end of log"),
+        })?;
     Ok(messages)
 }
+
+/// The error collected while parsing a log. Unlike nom's default error, this
+/// keeps the message from errors like a continued line with no message.
+#[derive(Clone, Debug)]
+struct LogError<'i> {
+    input: &'i str,
+    message: String,
+}
+
+impl<'i> ParseError<&'i str> for LogError<'i> {
+    fn from_error_kind(input: &'i str, kind: ErrorKind) -> Self {
+        LogError {
+            input,
+            message: kind.description().to_lowercase(),
+        }
+    }
+
+    fn append(_input: &'i str, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<'i> FromExternalError<&'i str, anyhow::Error> for LogError<'i> {
+    fn from_external_error(input: &'i str, _kind: ErrorKind, error: anyhow::Error) -> Self {
+        LogError {
+            input,
+            message: error.to_string(),
+        }
+    }
+}
+
+/// A log that couldn't be parsed, along with where parsing failed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseFailure {
+    /// The line parsing failed at, starting from 1.
+    pub line: usize,
+    /// The byte offset in the log parsing failed at.
+    pub offset: usize,
+    /// What went wrong.
+    pub message: String,
+    /// The lines around the line parsing failed at.
+    pub context: String,
+}
+
+impl ParseFailure {
+    /// The number of lines to include before and after the failing line.
+    const CONTEXT_LINES: usize = 3;
+
+    /// Describes a failure to parse the log at the start of `remaining`,
+    /// which must be the end of `contents`.
+    fn new(contents: &str, remaining: &str, message: impl Into<String>) -> Self {
+        let offset = contents.len() - remaining.len();
+        let line = contents[..offset].matches('\n').count() + 1;
+
+        // Number the lines so the failing line is easy to find
+        let first = line.saturating_sub(Self::CONTEXT_LINES).max(1);
+        let last = line + Self::CONTEXT_LINES;
+        let width = last.to_string().len();
+        let context = contents
+            .lines()
+            .enumerate()
+            .map(|(index, text)| (index + 1, text))
+            .skip(first - 1)
+            .take_while(|&(number, _)| number <= last)
+            .fold(String::new(), |mut context, (number, text)| {
+                let marker = if number == line { '>' } else { ' ' };
+                let _ = writeln!(context, "{marker} {number:>width$} | {text}");
+                context
+            });
+
+        ParseFailure {
+            line,
+            offset,
+            message: message.into(),
+            context,
+        }
+    }
+
+    /// Creates a report of the failure that can be included in a bug report.
+    pub fn report(&self) -> String {
+        format!(
+            "pufferwatch {version} couldn't parse a log at line {line} (byte {offset}): \
+             {message}\n\n{context}",
+            version = env!("CARGO_PKG_VERSION"),
+            line = self.line,
+            offset = self.offset,
+            message = self.message,
+            context = self.context,
+        )
+    }
+}
+
+impl Display for ParseFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at line {} (byte {})",
+            self.message, self.line, self.offset
+        )
+    }
+}
+
+impl std::error::Error for ParseFailure {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::GameEvent;
+
+    #[test]
+    fn parses_verbose_logs() {
+        let messages = parse(include_str!("../logs/verbose.txt"), TimestampFormat::Auto).unwrap();
+        let parsed = messages
+            .iter()
+            .map(|message| (message.level, message.source.as_ref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parsed,
+            [
+                (Level::Info, "SMAPI"),
+                (Level::Info, "SMAPI"),
+                (Level::Trace, "SMAPI"),
+                (Level::Trace, "SMAPI"),
+                (Level::Debug, "SMAPI"),
+                (Level::Trace, "SMAPI"),
+                (Level::Trace, "SMAPI"),
+                (Level::Trace, "SMAPI"),
+                (Level::Info, "SMAPI"),
+                (Level::Trace, "Content Patcher"),
+                (Level::Trace, "[CP] Seasonal Outfits"),
+                (Level::Trace, "[JA] Fruit Trees [Legacy]"),
+                (Level::Warn, "[JA] Fruit Trees [Legacy]"),
+                (Level::Info, "SMAPI"),
+            ]
+        );
+        assert_eq!(messages[10].contents, "Loaded 12 patches.");
+        assert_eq!(messages[12].contents.lines().count(), 2);
+        assert!(messages[6].contents.contains("[CP] Seasonal Outfits (from"));
+    }
+
+    #[test]
+    fn parses_irregular_spacing_after_header() {
+        let messages = parse(
+            include_str!("../logs/irregular_spacing.txt"),
+            TimestampFormat::Auto,
+        )
+        .unwrap();
+        let parsed = messages
+            .iter()
+            .map(|message| (message.level, message.source.as_ref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parsed,
+            [
+                (Level::Info, "SMAPI"),
+                (Level::Trace, "SMAPI"),
+                (Level::Debug, "SMAPI"),
+                (Level::Warn, "Content Patcher"),
+                (Level::Error, "Json Assets"),
+                (Level::Info, "SMAPI"),
+            ]
+        );
+        assert!(messages[0].contents.starts_with("SMAPI 3.18.1"));
+        assert!(messages[1].contents.starts_with("Log started"));
+        assert!(messages[2].contents.starts_with("Loading mods"));
+        assert!(messages[3].contents.starts_with("Content pack"));
+        assert!(messages[4].contents.starts_with("This mod failed"));
+        assert_eq!(messages[4].contents.lines().count(), 3);
+        assert!(messages[5].contents.is_empty());
+    }
+
+    #[test]
+    fn reports_where_parsing_failed() {
+        let error = parse(include_str!("../logs/invalid.txt"), TimestampFormat::Auto).unwrap_err();
+        let failure = error.downcast_ref::<ParseFailure>().unwrap();
+        assert_eq!((failure.line, failure.offset), (1, 0));
+        assert_eq!(failure.message, "no message to continue");
+
+        let report = failure.report();
+        assert!(report.contains("at line 1 (byte 0)"));
+        assert!(report.contains("> 1 | aisdhfouahg\n  2 | aosdjf\n"));
+
+        // Failures partway through a line point at where they happened
+        let contents = "[10:00:00 INFO  SMAPI] Starting\n[10:00:01 INFO";
+        let failure = ParseFailure::new(contents, &contents[42..], "unexpected end of log");
+        assert_eq!((failure.line, failure.offset), (2, 42));
+    }
+
+    #[test]
+    fn detects_timestamp_formats() {
+        let logs = [
+            "[13:05:09 INFO  SMAPI] Hello\n[13:05:10 WARN  SMAPI] World\n",
+            "[13.05.09 INFO  SMAPI] Hello\n[13.05.10 WARN  SMAPI] World\n",
+            "[1:05:09 PM INFO  SMAPI] Hello\n[1:05:10 pm WARN  SMAPI] World\n",
+        ];
+        for log in logs {
+            let messages = parse(log, TimestampFormat::Auto).unwrap();
+            let parsed = messages
+                .iter()
+                .map(|message| (message.timestamp.to_string(), message.level))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                parsed,
+                [
+                    ("13:05:09".to_string(), Level::Info),
+                    ("13:05:10".to_string(), Level::Warn)
+                ],
+                "{log}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_twelve_hour_timestamps() {
+        let log = "[12:00:00 AM INFO  SMAPI] Midnight\n[12:30:00 PM INFO  SMAPI] Noon\n";
+        let messages = parse(log, TimestampFormat::TwelveHour).unwrap();
+        let hours = messages
+            .iter()
+            .map(|message| message.timestamp.hour)
+            .collect::<Vec<_>>();
+        assert_eq!(hours, [0, 12]);
+    }
+
+    #[test]
+    fn parses_dates_before_timestamps() {
+        let log = "\
+[2024-03-01 23:59:58 INFO  SMAPI] Before midnight
+[2024-03-02 00:00:01 INFO  SMAPI] After midnight
+[00:00:02 INFO  SMAPI] No date
+";
+        let messages = parse(log, TimestampFormat::Auto).unwrap();
+        let dates: Vec<_> = messages
+            .iter()
+            .map(|message| message.timestamp.date)
+            .collect();
+        assert_eq!(
+            dates,
+            [
+                NaiveDate::from_ymd_opt(2024, 3, 1),
+                NaiveDate::from_ymd_opt(2024, 3, 2),
+                None
+            ]
+        );
+        assert_eq!(messages[1].timestamp.to_string(), "00:00:01");
+        assert_eq!(
+            messages[1]
+                .timestamp
+                .duration_since(messages[0].timestamp)
+                .as_secs(),
+            3
+        );
+
+        // Invalid dates aren't headers
+        let log = "[13:05:09 INFO  SMAPI] Hello\n[2024-13-01 13:05:10 INFO  SMAPI] World\n";
+        let messages = parse(log, TimestampFormat::Colons).unwrap();
+        assert_eq!(messages.len(), 1);
+    }
+
+    #[test]
+    fn treats_mismatched_headers_as_continuations() {
+        let log = "[13:05:09 INFO  SMAPI] Hello\n[13.05.10 WARN  SMAPI] World\n";
+        let messages = parse(log, TimestampFormat::Colons).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].contents, "Hello\n[13.05.10 WARN  SMAPI] World");
+    }
+
+    #[test]
+    fn recognizes_recording_markers() {
+        let log = "\
+[13:05:09 INFO  SMAPI] Hello
+[13:05:10 INFO  pufferwatch] Recording started by pufferwatch 0.2.0 on 2024-03-01
+[13:05:11 INFO  Json Assets] Recording started
+";
+        let messages = parse(log, TimestampFormat::Auto).unwrap();
+        let events: Vec<_> = messages.iter().map(Message::game_event).collect();
+        assert_eq!(events, [None, Some(GameEvent::RecordingStarted), None]);
+    }
+
+    #[test]
+    fn parses_last_line_without_newline() {
+        let log = "[13:05:09 INFO  SMAPI] Hello\n[13:05:10 WARN  SMAPI] World";
+        let messages = parse(log, TimestampFormat::Auto).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].level, Level::Warn);
+        assert_eq!(messages[1].contents, "World");
+
+        // Continuations are kept too
+        let log = "[13:05:09 ERROR SMAPI] Failed\nSystem.Exception";
+        let messages = parse(log, TimestampFormat::Auto).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].contents, "Failed\nSystem.Exception");
+
+        // A partly written first line isn't an error
+        let messages = parse("[13:05", TimestampFormat::Auto).unwrap();
+        assert!(messages.is_empty());
+    }
+
+    #[test]
+    fn parses_unknown_levels() {
+        let log = "[13:05:09 FATAL SMAPI] Crashed\n[13:05:10 NOTICE Json Assets] Hello\n";
+        let messages = parse(log, TimestampFormat::Auto).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].level.to_string(), "FATAL");
+        assert!(matches!(messages[0].level, Level::Other(_)));
+        assert_eq!(messages[0].source, "SMAPI");
+        assert_eq!(messages[1].level.to_string(), "NOTICE");
+        assert_eq!(messages[1].source, "Json Assets");
+
+        // Known levels aren't unknown, and long names aren't levels
+        let log = "[13:05:09 INFO  SMAPI] Hello\n[13:05:10 SOMETHINGELSE SMAPI] World\n";
+        let messages = parse(log, TimestampFormat::Auto).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].level, Level::Info);
+        assert_eq!(
+            messages[0].contents,
+            "Hello\n[13:05:10 SOMETHINGELSE SMAPI] World"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::grep::to_crossterm_color;
+use anyhow::Context;
+use crossterm::{
+    queue,
+    style::{Attribute, SetAttribute, SetBackgroundColor, SetForegroundColor},
+};
+use std::{io::Write, path::PathBuf};
+use tui::{
+    buffer::Buffer,
+    style::{Color, Modifier, Style},
+};
+use unicode_width::UnicodeWidthStr;
+
+/// Saves a rendered frame as ANSI-colored text in the working directory, so
+/// it can be shared with its layout and colors intact. Returns the path of
+/// the new file.
+pub fn save_screen(buffer: &Buffer) -> anyhow::Result<PathBuf> {
+    let file_name = chrono::Local::now()
+        .format("pufferwatch-%Y%m%d-%H%M%S.ans")
+        .to_string();
+    let path = std::env::current_dir()
+        .context("error getting working directory")?
+        .join(file_name);
+
+    let mut contents = Vec::new();
+    write_ansi(&mut contents, buffer).context("error formatting screen")?;
+    std::fs::write(&path, contents)
+        .with_context(|| format!("error writing screen: {}", path.display()))?;
+    Ok(path)
+}
+
+/// Writes the cells of a buffer as text, with escape codes for their colors
+/// and modifiers. Each row ends with a reset so rows can be printed on their
+/// own.
+pub fn write_ansi(output: &mut impl Write, buffer: &Buffer) -> std::io::Result<()> {
+    let area = buffer.area;
+    for y in area.top()..area.bottom() {
+        let mut style = None;
+        let mut covered = 0;
+        for x in area.left()..area.right() {
+            // Wide characters cover the cells after them
+            if covered > 0 {
+                covered -= 1;
+                continue;
+            }
+
+            let cell = buffer.get(x, y);
+            let cell_style = cell.style();
+            if style != Some(cell_style) {
+                write_style(output, cell_style)?;
+                style = Some(cell_style);
+            }
+            output.write_all(cell.symbol.as_bytes())?;
+            covered = cell.symbol.width().saturating_sub(1);
+        }
+
+        queue!(output, SetAttribute(Attribute::Reset))?;
+        writeln!(output)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the escape codes to switch to a style.
+fn write_style(output: &mut impl Write, style: Style) -> std::io::Result<()> {
+    const ATTRIBUTES: [(Modifier, Attribute); 9] = [
+        (Modifier::BOLD, Attribute::Bold),
+        (Modifier::DIM, Attribute::Dim),
+        (Modifier::ITALIC, Attribute::Italic),
+        (Modifier::UNDERLINED, Attribute::Underlined),
+        (Modifier::SLOW_BLINK, Attribute::SlowBlink),
+        (Modifier::RAPID_BLINK, Attribute::RapidBlink),
+        (Modifier::REVERSED, Attribute::Reverse),
+        (Modifier::HIDDEN, Attribute::Hidden),
+        (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+    ];
+
+    // Colors are already reset along with the attributes
+    queue!(output, SetAttribute(Attribute::Reset))?;
+    if let Some(fg) = style.fg.filter(|&fg| fg != Color::Reset) {
+        queue!(output, SetForegroundColor(to_crossterm_color(fg)))?;
+    }
+    if let Some(bg) = style.bg.filter(|&bg| bg != Color::Reset) {
+        queue!(output, SetBackgroundColor(to_crossterm_color(bg)))?;
+    }
+    for (modifier, attribute) in ATTRIBUTES {
+        if style.add_modifier.contains(modifier) {
+            queue!(output, SetAttribute(attribute))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tui::layout::Rect;
+
+    #[test]
+    fn writes_cells_with_styles() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 2));
+        buffer.set_string(0, 0, "ab", Style::default().fg(Color::Red));
+        buffer.set_string(2, 0, "cd", Style::default().fg(Color::Red));
+        buffer.set_string(0, 1, "ok", Style::default().add_modifier(Modifier::BOLD));
+
+        let mut output = Vec::new();
+        write_ansi(&mut output, &buffer).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            output,
+            "\x1b[0m\x1b[38;5;1mabcd\x1b[0m  \x1b[0m\n\
+             \x1b[0m\x1b[1mok\x1b[0m    \x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn skips_cells_covered_by_wide_characters() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 4, 1));
+        buffer.set_string(0, 0, "日x", Style::default());
+
+        let mut output = Vec::new();
+        write_ansi(&mut output, &buffer).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output, "\x1b[0m日x \x1b[0m\n");
+    }
+}
@@ -0,0 +1,397 @@
+use crate::{
+    alias::Aliases,
+    ast::Level,
+    classify::{Category, MessageClassifier, PatternClassifier},
+    widgets::{ColumnLayout, CopyFormat, SelectedTab, SourceHistogram, Theme},
+};
+use anyhow::{bail, Context};
+use serde::Deserialize;
+use std::{io::ErrorKind, path::PathBuf};
+use tracing::{instrument, trace, warn};
+use tui::style::Color;
+
+/// User settings loaded from the config file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Settings {
+    /// Overrides for the colors of the selected theme.
+    pub theme: ThemeSettings,
+    /// The prompt displayed before commands sent to SMAPI.
+    pub command_prompt: String,
+    /// The maximum number of times per second to scroll to new lines while
+    /// following the end of the log. Set to 0 to scroll on every update.
+    pub auto_scroll_rate: u32,
+    /// Sources whose info messages are hidden when only showing important
+    /// messages.
+    pub noisy_sources: Vec<String>,
+    /// What to include when copying a message.
+    pub copy_format: CopyFormat,
+    /// Joins the lines of multi-line messages with this separator when
+    /// copying or exporting them, so each message is on one line. Without a
+    /// separator, the lines are kept as they are.
+    pub join_lines: Option<String>,
+    /// Whether jumping to the next match, error, or message past the end of
+    /// the log wraps around to the other end. This is on by default.
+    pub wrap_navigation: bool,
+    /// Ring the terminal bell when new messages with these levels arrive.
+    pub bell_levels: Vec<Level>,
+    /// The columns shown before each message in the log tab, in order.
+    pub columns: ColumnLayout,
+    /// The maximum width of the source column in the log tab. Longer sources
+    /// are cut off with an ellipsis.
+    pub max_source_width: Option<usize>,
+    /// The maximum width of each line of a message in the log tab. Longer
+    /// lines are cut off with an ellipsis, except for the message at the top
+    /// of the log, which is shown in full.
+    pub max_message_width: Option<usize>,
+    /// Rules for tagging messages with a category, which colors them in the
+    /// log tab and lets them be filtered by category.
+    pub classifiers: Vec<ClassifierSettings>,
+    /// Whether to show a spinner while following a log, so it's clear
+    /// pufferwatch is still running when nothing is being logged.
+    pub heartbeat: bool,
+    /// Short names for commands sent to SMAPI.
+    pub aliases: Aliases,
+    /// The number of rows the controls bar at the bottom of the screen uses.
+    /// Controls that don't fit are split into pages.
+    pub controls_rows: u16,
+    /// The tab that is selected when pufferwatch starts, either `log`, `raw`,
+    /// or `summary`.
+    pub startup_tab: SelectedTab,
+    /// Whether to expand the message at the top of the log while the other
+    /// messages are collapsed.
+    pub auto_expand: bool,
+    /// Whether Ctrl+C quits even while typing a command. By default, it
+    /// clears the command instead, like in a shell.
+    pub ctrl_c_always_quits: bool,
+    /// Whether notes on messages are saved, so they're restored when the
+    /// same log is opened again.
+    pub persist_notes: bool,
+    /// Sources with fewer messages than this are grouped together as
+    /// "(other)" in the filters, so they can be toggled at once. Set to 0 to
+    /// list every source.
+    pub group_sources_below: usize,
+    /// How many seconds the source of the latest error is highlighted for
+    /// while following a log. Pressing a key also clears it. Set to 0 to turn
+    /// the highlight off.
+    pub error_highlight_seconds: u64,
+    /// The number of bars the session is split into in the histogram of a
+    /// source's messages. Each bar is colored by the most severe level in it,
+    /// using the theme's level colors. This must be between 1 and 200.
+    pub histogram_buckets: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            theme: ThemeSettings::default(),
+            command_prompt: "> ".to_string(),
+            auto_scroll_rate: 4,
+            noisy_sources: [
+                "Content Patcher",
+                "Json Assets",
+                "SpaceCore",
+                "Farm Type Manager",
+                "Expanded Preconditions Utility",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            copy_format: CopyFormat::default(),
+            join_lines: None,
+            wrap_navigation: true,
+            bell_levels: Vec::new(),
+            columns: ColumnLayout::default(),
+            max_source_width: None,
+            max_message_width: None,
+            classifiers: Vec::new(),
+            heartbeat: true,
+            aliases: Aliases::default(),
+            controls_rows: 1,
+            startup_tab: SelectedTab::default(),
+            auto_expand: false,
+            ctrl_c_always_quits: false,
+            persist_notes: false,
+            group_sources_below: 0,
+            error_highlight_seconds: 10,
+            histogram_buckets: SourceHistogram::DEFAULT_BUCKETS,
+        }
+    }
+}
+
+impl Settings {
+    /// Gets the path to the config file.
+    pub fn path() -> Option<PathBuf> {
+        let mut path = dirs::config_dir()?;
+        path.push("pufferwatch/config.toml");
+        Some(path)
+    }
+
+    /// Loads the config file. If the config file does not exist, the default
+    /// settings are returned instead.
+    #[instrument(level = "trace")]
+    pub fn load() -> anyhow::Result<Self> {
+        let path = Self::path().context("unable to find config file path")?;
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                trace!(?path, "config file not found");
+                return Ok(Settings::default());
+            }
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("error reading config file: {}", path.display()))
+            }
+        };
+
+        toml::from_str(&contents)
+            .map(Settings::validate)
+            .with_context(|| format!("error parsing config file: {}", path.display()))
+    }
+
+    /// Fixes settings that can't be used as they are, warning about each one.
+    fn validate(mut self) -> Self {
+        let buckets = self
+            .histogram_buckets
+            .clamp(1, SourceHistogram::MAX_BUCKETS);
+        if buckets != self.histogram_buckets {
+            warn!(
+                histogram_buckets = self.histogram_buckets,
+                buckets, "histogram-buckets is out of range"
+            );
+            self.histogram_buckets = buckets;
+        }
+
+        if self.columns.is_empty() {
+            warn!("columns is empty, using the default columns");
+            self.columns = ColumnLayout::default();
+        } else if self.columns.remove_duplicates() {
+            warn!(columns = ?self.columns, "columns has duplicates, only the first of each is used");
+        }
+
+        self
+    }
+
+    /// Creates the classifiers configured in the config file.
+    pub fn classifiers(&self) -> Vec<Box<dyn MessageClassifier>> {
+        self.classifiers
+            .iter()
+            .map(|classifier| Box::new(classifier.to_classifier()) as Box<dyn MessageClassifier>)
+            .collect()
+    }
+}
+
+/// Overrides for the colors of a theme.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ThemeSettings {
+    /// The color of each log level.
+    pub levels: LevelColorSettings,
+    /// The color of message sources.
+    pub source: Option<ColorSetting>,
+    /// The background color of the selected item in lists.
+    pub selection: Option<ColorSetting>,
+    /// The text color of the controls bar.
+    pub controls: Option<ColorSetting>,
+    /// The background color of the controls bar.
+    pub controls_background: Option<ColorSetting>,
+    /// The background color of enabled filters in the filters bar.
+    pub filter_enabled: Option<ColorSetting>,
+    /// The background color of disabled filters in the filters bar.
+    pub filter_disabled: Option<ColorSetting>,
+    /// The background color of text matching the search.
+    pub search_match: Option<ColorSetting>,
+}
+
+impl ThemeSettings {
+    /// Applies the overrides to a theme. RGB colors are converted to the
+    /// closest color in the 256 color palette if the terminal doesn't support
+    /// true color.
+    pub fn apply(&self, theme: &mut Theme) {
+        let truecolor = supports_truecolor();
+        let overrides = [
+            (&mut theme.level_colors.trace, self.levels.trace),
+            (&mut theme.level_colors.debug, self.levels.debug),
+            (&mut theme.level_colors.info, self.levels.info),
+            (&mut theme.level_colors.alert, self.levels.alert),
+            (&mut theme.level_colors.warn, self.levels.warn),
+            (&mut theme.level_colors.error, self.levels.error),
+            (&mut theme.level_colors.other, self.levels.other),
+            (&mut theme.source_color, self.source),
+            (&mut theme.selection_color, self.selection),
+            (&mut theme.controls_color, self.controls),
+            (&mut theme.controls_background, self.controls_background),
+            (&mut theme.filter_enabled_color, self.filter_enabled),
+            (&mut theme.filter_disabled_color, self.filter_disabled),
+            (&mut theme.search_match_color, self.search_match),
+        ];
+        for (color, setting) in overrides {
+            if let Some(setting) = setting {
+                *color = setting.to_color(truecolor);
+            }
+        }
+    }
+}
+
+/// A rule for tagging messages with a category.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ClassifierSettings {
+    /// The name of the category.
+    pub category: String,
+    /// The color to show messages in the category with.
+    pub color: ColorSetting,
+    /// Only tag messages from this source.
+    pub source: Option<String>,
+    /// Only tag messages containing this text.
+    pub contains: Option<String>,
+}
+
+impl ClassifierSettings {
+    /// Creates a classifier from the rule.
+    pub fn to_classifier(&self) -> PatternClassifier {
+        PatternClassifier {
+            category: Category {
+                name: self.category.clone(),
+                color: self.color.to_color(supports_truecolor()),
+            },
+            source: self.source.clone(),
+            contains: self.contains.clone(),
+        }
+    }
+}
+
+/// Overrides for the color of each log level.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct LevelColorSettings {
+    pub trace: Option<ColorSetting>,
+    pub debug: Option<ColorSetting>,
+    pub info: Option<ColorSetting>,
+    pub alert: Option<ColorSetting>,
+    pub warn: Option<ColorSetting>,
+    pub error: Option<ColorSetting>,
+    /// The color of levels pufferwatch doesn't know about.
+    pub other: Option<ColorSetting>,
+}
+
+/// A color in the config file. Colors can be one of the 16 named terminal
+/// colors (like `"light-blue"`), an index into the 256 color palette (like
+/// `"208"`), or an RGB color (like `"#ff8800"`).
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ColorSetting(pub Color);
+
+impl ColorSetting {
+    /// Gets the color to use, converting RGB colors to the closest color in
+    /// the 256 color palette if the terminal doesn't support true color.
+    pub fn to_color(self, truecolor: bool) -> Color {
+        if truecolor {
+            self.0
+        } else {
+            to_ansi_256(self.0)
+        }
+    }
+}
+
+impl TryFrom<String> for ColorSetting {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        // RGB
+        if let Some(hex) = value.strip_prefix('#') {
+            if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                bail!("expected a color in the format #rrggbb, but found '{value}'");
+            }
+
+            let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16);
+            return Ok(ColorSetting(Color::Rgb(
+                channel(0)?,
+                channel(2)?,
+                channel(4)?,
+            )));
+        }
+
+        // 256 color palette
+        if value.chars().all(|c| c.is_ascii_digit()) {
+            let index = value
+                .parse()
+                .with_context(|| format!("color index must be from 0 to 255, but found {value}"))?;
+            return Ok(ColorSetting(Color::Indexed(index)));
+        }
+
+        // Named colors
+        let name = value.to_ascii_lowercase().replace(['-', '_', ' '], "");
+        let color = match name.as_str() {
+            "reset" => Color::Reset,
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "gray" | "grey" => Color::Gray,
+            "darkgray" | "darkgrey" => Color::DarkGray,
+            "lightred" => Color::LightRed,
+            "lightgreen" => Color::LightGreen,
+            "lightyellow" => Color::LightYellow,
+            "lightblue" => Color::LightBlue,
+            "lightmagenta" => Color::LightMagenta,
+            "lightcyan" => Color::LightCyan,
+            "white" => Color::White,
+            _ => bail!("unknown color '{value}'"),
+        };
+        Ok(ColorSetting(color))
+    }
+}
+
+/// Checks whether the terminal advertises support for 24-bit colors.
+fn supports_truecolor() -> bool {
+    std::env::var("COLORTERM")
+        .is_ok_and(|value| value.eq_ignore_ascii_case("truecolor") || value == "24bit")
+}
+
+/// Converts RGB colors to the closest color in the 256 color palette. Other
+/// colors are returned unchanged.
+fn to_ansi_256(color: Color) -> Color {
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+
+    // Closest color in the 6x6x6 color cube
+    let to_cube = |value: u8| -> u8 {
+        match value {
+            0..=47 => 0,
+            48..=114 => 1,
+            _ => (value - 35) / 40,
+        }
+    };
+    let from_cube = |index: u8| -> u8 {
+        if index == 0 {
+            0
+        } else {
+            55 + index * 40
+        }
+    };
+    let cube = (to_cube(r), to_cube(g), to_cube(b));
+    let cube_color = (from_cube(cube.0), from_cube(cube.1), from_cube(cube.2));
+
+    // Closest color in the grayscale ramp
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray_index = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray = 8 + gray_index * 10;
+
+    // Use whichever is closer
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        [(r, r2), (g, g2), (b, b2)]
+            .into_iter()
+            .map(|(a, b)| (i32::from(a) - i32::from(b)).pow(2))
+            .sum::<i32>()
+    };
+    if distance((gray, gray, gray)) < distance(cube_color) {
+        Color::Indexed(232 + gray_index)
+    } else {
+        Color::Indexed(16 + 36 * cube.0 + 6 * cube.1 + cube.2)
+    }
+}
@@ -1,42 +1,82 @@
-use crate::log::Log;
+use crate::{
+    archive,
+    ast::{GameEvent, Level, Message, TimestampFormat},
+    log::Log,
+    parse::detect_timestamp_format,
+    widgets::LogFilters,
+};
 use anyhow::Context;
+use chrono::Local;
 use crossbeam::channel::Receiver;
+use itertools::Itertools;
 use notify::{
     event::{MetadataKind, ModifyKind},
     Config, Event, EventKind, PollWatcher, RecursiveMode, Watcher,
 };
+use reqwest::{blocking::Client, Url};
 use std::{
-    fmt::Debug,
-    io::{BufRead, BufReader, Read},
+    collections::hash_map::DefaultHasher,
+    fmt::{Debug, Write as _},
+    fs::{File, Metadata},
+    hash::{Hash, Hasher},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
     path::{Path, PathBuf},
+    process::{Child, Stdio},
     thread::JoinHandle,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tracing::{debug, debug_span, info, instrument, trace, warn};
 
 pub trait LogSource {
     fn update_log(&mut self, log: &Log) -> anyhow::Result<Option<Log>>;
+
+    /// Gets the path to the file this log is read from, if any.
+    fn path(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Gets how often the log is checked for changes, if it's checked by
+    /// polling.
+    fn poll_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Checks whether the log is followed for new messages after it's first
+    /// read.
+    fn is_following(&self) -> bool;
 }
 
 #[derive(Debug)]
-pub struct StaticLogSource;
+pub struct StaticLogSource {
+    path: Option<PathBuf>,
+}
 
 impl StaticLogSource {
-    /// Creates a new static log source from a file path.
+    /// Creates a new static log source from a file path, starting at a byte
+    /// offset in the file.
     #[instrument(skip_all)]
-    pub fn from_file(path: &Path) -> anyhow::Result<(Self, Log)> {
-        info!(?path, "creating static log source");
-        Log::parse_file(path)
-            .map(|log| (StaticLogSource, log))
+    pub fn from_file(
+        path: &Path,
+        offset: u64,
+        format: TimestampFormat,
+    ) -> anyhow::Result<(Self, Log)> {
+        info!(?path, offset, "creating static log source");
+        Log::parse_file_from(path, offset, format)
+            .map(|log| {
+                let source = StaticLogSource {
+                    path: Some(path.to_path_buf()),
+                };
+                (source, log)
+            })
             .context("error parsing log")
     }
 
     /// Creates a new static log source from a string.
     #[instrument(skip_all)]
-    pub fn from_string(raw: String) -> anyhow::Result<(Self, Log)> {
+    pub fn from_string(raw: String, format: TimestampFormat) -> anyhow::Result<(Self, Log)> {
         info!(len=%raw.len(), "creating static log source");
-        Log::parse(raw)
-            .map(|log| (StaticLogSource, log))
+        Log::parse_as(raw, format)
+            .map(|log| (StaticLogSource { path: None }, log))
             .context("Error parsing log")
     }
 }
@@ -45,6 +85,14 @@ impl LogSource for StaticLogSource {
     fn update_log(&mut self, _log: &Log) -> anyhow::Result<Option<Log>> {
         Ok(None)
     }
+
+    fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    fn is_following(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
@@ -56,13 +104,42 @@ enum FileUpdate {
 #[derive(Debug)]
 pub struct FollowedLogSource {
     path: PathBuf,
+    offset: u64,
+    format: TimestampFormat,
+    /// Where the log has been read up to in the file, or [`None`] if the
+    /// whole log needs to be read again.
+    position: Option<ReadPosition>,
     _watcher: PollWatcher,
     rx: Receiver<FileUpdate>,
 }
 
 impl FollowedLogSource {
-    pub fn new(path: PathBuf) -> anyhow::Result<(Self, Log)> {
-        info!(?path, "creating followed log source");
+    const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+    /// Follows a log file. If `compare_contents` is set, the file is hashed
+    /// each time it's polled to detect changes. Otherwise, only its size and
+    /// modification time are checked, which is much cheaper for large files
+    /// but can miss edits that don't change either.
+    pub fn new(
+        path: PathBuf,
+        compare_contents: bool,
+        format: TimestampFormat,
+    ) -> anyhow::Result<(Self, Log)> {
+        Self::from_offset(path, 0, compare_contents, format)
+    }
+
+    /// Follows a log file, ignoring everything before a byte offset in the
+    /// file.
+    pub fn from_offset(
+        path: PathBuf,
+        offset: u64,
+        compare_contents: bool,
+        format: TimestampFormat,
+    ) -> anyhow::Result<(Self, Log)> {
+        info!(
+            ?path,
+            offset, compare_contents, "creating followed log source"
+        );
 
         // Create file watcher
         let (tx, rx) = crossbeam::channel::bounded(10);
@@ -101,8 +178,8 @@ impl FollowedLogSource {
                 }
             },
             Config::default()
-                .with_poll_interval(Duration::from_secs(2))
-                .with_compare_contents(true),
+                .with_poll_interval(Self::POLL_INTERVAL)
+                .with_compare_contents(compare_contents),
         )
         .context("error creating file watcher")?;
         watcher
@@ -110,61 +187,360 @@ impl FollowedLogSource {
             .context("error starting file watcher")?;
 
         // Parse log
-        let log = Log::parse_file(&path).context("error parsing log file")?;
-        let source = FollowedLogSource {
+        let mut source = FollowedLogSource {
             path,
+            offset,
+            format,
+            position: None,
             _watcher: watcher,
             rx,
         };
+        let log = source.read_whole_file()?;
         Ok((source, log))
     }
+
+    /// Parses the whole file again.
+    fn read_whole_file(&mut self) -> anyhow::Result<Log> {
+        let metadata = std::fs::metadata(&self.path).context("error reading log file metadata")?;
+        let (contents, end) = Log::read_file_from(&self.path, self.offset)?;
+        let log = Log::parse_as(contents, self.format).context("error parsing log file")?;
+        self.position = Some(ReadPosition::new(end, &metadata, log.raw()));
+        Ok(log)
+    }
+
+    /// Reads the changes to the file. If text was only added to the end of
+    /// the file, just the new text is parsed and added to the log. Otherwise,
+    /// the whole file is parsed again.
+    fn read_changes(&mut self, log: &Log) -> anyhow::Result<Log> {
+        let metadata = std::fs::metadata(&self.path).context("error reading log file metadata")?;
+        let len = metadata.len();
+        let identity = FileIdentity::of(&metadata);
+        match &self.position {
+            // Logs without messages are cheap to parse again, and might need
+            // to skip to the first message after the offset
+            Some(position)
+                if len > position.end
+                    && identity == position.identity
+                    && !log.messages().is_empty() =>
+            {
+                let end = position.end;
+                if let Some(appended) = self.read_appended(position, len)? {
+                    let new_log = log.append(&appended, self.format)?;
+                    self.position = Some(ReadPosition::new(
+                        end + appended.len() as u64,
+                        &metadata,
+                        new_log.raw(),
+                    ));
+                    return Ok(new_log);
+                }
+            }
+            _ => {}
+        }
+
+        // The file shrank, was replaced, or was edited in place
+        debug!(?self.position, len, "parsing whole log file");
+        self.read_whole_file()
+    }
+
+    /// Reads the text added to the file after the position the log was read
+    /// up to. A character cut off at the end is left to be read next time.
+    /// If the text before the position changed, nothing is read.
+    fn read_appended(&self, position: &ReadPosition, len: u64) -> anyhow::Result<Option<String>> {
+        let mut log_file = File::open(&self.path)
+            .with_context(|| format!("Failed to open log file: {}", self.path.display()))?;
+        let tail_start = position.end - position.tail_len as u64;
+        log_file
+            .seek(SeekFrom::Start(tail_start))
+            .context("Failed to seek in log file")?;
+        let mut bytes = Vec::new();
+        log_file
+            .take(len - tail_start)
+            .read_to_end(&mut bytes)
+            .context("Failed to read log file")?;
+
+        // Check that the end of what was read before is still the same
+        if bytes.len() < position.tail_len
+            || checksum(&bytes[..position.tail_len]) != position.tail_checksum
+        {
+            debug!("log file changed before the end of the log");
+            return Ok(None);
+        }
+        let bytes = bytes.split_off(position.tail_len);
+
+        let appended = match String::from_utf8(bytes) {
+            Ok(appended) => Ok(appended),
+            Err(error) if error.utf8_error().error_len().is_none() => {
+                let valid = error.utf8_error().valid_up_to();
+                let mut bytes = error.into_bytes();
+                bytes.truncate(valid);
+                String::from_utf8(bytes).context("Failed to read log file")
+            }
+            Err(error) => Err(error).context("Failed to read log file"),
+        };
+        appended.map(Some)
+    }
+}
+
+/// Where a log has been read up to in a file, and what the file looked like
+/// there. Changes that aren't just text added after this point mean the file
+/// needs to be read again.
+#[derive(Clone, Debug)]
+struct ReadPosition {
+    /// The byte offset in the file that the log has been read up to.
+    end: u64,
+    /// Which file was read, to tell when it's replaced by another file.
+    identity: FileIdentity,
+    /// The number of bytes before `end` that are checked for changes.
+    tail_len: usize,
+    /// A checksum of the bytes before `end`.
+    tail_checksum: u64,
+}
+
+impl ReadPosition {
+    /// The most bytes before the end that are checked for changes.
+    const MAX_TAIL_LEN: usize = 256;
+
+    /// Creates the position after reading a file up to `end`. The raw log
+    /// must be the text just before `end` in the file.
+    fn new(end: u64, metadata: &Metadata, raw: &str) -> Self {
+        let tail = &raw.as_bytes()[raw.len().saturating_sub(Self::MAX_TAIL_LEN)..];
+        ReadPosition {
+            end,
+            identity: FileIdentity::of(metadata),
+            tail_len: tail.len(),
+            tail_checksum: checksum(tail),
+        }
+    }
+}
+
+/// Identifies a file, so a file that's deleted and created again at the
+/// same path is treated as a different file. Platforms without a way to
+/// identify files only rely on the checksum.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct FileIdentity(u64, u64);
+
+impl FileIdentity {
+    #[cfg(unix)]
+    fn of(metadata: &Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+        FileIdentity(metadata.dev(), metadata.ino())
+    }
+
+    #[cfg(windows)]
+    fn of(metadata: &Metadata) -> Self {
+        use std::os::windows::fs::MetadataExt;
+        FileIdentity(0, metadata.creation_time())
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn of(_metadata: &Metadata) -> Self {
+        FileIdentity(0, 0)
+    }
+}
+
+fn checksum(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
 }
 
 impl LogSource for FollowedLogSource {
     #[instrument(skip_all, fields(path=?self.path))]
-    fn update_log(&mut self, _log: &Log) -> anyhow::Result<Option<Log>> {
+    fn update_log(&mut self, log: &Log) -> anyhow::Result<Option<Log>> {
         // Check for updates
-        self.rx.try_iter().try_fold(None, |new_log, event| {
-            let _span = debug_span!("file_event", file_event=?event).entered();
-            trace!("handling file event");
-            match event {
-                FileUpdate::Removed => {
-                    // Reset
-                    Ok(Some(Log::empty()))
+        let events: Vec<_> = self.rx.try_iter().collect();
+        events
+            .into_iter()
+            .try_fold(None, |new_log: Option<Log>, event| {
+                let _span = debug_span!("file_event", file_event=?event).entered();
+                trace!("handling file event");
+                match event {
+                    FileUpdate::Removed => {
+                        // Reset
+                        self.position = None;
+                        Ok(Some(Log::empty()))
+                    }
+                    FileUpdate::Updated => {
+                        // Try to parse log
+                        let current = new_log.as_ref().unwrap_or(log);
+                        match self.read_changes(current) {
+                            Ok(log) => Ok(Some(log)),
+                            Err(error) => {
+                                // Don't error out on failure - the file might be in the process of
+                                // being written to.
+                                warn!(?error, "error parsing log file");
+                                Ok(new_log)
+                            }
+                        }
+                    }
                 }
-                FileUpdate::Updated => {
-                    // Try to parse log
-                    let log = if let Ok(log) = Log::parse_file(&self.path) {
-                        log
-                    } else {
-                        // Don't error out on failure - the file might be in the process of being
-                        // written to.
-                        warn!("error parsing log file");
-                        return Ok(new_log);
-                    };
+            })
+    }
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+
+    fn poll_interval(&self) -> Option<Duration> {
+        Some(Self::POLL_INTERVAL)
+    }
+
+    fn is_following(&self) -> bool {
+        true
+    }
+}
+
+/// Follows whichever log file in a directory was most recently written to.
+#[derive(Debug)]
+pub struct DirectoryLogSource {
+    _watcher: PollWatcher,
+    rx: Receiver<PathBuf>,
+    current: Option<FollowedLogSource>,
+    compare_contents: bool,
+    format: TimestampFormat,
+}
+
+impl DirectoryLogSource {
+    /// How much more recently another log file has to be written than the
+    /// current one to switch to it. Without this, two log files written at
+    /// about the same time would keep switching back and forth.
+    const SWITCH_MARGIN: Duration = Duration::from_secs(2);
+
+    /// Watches a directory for log files, starting with the given log file
+    /// if it exists.
+    pub fn new(
+        dir: &Path,
+        initial: PathBuf,
+        compare_contents: bool,
+        format: TimestampFormat,
+    ) -> anyhow::Result<(Self, Log)> {
+        info!(?dir, ?initial, "creating directory log source");
+
+        // Create directory watcher
+        let (tx, rx) = crossbeam::channel::bounded(10);
+        let mut watcher = PollWatcher::new(
+            move |event: notify::Result<Event>| {
+                let _span = debug_span!("directory_watcher", watcher_event=?event).entered();
+                let event = match event {
+                    Ok(event) => event,
+                    Err(error) => {
+                        warn!(?error, "error watching log directory");
+                        return;
+                    }
+                };
+
+                // Only log files that were written to are interesting
+                if !matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Any
+                ) {
+                    return;
+                }
+                for path in event.paths {
+                    if path.extension().is_some_and(|extension| extension == "txt") {
+                        let _ = tx.send(path);
+                    }
+                }
+            },
+            Config::default().with_poll_interval(FollowedLogSource::POLL_INTERVAL),
+        )
+        .context("error creating directory watcher")?;
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .context("error starting directory watcher")?;
+
+        // Follow the initial log file if SMAPI already created it
+        let (current, log) = if initial.is_file() {
+            let (current, log) = FollowedLogSource::new(initial, compare_contents, format)?;
+            (Some(current), log)
+        } else {
+            (None, Log::empty())
+        };
+        let source = DirectoryLogSource {
+            _watcher: watcher,
+            rx,
+            current,
+            compare_contents,
+            format,
+        };
+        Ok((source, log))
+    }
 
-                    Ok(Some(log))
+    /// Checks whether a log file was written to enough after the current one
+    /// to switch to it. Files that can't be checked aren't switched to,
+    /// unless the current file is gone.
+    fn is_newer(current: &Path, other: &Path) -> bool {
+        let modified =
+            |path: &Path| std::fs::metadata(path).and_then(|metadata| metadata.modified());
+        match (modified(current), modified(other)) {
+            (Ok(current), Ok(other)) => other
+                .duration_since(current)
+                .is_ok_and(|newer_by| newer_by > Self::SWITCH_MARGIN),
+            (Err(_), Ok(_)) => true,
+            (_, Err(_)) => false,
+        }
+    }
+}
+
+impl LogSource for DirectoryLogSource {
+    #[instrument(skip_all)]
+    fn update_log(&mut self, log: &Log) -> anyhow::Result<Option<Log>> {
+        // Switch to whichever log file was written to last
+        let mut new_log = None;
+        for path in self.rx.try_iter() {
+            if self.current.as_ref().is_some_and(|current| {
+                current.path == path || !Self::is_newer(&current.path, &path)
+            }) {
+                continue;
+            }
+
+            debug!(?path, "switching to new log file");
+            match FollowedLogSource::new(path, self.compare_contents, self.format) {
+                Ok((current, log)) => {
+                    self.current = Some(current);
+                    new_log = Some(log);
                 }
+                Err(error) => warn!(?error, "error following new log file"),
             }
-        })
+        }
+
+        // Check for updates to the current log file
+        let Some(current) = self.current.as_mut() else {
+            return Ok(new_log);
+        };
+        let updated = current.update_log(new_log.as_ref().unwrap_or(log))?;
+        Ok(updated.or(new_log))
+    }
+
+    fn path(&self) -> Option<&Path> {
+        self.current.as_ref().and_then(LogSource::path)
+    }
+
+    fn poll_interval(&self) -> Option<Duration> {
+        Some(FollowedLogSource::POLL_INTERVAL)
+    }
+
+    fn is_following(&self) -> bool {
+        true
     }
 }
 
 #[derive(Debug)]
 pub struct ReaderLogSource {
     unparsed: String,
+    format: TimestampFormat,
     rx: Receiver<anyhow::Result<String>>,
     _reader_thread: JoinHandle<()>,
 }
 
 impl ReaderLogSource {
-    pub fn new<R: Read + Send + 'static>(reader: R) -> Self {
+    pub fn new<R: Read + Send + 'static>(reader: R, format: TimestampFormat) -> Self {
         let (tx, rx) = crossbeam::channel::unbounded::<anyhow::Result<String>>();
         let mut reader = BufReader::new(reader);
         let reader_thread = std::thread::spawn(move || loop {
             let mut buffer = String::new();
             match reader.read_line(&mut buffer) {
-                Ok(0) => continue,
+                Ok(0) => {}
                 Ok(_) => tx.send(Ok(buffer)).unwrap(),
                 Err(error) => tx.send(Err(error.into())).unwrap(),
             }
@@ -172,13 +548,14 @@ impl ReaderLogSource {
 
         Self {
             unparsed: String::new(),
+            format,
             rx,
             _reader_thread: reader_thread,
         }
     }
 
-    pub fn from_stdin() -> Self {
-        ReaderLogSource::new(std::io::stdin())
+    pub fn from_stdin(format: TimestampFormat) -> Self {
+        ReaderLogSource::new(std::io::stdin(), format)
     }
 }
 
@@ -200,7 +577,7 @@ impl LogSource for ReaderLogSource {
         // Append to the log
         let mut raw = log.raw().to_string();
         raw.push_str(&self.unparsed);
-        if let Ok(log) = Log::parse(raw) {
+        if let Ok(log) = Log::parse_as(raw, self.format) {
             self.unparsed.clear();
             Ok(Some(log))
         } else {
@@ -208,4 +585,746 @@ impl LogSource for ReaderLogSource {
             Ok(None)
         }
     }
+
+    fn is_following(&self) -> bool {
+        true
+    }
+}
+
+/// Merges the messages from a followed log file and stdin by timestamp.
+/// Messages from stdin have "(stdin)" added to their source so they can be
+/// filtered separately.
+#[derive(Debug)]
+pub struct MergedLogSource {
+    file: FollowedLogSource,
+    file_log: Log,
+    stdin: ReaderLogSource,
+    stdin_log: Log,
+}
+
+impl MergedLogSource {
+    pub fn new(
+        path: PathBuf,
+        compare_contents: bool,
+        format: TimestampFormat,
+    ) -> anyhow::Result<(Self, Log)> {
+        info!(?path, "creating merged log source");
+        let (file, file_log) = FollowedLogSource::new(path, compare_contents, format)?;
+        let stdin_log = Log::empty();
+        let log = Self::merge(&file_log, &stdin_log)?;
+        let source = MergedLogSource {
+            file,
+            file_log,
+            stdin: ReaderLogSource::from_stdin(format),
+            stdin_log,
+        };
+        Ok((source, log))
+    }
+
+    fn merge(file_log: &Log, stdin_log: &Log) -> anyhow::Result<Log> {
+        let stdin_messages = stdin_log.messages().iter().map(|message| Message {
+            source: format!("{} (stdin)", message.source).into(),
+            ..message.clone()
+        });
+        let raw = file_log
+            .messages()
+            .iter()
+            .cloned()
+            .merge_by(stdin_messages, |a, b| a.timestamp <= b.timestamp)
+            .fold(String::new(), |mut raw, message| {
+                let _ = writeln!(raw, "{message}");
+                raw
+            });
+        Log::parse(raw).context("error merging logs")
+    }
+}
+
+impl LogSource for MergedLogSource {
+    #[instrument(skip_all)]
+    fn update_log(&mut self, _log: &Log) -> anyhow::Result<Option<Log>> {
+        let mut updated = false;
+        if let Some(file_log) = self.file.update_log(&self.file_log)? {
+            self.file_log = file_log;
+            updated = true;
+        }
+        if let Some(stdin_log) = self.stdin.update_log(&self.stdin_log)? {
+            self.stdin_log = stdin_log;
+            updated = true;
+        }
+
+        if updated {
+            Self::merge(&self.file_log, &self.stdin_log).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn poll_interval(&self) -> Option<Duration> {
+        self.file.poll_interval()
+    }
+
+    fn is_following(&self) -> bool {
+        true
+    }
+}
+
+/// Downloads a remote log again periodically to check for new messages.
+#[derive(Debug)]
+pub struct RemoteLogSource {
+    url: Url,
+    format: TimestampFormat,
+    rx: Receiver<String>,
+    _fetch_thread: JoinHandle<()>,
+}
+
+impl RemoteLogSource {
+    const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+    /// Downloads a remote log, then keeps downloading it in the background.
+    #[instrument(skip_all, fields(%url))]
+    pub fn new(url: Url, format: TimestampFormat) -> anyhow::Result<(Self, Log)> {
+        info!("creating remote log source");
+        let log =
+            Log::parse_as(fetch_remote_log(url.clone())?, format).context("error parsing log")?;
+
+        // The thread stops once the source is dropped and the channel closes
+        let (tx, rx) = crossbeam::channel::bounded(1);
+        let fetch_thread = std::thread::spawn({
+            let url = url.clone();
+            move || loop {
+                std::thread::sleep(Self::POLL_INTERVAL);
+                match fetch_remote_log(url.clone()) {
+                    Ok(contents) => {
+                        if tx.send(contents).is_err() {
+                            break;
+                        }
+                    }
+                    // The server might be briefly unavailable
+                    Err(error) => warn!(?error, "error fetching remote log"),
+                }
+            }
+        });
+
+        let source = RemoteLogSource {
+            url,
+            format,
+            rx,
+            _fetch_thread: fetch_thread,
+        };
+        Ok((source, log))
+    }
+}
+
+impl LogSource for RemoteLogSource {
+    #[instrument(skip_all, fields(url=%self.url))]
+    fn update_log(&mut self, log: &Log) -> anyhow::Result<Option<Log>> {
+        let Some(contents) = self.rx.try_iter().last() else {
+            return Ok(None);
+        };
+        if contents == log.raw() {
+            return Ok(None);
+        }
+
+        match Log::parse_as(contents, self.format) {
+            Ok(log) => Ok(Some(log)),
+            Err(error) => {
+                warn!(?error, "error parsing remote log");
+                Ok(None)
+            }
+        }
+    }
+
+    fn poll_interval(&self) -> Option<Duration> {
+        Some(Self::POLL_INTERVAL)
+    }
+
+    fn is_following(&self) -> bool {
+        true
+    }
+}
+
+/// Downloads the contents of a remote log. Responses with `Content-Encoding:
+/// gzip` are decompressed by the client, and gzipped files are decompressed
+/// here.
+#[instrument(skip_all, fields(%url))]
+pub fn fetch_remote_log(url: Url) -> anyhow::Result<String> {
+    info!("fetching remote log");
+    let data = Client::new()
+        .get(url)
+        .send()
+        .context("error retrieving remote log")?
+        .bytes()
+        .context("error reading remote log")?;
+    archive::decode_log(data.to_vec()).context("error reading remote log")
+}
+
+/// Records the raw contents of another log source to a file as they are
+/// received.
+pub struct RecordedLogSource {
+    inner: Box<dyn LogSource>,
+    file: Option<File>,
+}
+
+impl RecordedLogSource {
+    /// Wraps a log source, appending its contents to the file at the given
+    /// path. The initial log is written immediately. If `marker_format` is
+    /// set, a message marking the start of the recording is written first,
+    /// with its timestamp in that format. Auto uses the log's format.
+    #[instrument(skip(inner, log))]
+    pub fn new(
+        inner: Box<dyn LogSource>,
+        log: &Log,
+        path: &Path,
+        marker_format: Option<TimestampFormat>,
+    ) -> anyhow::Result<Self> {
+        info!("creating recorded log source");
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("error opening recording file: {}", path.display()))?;
+        if let Some(format) = marker_format {
+            let format = match format {
+                TimestampFormat::Auto => detect_timestamp_format(log.raw()),
+                format => format,
+            };
+            write_session_marker(&mut file, format)
+                .with_context(|| format!("error writing to recording file: {}", path.display()))?;
+        }
+
+        let mut source = RecordedLogSource {
+            inner,
+            file: Some(file),
+        };
+        source.record(log.raw());
+        Ok(source)
+    }
+
+    fn record(&mut self, text: &str) {
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+
+        let result = file.write_all(text.as_bytes()).and_then(|()| file.flush());
+        if let Err(error) = result {
+            // Don't crash the UI, just stop recording
+            warn!(?error, "error writing to recording file, recording stopped");
+            self.file = None;
+        }
+    }
+}
+
+/// Writes a message marking the start of a recording session, like
+/// `[10:00:00 INFO  pufferwatch] Recording started by pufferwatch 0.2.0 on
+/// 2024-03-01`. If the file doesn't end with a newline, the message is put on
+/// a new line so it isn't mistaken for part of the last message.
+fn write_session_marker(file: &mut File, format: TimestampFormat) -> std::io::Result<()> {
+    let mut prefix = "";
+    if file.metadata()?.len() > 0 {
+        let mut last = [0];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            prefix = "\n";
+        }
+    }
+
+    let now = Local::now();
+    writeln!(
+        file,
+        "{prefix}[{} INFO  {}] {} by pufferwatch {} on {}",
+        now.format(format.strftime()),
+        GameEvent::RECORDING_SOURCE,
+        GameEvent::RECORDING_MARKER,
+        env!("CARGO_PKG_VERSION"),
+        now.format("%Y-%m-%d"),
+    )?;
+    file.flush()
+}
+
+impl Debug for RecordedLogSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecordedLogSource")
+            .field("file", &self.file)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LogSource for RecordedLogSource {
+    fn update_log(&mut self, log: &Log) -> anyhow::Result<Option<Log>> {
+        let new_log = self.inner.update_log(log)?;
+        if let Some(new_log) = new_log.as_ref() {
+            // Only write what hasn't been written yet
+            self.record(new_log.text_added_since(log));
+        }
+
+        Ok(new_log)
+    }
+
+    fn path(&self) -> Option<&Path> {
+        self.inner.path()
+    }
+
+    fn poll_interval(&self) -> Option<Duration> {
+        self.inner.poll_interval()
+    }
+
+    fn is_following(&self) -> bool {
+        self.inner.is_following()
+    }
+}
+
+/// A log source that writes each new message that passes its filters to
+/// another program, like a named pipe or the stdin of a command.
+pub struct StreamedLogSource {
+    inner: Box<dyn LogSource>,
+    output: Option<Box<dyn Write>>,
+    child: Option<Child>,
+    levels: Vec<Level>,
+    sources: Vec<String>,
+    next: usize,
+    pending: Option<(String, Instant)>,
+    join_lines: Option<String>,
+}
+
+impl StreamedLogSource {
+    /// How long the log has to stay the same before the last message is
+    /// written without waiting for the next one.
+    const QUIET_PERIOD: Duration = Duration::from_millis(500);
+
+    /// How long the stream command gets to exit after its stdin is closed
+    /// before it's killed.
+    const EXIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// Wraps a log source, writing new messages to the file at the given
+    /// path. Opening a named pipe waits until something starts reading it.
+    #[instrument(skip(inner, log))]
+    pub fn to_file(
+        inner: Box<dyn LogSource>,
+        log: &Log,
+        path: &Path,
+        levels: Vec<Level>,
+        sources: Vec<String>,
+    ) -> anyhow::Result<Self> {
+        info!("creating streamed log source");
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("error opening stream file: {}", path.display()))?;
+        Ok(Self::new(inner, log, Box::new(file), None, levels, sources))
+    }
+
+    /// Wraps a log source, writing new messages to the stdin of a shell
+    /// command. The command's output is ignored so it doesn't draw over the
+    /// UI.
+    #[instrument(skip(inner, log))]
+    pub fn to_command(
+        inner: Box<dyn LogSource>,
+        log: &Log,
+        command: &str,
+        levels: Vec<Level>,
+        sources: Vec<String>,
+    ) -> anyhow::Result<Self> {
+        info!("creating streamed log source");
+        let (shell, flag) = if cfg!(windows) {
+            ("cmd", "/C")
+        } else {
+            ("sh", "-c")
+        };
+        let mut child = std::process::Command::new(shell)
+            .arg(flag)
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("error starting stream command: {command}"))?;
+        let stdin = child.stdin.take().context("stream command has no stdin")?;
+        Ok(Self::new(
+            inner,
+            log,
+            Box::new(stdin),
+            Some(child),
+            levels,
+            sources,
+        ))
+    }
+
+    /// Wraps a log source, writing new messages to an output. Only messages
+    /// with the given levels and sources are written, or every message if
+    /// those are empty. Messages already in the log aren't written.
+    pub fn new(
+        inner: Box<dyn LogSource>,
+        log: &Log,
+        output: Box<dyn Write>,
+        child: Option<Child>,
+        levels: Vec<Level>,
+        sources: Vec<String>,
+    ) -> Self {
+        StreamedLogSource {
+            inner,
+            output: Some(output),
+            child,
+            levels,
+            sources,
+            next: log.messages().len(),
+            pending: None,
+            join_lines: None,
+        }
+    }
+
+    /// Joins the lines of each message with a separator so every message is
+    /// written on one line.
+    pub fn join_lines(mut self, separator: Option<String>) -> Self {
+        self.join_lines = separator;
+        self
+    }
+
+    /// Formats a message the same way SMAPI writes it, with its lines joined
+    /// if a separator is set.
+    fn format_message(message: &Message, join_lines: Option<&str>) -> String {
+        format!(
+            "{} {}",
+            message.header(true),
+            message.joined_contents(join_lines)
+        )
+    }
+
+    fn stream(&mut self, log: &Log) {
+        let Some(output) = self.output.as_mut() else {
+            return;
+        };
+
+        // If the log shrank, it was replaced, so start from the beginning
+        let messages = log.messages();
+        if messages.len() < self.next {
+            self.next = 0;
+        }
+
+        // The last message can still get more lines, so it's held back until
+        // the next message arrives or the log stops changing
+        let filters = LogFilters::matching(log, &self.levels, &self.sources);
+        let complete = messages.len().saturating_sub(1).max(self.next);
+        let join_lines = self.join_lines.as_deref();
+        let result = messages[self.next..complete]
+            .iter()
+            .filter(|message| filters.matches(message))
+            .try_for_each(|message| {
+                writeln!(output, "{}", Self::format_message(message, join_lines))
+            })
+            .and_then(|()| output.flush());
+        if let Err(error) = result {
+            // Don't crash the UI, just stop streaming
+            warn!(?error, "error writing to stream, streaming stopped");
+            self.output = None;
+            self.pending = None;
+            return;
+        }
+
+        self.next = complete;
+        self.pending = messages
+            .get(complete)
+            .filter(|message| filters.matches(message))
+            .map(|message| (Self::format_message(message, join_lines), Instant::now()));
+    }
+
+    /// Writes the held back message if the log hasn't changed for a while.
+    /// Lines added to it after that aren't written.
+    fn flush_if_quiet(&mut self) {
+        let quiet = self
+            .pending
+            .as_ref()
+            .is_some_and(|(_, since)| since.elapsed() >= Self::QUIET_PERIOD);
+        if quiet {
+            self.flush_pending();
+            self.next += 1;
+        }
+    }
+
+    fn flush_pending(&mut self) {
+        let (Some(output), Some((message, _))) = (self.output.as_mut(), self.pending.take()) else {
+            return;
+        };
+
+        let result = writeln!(output, "{message}").and_then(|()| output.flush());
+        if let Err(error) = result {
+            warn!(?error, "error writing to stream, streaming stopped");
+            self.output = None;
+        }
+    }
+}
+
+impl Drop for StreamedLogSource {
+    fn drop(&mut self) {
+        // Write the held back message now that no more lines can arrive
+        self.flush_pending();
+
+        // Closing stdin lets the command finish, but it's killed if it takes
+        // too long so it isn't left running in the background
+        self.output = None;
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+        let start = Instant::now();
+        loop {
+            match child.try_wait() {
+                Ok(Some(_)) => return,
+                Ok(None) if start.elapsed() < Self::EXIT_TIMEOUT => {
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                Ok(None) => break,
+                Err(error) => {
+                    warn!(?error, "error waiting for stream command");
+                    break;
+                }
+            }
+        }
+        if let Err(error) = child.kill().and_then(|()| child.wait()) {
+            warn!(?error, "error stopping stream command");
+        }
+    }
+}
+
+impl Debug for StreamedLogSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamedLogSource")
+            .field("child", &self.child)
+            .field("levels", &self.levels)
+            .field("sources", &self.sources)
+            .field("next", &self.next)
+            .field("join_lines", &self.join_lines)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LogSource for StreamedLogSource {
+    fn update_log(&mut self, log: &Log) -> anyhow::Result<Option<Log>> {
+        let new_log = self.inner.update_log(log)?;
+        match new_log.as_ref() {
+            Some(new_log) => self.stream(new_log),
+            None => self.flush_if_quiet(),
+        }
+
+        Ok(new_log)
+    }
+
+    fn path(&self) -> Option<&Path> {
+        self.inner.path()
+    }
+
+    fn poll_interval(&self) -> Option<Duration> {
+        self.inner.poll_interval()
+    }
+
+    fn is_following(&self) -> bool {
+        self.inner.is_following()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, fs::OpenOptions, rc::Rc};
+
+    /// A log file in the temporary directory, removed when it's dropped.
+    struct TempLog(PathBuf);
+
+    impl TempLog {
+        fn new(name: &str, contents: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("pufferwatch-{}-{name}.txt", std::process::id()));
+            std::fs::write(&path, contents).unwrap();
+            TempLog(path)
+        }
+
+        fn append(&self, bytes: &[u8]) {
+            let mut file = OpenOptions::new().append(true).open(&self.0).unwrap();
+            file.write_all(bytes).unwrap();
+        }
+    }
+
+    impl Drop for TempLog {
+        fn drop(&mut self) {
+            drop(std::fs::remove_file(&self.0));
+        }
+    }
+
+    fn contents(log: &Log) -> Vec<&str> {
+        log.messages()
+            .iter()
+            .map(|message| message.contents.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn follows_appended_text() {
+        let file = TempLog::new("appended", "[10:00:00 INFO  SMAPI] Starting\n");
+        let (mut source, log) =
+            FollowedLogSource::new(file.0.clone(), false, TimestampFormat::Auto).unwrap();
+
+        // A character cut off at the end is read once it's complete
+        file.append("[10:00:01 INFO  SMAPI] Caf".as_bytes());
+        file.append(&"é".as_bytes()[..1]);
+        let log = source.read_changes(&log).unwrap();
+        assert_eq!(contents(&log), ["Starting", "Caf"]);
+        file.append(&"é\nat Stack.Trace()\n".as_bytes()[1..]);
+        let log = source.read_changes(&log).unwrap();
+        assert_eq!(contents(&log), ["Starting", "Café\nat Stack.Trace()"]);
+    }
+
+    #[test]
+    fn parses_changed_file_again() {
+        let file = TempLog::new("changed", "[10:00:00 INFO  SMAPI] Starting\n");
+        let (mut source, log) =
+            FollowedLogSource::new(file.0.clone(), false, TimestampFormat::Auto).unwrap();
+
+        // A longer file with different contents isn't added to the log
+        std::fs::write(
+            &file.0,
+            "[11:00:00 INFO  SMAPI] New session\n[11:00:01 INFO  SMAPI] Loaded\n",
+        )
+        .unwrap();
+        let log = source.read_changes(&log).unwrap();
+        assert_eq!(contents(&log), ["New session", "Loaded"]);
+
+        // Neither is a shorter file
+        std::fs::write(&file.0, "[12:00:00 INFO  SMAPI] Again\n").unwrap();
+        let log = source.read_changes(&log).unwrap();
+        assert_eq!(contents(&log), ["Again"]);
+    }
+
+    #[test]
+    fn only_switches_to_newer_log_files() {
+        let current = TempLog::new("current", "");
+        let other = TempLog::new("other", "");
+        let set_modified = |file: &TempLog, time: std::time::SystemTime| {
+            File::options()
+                .write(true)
+                .open(&file.0)
+                .unwrap()
+                .set_modified(time)
+                .unwrap();
+        };
+        let now = std::time::SystemTime::now();
+        set_modified(&current, now);
+
+        // Files written at about the same time don't switch
+        set_modified(&other, now + Duration::from_secs(1));
+        assert!(!DirectoryLogSource::is_newer(&current.0, &other.0));
+        assert!(!DirectoryLogSource::is_newer(&other.0, &current.0));
+
+        set_modified(&other, now + Duration::from_secs(10));
+        assert!(DirectoryLogSource::is_newer(&current.0, &other.0));
+        assert!(!DirectoryLogSource::is_newer(&other.0, &current.0));
+    }
+
+    /// An output that can be read after it's given to a source.
+    #[derive(Clone, Default)]
+    struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+    impl SharedOutput {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn streams_new_filtered_messages() {
+        let first = "[10:00:00 INFO  SMAPI] Old message\n";
+        let log = Log::parse(first.to_string()).unwrap();
+        let output = SharedOutput::default();
+        let mut source = StreamedLogSource::new(
+            Box::new(StaticLogSource { path: None }),
+            &log,
+            Box::new(output.clone()),
+            None,
+            vec![Level::Error],
+            Vec::new(),
+        );
+
+        let second = format!(
+            "{first}[10:00:01 ERROR SMAPI] Failed\nat Stack.Trace()\n\
+             [10:00:02 INFO  SMAPI] Skipped\n[10:00:03 ERROR Mod] Last\n"
+        );
+        source.stream(&Log::parse(second).unwrap());
+        assert_eq!(
+            output.contents(),
+            "[10:00:01 ERROR SMAPI] Failed\nat Stack.Trace()\n"
+        );
+
+        // The last message is written once nothing else can be added to it
+        drop(source);
+        assert_eq!(
+            output.contents(),
+            "[10:00:01 ERROR SMAPI] Failed\nat Stack.Trace()\n[10:00:03 ERROR Mod] Last\n"
+        );
+    }
+
+    #[test]
+    fn streams_joined_lines() {
+        let log = Log::parse(String::new()).unwrap();
+        let output = SharedOutput::default();
+        let mut source = StreamedLogSource::new(
+            Box::new(StaticLogSource { path: None }),
+            &log,
+            Box::new(output.clone()),
+            None,
+            Vec::new(),
+            Vec::new(),
+        )
+        .join_lines(Some(" | ".to_string()));
+        let log = "[10:00:01 ERROR SMAPI] Failed\nat Stack.Trace()\n[10:00:02 INFO  SMAPI] Next\n";
+        source.stream(&Log::parse(log.to_string()).unwrap());
+        assert_eq!(
+            output.contents(),
+            "[10:00:01 ERROR SMAPI] Failed | at Stack.Trace()\n"
+        );
+    }
+
+    #[test]
+    fn streams_last_message_when_log_is_quiet() {
+        let first = "[10:00:00 ERROR SMAPI] First\n";
+        let log = Log::parse(String::new()).unwrap();
+        let output = SharedOutput::default();
+        let mut source = StreamedLogSource::new(
+            Box::new(StaticLogSource { path: None }),
+            &log,
+            Box::new(output.clone()),
+            None,
+            Vec::new(),
+            Vec::new(),
+        );
+        let log = Log::parse(first.to_string()).unwrap();
+        source.stream(&log);
+        assert_eq!(output.contents(), "");
+
+        // Nothing is written until the log has been quiet for a while
+        source.update_log(&log).unwrap();
+        assert_eq!(output.contents(), "");
+        if let Some((_, since)) = source.pending.as_mut() {
+            *since -= StreamedLogSource::QUIET_PERIOD;
+        }
+        source.update_log(&log).unwrap();
+        assert_eq!(output.contents(), first);
+
+        // The message isn't written again when the next one arrives
+        source.stream(&Log::parse(format!("{first}[10:00:01 INFO  SMAPI] Second\n")).unwrap());
+        drop(source);
+        assert_eq!(
+            output.contents(),
+            format!("{first}[10:00:01 INFO  SMAPI] Second\n")
+        );
+    }
 }
@@ -1,29 +1,51 @@
 use crate::{
+    archive,
+    ast::{Level, TimestampFormat},
     config::{
-        App, AppCommand, CommandEncoding, MonitorCommand, RemoteCommand, RunCommand, StdinCommand,
+        App, AppCommand, CommandEncoding, EnvVar, MonitorCommand, RemoteCommand, RunCommand,
+        StdinCommand, ThemeName,
     },
-    encoded_writer::{ByteOrder, EncodedWriter},
+    encoded_writer::{ByteOrder, CommandPipe, EncodedWriter},
     events::{AppEvent, EventController},
-    install_path::get_install_paths,
+    export::export,
+    grep::grep,
+    install_path::{get_custom_log_paths, get_install_paths, InstallKind},
     log::Log,
-    source::{FollowedLogSource, LogSource, ReaderLogSource, StaticLogSource},
-    widgets::{Root, RootState, State, WithLog},
+    notes::NotesFile,
+    parse::ParseFailure,
+    screenshot::save_screen,
+    settings::Settings,
+    source::{
+        fetch_remote_log, DirectoryLogSource, FollowedLogSource, LogSource, MergedLogSource,
+        ReaderLogSource, RecordedLogSource, RemoteLogSource, StaticLogSource, StreamedLogSource,
+    },
+    state_file::{SavedFilters, StateFile},
+    widgets::{Root, RootOptions, RootState, SnapshotRequest, State, Theme, WithLog},
 };
-use anyhow::Context;
+use anyhow::{bail, Context};
+use arboard::Clipboard;
+use crossbeam::channel::RecvTimeoutError;
 use crossterm::{
-    event::{Event, KeyCode, KeyModifiers},
+    event::{DisableMouseCapture, EnableMouseCapture, Event},
     terminal::{EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
 use ouroboros::self_referencing;
-use reqwest::blocking::Client;
+use reqwest::Url;
 use std::{
-    ffi::OsStr,
-    io::{stdout, Write},
+    collections::HashMap,
+    ffi::{OsStr, OsString},
+    fs::{File, OpenOptions},
+    io::{stdin, stdout, IsTerminal, Read, Write},
     path::{Path, PathBuf},
-    process::{Child, ChildStdin, Stdio},
+    process::{Child, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
-use tracing::{debug_span, info, info_span, instrument, trace};
+use tracing::{debug, debug_span, info, info_span, instrument, trace, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry};
 use tui::{
     backend::{Backend, CrosstermBackend},
@@ -36,71 +58,732 @@ pub fn start(config: App) -> anyhow::Result<()> {
     let _span = info_span!("start").entered();
     info!("starting pufferwatch");
 
+    // Load settings
+    let settings = Settings::load().unwrap_or_else(|error| {
+        warn!(?error, "error loading config file");
+        Settings::default()
+    });
+    let mut theme = match config.theme {
+        ThemeName::Default => Theme::default(),
+        ThemeName::ColorBlind => Theme::color_blind(),
+    };
+    settings.theme.apply(&mut theme);
+
+    // Search or export the log without starting the TUI
+    if let Some(result) = run_without_tui(&config, &theme, settings.join_lines.as_deref()) {
+        return result;
+    }
+
+    // Load persisted state
+    let state_file = StateFile::load().unwrap_or_else(|error| {
+        warn!(?error, "error loading state file");
+        StateFile::default()
+    });
+
+    // Options for exiting run mode
+    let (idle_timeout, kill_when_idle, print_log) = match &config.command {
+        AppCommand::Run(command) => (
+            command.exit_when_idle.map(Duration::from_secs),
+            command.kill_when_idle,
+            command.print_log,
+        ),
+        _ => (None, false, false),
+    };
+
     // Setup log source
-    let (source, log, child_stdin) = get_source(config.command)?;
+    let (source, log, child_stdin, smapi) = get_source(
+        config.command,
+        !config.no_compare_contents,
+        config.timestamp_format,
+    )
+    .inspect_err(offer_to_copy_parse_failure)?;
+    let marker_format = config.mark_sessions.then_some(config.timestamp_format);
+    let source = record_source(source, &log, config.record.as_deref(), marker_format)?;
+    let source = stream_source(
+        source,
+        &log,
+        config.stream_to.as_deref(),
+        config.stream_command.as_deref(),
+        config.stream_levels,
+        config.stream_sources,
+        settings.join_lines.clone(),
+    )?;
 
-    // Initialize TUI
-    trace!("initializing TUI");
-    let backend = CrosstermBackend::new(stdout());
-    let mut terminal = Terminal::new(backend)?;
+    // Open command log
+    let command_log = config
+        .command_log
+        .as_deref()
+        .map(open_command_log)
+        .transpose()?;
 
-    // Prepare alternate screen
-    trace!("entering alternate screen");
-    terminal.backend_mut().execute(EnterAlternateScreen)?;
-    crossterm::terminal::enable_raw_mode()?;
-    terminal.hide_cursor()?;
-    terminal.clear()?;
+    // Exit cleanly when asked to stop so the terminal is restored
+    let terminated = register_exit_signals()?;
+
+    // Initialize TUI, or print the log instead if the terminal can't show it
+    trace!("initializing TUI");
+    let mut terminal = match open_tui(config.mouse) {
+        Ok(terminal) => terminal,
+        Err(error) => {
+            warn!(?error, "error initializing TUI, printing the log instead");
+            eprintln!("Unable to start the interface ({error:#}), printing the log instead.");
+            let exit = print_loop(log, source, idle_timeout, &terminated)?;
+            return finish_run(&exit, smapi, kill_when_idle, false);
+        }
+    };
 
     // TUI event loop
-    let result = render_loop(log, source, child_stdin, &mut terminal);
+    let persist_notes = settings.persist_notes;
+    let options = RootOptions {
+        show_intro: !state_file.seen_intro,
+        show_line_count: config.line_count,
+        command_log,
+        time_offset: config.time_offset,
+        tab: config.tab.unwrap_or(settings.startup_tab),
+        jump_to_error: config.jump_to_error,
+        notes: load_notes(&log, persist_notes),
+        saved_filters: state_file.filters.clone(),
+        ..root_options(settings, theme, source.as_ref())
+    };
+    let frame_interval = if config.max_fps == 0 {
+        Duration::ZERO
+    } else {
+        Duration::from_secs(1) / config.max_fps
+    };
+    let loop_options = LoopOptions {
+        frame_interval,
+        capture_mouse: config.mouse,
+        idle_timeout,
+        persist_notes,
+    };
+    let result = render_loop(
+        log,
+        source,
+        child_stdin,
+        options,
+        loop_options,
+        &terminated,
+        &mut terminal,
+    );
 
     // Exit alternate screen
-    terminal.backend_mut().execute(LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
-    crossterm::terminal::disable_raw_mode()?;
-    result
+    leave_tui(&mut terminal, config.mouse)?;
+
+    // Save persisted state
+    save_state(state_file, &result);
+
+    let exit = result?;
+    finish_run(&exit, smapi, kill_when_idle, print_log)
+}
+
+/// Saves the state to restore the next time pufferwatch starts.
+fn save_state(mut state_file: StateFile, result: &anyhow::Result<LoopExit>) {
+    state_file.seen_intro = true;
+    if let Ok(LoopExit {
+        filters: Some(filters),
+        ..
+    }) = result
+    {
+        state_file.filters = Some(filters.clone());
+    }
+    if let Err(error) = state_file.save() {
+        warn!(?error, "error saving state file");
+    }
+}
+
+/// Gets the options for the UI that come from the settings and the log
+/// source.
+fn root_options(settings: Settings, theme: Theme, source: &dyn LogSource) -> RootOptions {
+    RootOptions {
+        classifiers: settings.classifiers(),
+        theme,
+        can_open_editor: source.path().is_some(),
+        poll_interval: source.poll_interval(),
+        command_prompt: settings.command_prompt,
+        aliases: settings.aliases,
+        auto_scroll_interval: if settings.auto_scroll_rate == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(1) / settings.auto_scroll_rate
+        },
+        noisy_sources: settings.noisy_sources,
+        copy_format: settings.copy_format,
+        join_lines: settings.join_lines,
+        wrap_navigation: settings.wrap_navigation,
+        bell_levels: settings.bell_levels,
+        columns: settings.columns,
+        max_source_width: settings.max_source_width,
+        max_message_width: settings.max_message_width,
+        histogram_buckets: settings.histogram_buckets,
+        following: source.is_following(),
+        heartbeat: settings.heartbeat && source.is_following(),
+        controls_rows: settings.controls_rows.max(1),
+        auto_expand: settings.auto_expand,
+        ctrl_c_always_quits: settings.ctrl_c_always_quits,
+        group_sources_below: settings.group_sources_below,
+        error_highlight_duration: Duration::from_secs(settings.error_highlight_seconds),
+        ..RootOptions::default()
+    }
+}
+
+/// Runs the commands that print to the terminal instead of starting the TUI,
+/// like searching or exporting the log. Returns `None` for other commands.
+/// The lines of messages are joined with `join_lines` unless the command sets
+/// its own separator.
+fn run_without_tui(
+    config: &App,
+    theme: &Theme,
+    join_lines: Option<&str>,
+) -> Option<anyhow::Result<()>> {
+    match &config.command {
+        AppCommand::Grep(command) => Some(
+            read_log(
+                command.log.as_deref(),
+                command.url.clone(),
+                config.timestamp_format,
+            )
+            .and_then(|log| {
+                let join_lines = command.join_lines.as_deref().or(join_lines);
+                grep(command, &log, theme, join_lines)
+            }),
+        ),
+        AppCommand::Export(command) => Some(
+            read_log(
+                command.log.as_deref(),
+                command.url.clone(),
+                config.timestamp_format,
+            )
+            .and_then(|log| export(command, &log, command.join_lines.as_deref().or(join_lines))),
+        ),
+        _ => None,
+    }
+}
+
+/// Wraps a log source to record it to a file, if a recording path is given.
+/// The start of the recording is marked if `marker_format` is set.
+fn record_source(
+    source: Box<dyn LogSource>,
+    log: &Log,
+    record_path: Option<&Path>,
+    marker_format: Option<TimestampFormat>,
+) -> anyhow::Result<Box<dyn LogSource>> {
+    match record_path {
+        Some(path) => Ok(Box::new(RecordedLogSource::new(
+            source,
+            log,
+            path,
+            marker_format,
+        )?)),
+        None => Ok(source),
+    }
+}
+
+/// Wraps a log source to stream new messages to a file or the stdin of a
+/// command, if either is given. The lines of messages are joined with
+/// `join_lines` if it's set.
+fn stream_source(
+    source: Box<dyn LogSource>,
+    log: &Log,
+    path: Option<&Path>,
+    command: Option<&str>,
+    levels: Vec<Level>,
+    sources: Vec<String>,
+    join_lines: Option<String>,
+) -> anyhow::Result<Box<dyn LogSource>> {
+    match (path, command) {
+        (Some(path), _) => Ok(Box::new(
+            StreamedLogSource::to_file(source, log, path, levels, sources)?.join_lines(join_lines),
+        )),
+        (None, Some(command)) => Ok(Box::new(
+            StreamedLogSource::to_command(source, log, command, levels, sources)?
+                .join_lines(join_lines),
+        )),
+        (None, None) => Ok(source),
+    }
+}
+
+/// Loads the notes saved for a log, if notes are persisted.
+fn load_notes(log: &Log, persist_notes: bool) -> HashMap<usize, String> {
+    if !persist_notes {
+        return HashMap::new();
+    }
+
+    NotesFile::load(log).unwrap_or_else(|error| {
+        warn!(?error, "error loading notes file");
+        HashMap::new()
+    })
+}
+
+/// Opens the file to log commands sent to SMAPI to, appending to it if it
+/// already exists.
+fn open_command_log(path: &Path) -> anyhow::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("error opening command log: {}", path.display()))
+}
+
+/// Sets up a flag that is set when pufferwatch is asked to stop by a signal.
+fn register_exit_signals() -> anyhow::Result<Arc<AtomicBool>> {
+    let terminated = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    for signal in [signal_hook::consts::SIGTERM, signal_hook::consts::SIGHUP] {
+        signal_hook::flag::register(signal, terminated.clone())
+            .context("error registering signal handler")?;
+    }
+
+    Ok(terminated)
+}
+
+/// Stops SMAPI and prints the log after exiting, if requested.
+fn finish_run(
+    exit: &LoopExit,
+    smapi: Option<Child>,
+    kill_when_idle: bool,
+    print_log: bool,
+) -> anyhow::Result<()> {
+    // Stop SMAPI
+    if exit.terminated || exit.idle && kill_when_idle {
+        if let Some(mut smapi) = smapi {
+            info!("stopping SMAPI");
+            stop_smapi(&mut smapi)?;
+        }
+    }
+
+    // Print the log
+    if print_log {
+        let mut stdout = stdout().lock();
+        stdout
+            .write_all(exit.log.raw().as_bytes())
+            .and_then(|()| stdout.flush())
+            .context("error printing log")?;
+    }
+
+    Ok(())
+}
+
+/// Stops SMAPI, asking it to exit first so it can finish writing its log.
+/// It's killed if it doesn't exit in time.
+fn stop_smapi(smapi: &mut Child) -> anyhow::Result<ExitStatus> {
+    /// How long SMAPI gets to exit after it's asked to before it's killed.
+    const EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    if request_exit(smapi) {
+        let start = Instant::now();
+        while start.elapsed() < EXIT_TIMEOUT {
+            if let Some(status) = smapi.try_wait().context("error waiting for SMAPI")? {
+                return Ok(status);
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        warn!("SMAPI didn't exit in time, killing it");
+    }
+
+    smapi.kill().context("error stopping SMAPI")?;
+    smapi.wait().context("error waiting for SMAPI")
+}
+
+/// Sends SIGTERM to SMAPI. Returns whether it was sent.
+#[cfg(unix)]
+fn request_exit(smapi: &Child) -> bool {
+    let result = std::process::Command::new("kill")
+        .arg("-TERM")
+        .arg(smapi.id().to_string())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    match result {
+        Ok(status) if status.success() => true,
+        Ok(status) => {
+            warn!(%status, "error sending SIGTERM to SMAPI");
+            false
+        }
+        Err(error) => {
+            warn!(?error, "error sending SIGTERM to SMAPI");
+            false
+        }
+    }
+}
+
+/// There's no signal to ask SMAPI to exit on Windows, so it's killed right
+/// away.
+#[cfg(not(unix))]
+fn request_exit(_smapi: &Child) -> bool {
+    false
+}
+
+/// Options for the render loop.
+#[derive(Clone, Copy, Debug)]
+struct LoopOptions {
+    /// The minimum time between frames.
+    frame_interval: Duration,
+    /// Whether mouse capture is enabled.
+    capture_mouse: bool,
+    /// How long to wait for new log lines before exiting, if at all.
+    idle_timeout: Option<Duration>,
+    /// Whether notes on messages are saved when they change.
+    persist_notes: bool,
+}
+
+/// The state of the render loop when it exits.
+struct LoopExit {
+    /// The log when the loop exited.
+    log: Log,
+    /// Whether the loop exited because no new log lines were received.
+    idle: bool,
+    /// Whether the loop exited because pufferwatch was asked to stop.
+    terminated: bool,
+    /// The level and source filters when the loop exited.
+    filters: Option<SavedFilters>,
 }
 
 #[instrument(skip_all)]
 fn render_loop(
     log: Log,
     mut source: Box<dyn LogSource>,
-    smapi_stdin: Option<EncodedWriter<ChildStdin>>,
+    smapi_stdin: Option<EncodedWriter<CommandPipe>>,
+    options: RootOptions,
+    loop_options: LoopOptions,
+    terminated: &AtomicBool,
     terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
-) -> Result<(), anyhow::Error> {
-    let mut force_redraw = true;
-    let (event_rx, _event_controller) = EventController::start();
-    let mut renderer = Renderer::from_log(log, smapi_stdin);
+) -> Result<LoopExit, anyhow::Error> {
+    let LoopOptions {
+        frame_interval,
+        capture_mouse,
+        idle_timeout,
+        persist_notes,
+    } = loop_options;
+    let mut needs_redraw = true;
+    let mut last_draw: Option<Instant> = None;
+    let mut last_log_update = Instant::now();
+    let mut idle = false;
+    let mut clipboard = None;
+    let (mut event_rx, mut event_controller) = EventController::start();
+    let mut renderer = Renderer::from_log(log, smapi_stdin, options);
     loop {
-        // Read event
-        let event = event_rx.recv().context("error reading event")?;
-        let _span = debug_span!("term_event", term_event=?event).entered();
-        match event {
-            // Check if quitting
-            AppEvent::TermEvent(Event::Key(key_event)) => {
-                if key_event.code == KeyCode::Char('c')
-                    && key_event.modifiers == KeyModifiers::CONTROL
-                {
-                    // Quit
-                    break;
+        // Read event, waking up in time to draw a deferred frame if needed
+        let event = if needs_redraw {
+            let until_next_frame = last_draw.map_or(Duration::ZERO, |last_draw| {
+                frame_interval.saturating_sub(last_draw.elapsed())
+            });
+            match event_rx.recv_timeout(until_next_frame) {
+                Ok(event) => Some(event),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(error @ RecvTimeoutError::Disconnected) => {
+                    return Err(error).context("error reading event")
                 }
             }
+        } else {
+            Some(event_rx.recv().context("error reading event")?)
+        };
+
+        // Stop if a signal asked pufferwatch to exit
+        if terminated.load(Ordering::Relaxed) {
+            info!("exiting because of a signal");
+            break;
+        }
+
+        // Handle event
+        if let Some(event) = event {
+            let _span = debug_span!("term_event", term_event=?event).entered();
             // Check for resize
-            AppEvent::TermEvent(Event::Resize(_, _)) => {
-                force_redraw = true;
+            if let AppEvent::TermEvent(Event::Resize(_, _)) = event {
+                needs_redraw = true;
+            }
+
+            needs_redraw |= renderer.update(&event);
+
+            // Check if quitting
+            if renderer.take_quit_request() {
+                break;
             }
-            _ => {}
         }
 
         // Update log from source if needed
-        renderer = renderer
+        let notes_path = NotesFile::path(renderer.borrow_log());
+        let log_updated;
+        (renderer, log_updated) = renderer
             .update_from(source.as_mut())
             .context("error updating renderer with new log")?;
+        needs_redraw |= log_updated;
+
+        // A replaced log has its own notes
+        if persist_notes && log_updated && NotesFile::path(renderer.borrow_log()) != notes_path {
+            renderer.reload_notes();
+        }
+
+        // Exit if the log hasn't changed in a while
+        if log_updated {
+            last_log_update = Instant::now();
+        } else if idle_timeout.is_some_and(|timeout| last_log_update.elapsed() >= timeout) {
+            info!("exiting because the log is idle");
+            idle = true;
+            break;
+        }
+
+        // Draw terminal, limiting how often frames are drawn
+        let frame_ready = last_draw.is_none_or(|last_draw| last_draw.elapsed() >= frame_interval);
+        if needs_redraw && frame_ready {
+            renderer.draw(terminal).context("error rendering frame")?;
+            last_draw = Some(Instant::now());
+            needs_redraw = false;
+        }
+
+        // Copy text to the clipboard if requested
+        if let Some(text) = renderer.take_copy_request() {
+            if let Err(error) = copy_to_clipboard(&mut clipboard, text) {
+                warn!(?error, "error copying to clipboard");
+            }
+        }
+
+        // Save the screen to a file if requested
+        if renderer.take_save_screen_request() {
+            let notice = match renderer.save_screen(terminal) {
+                Ok(path) => format!("Saved screen to {}", path.display()),
+                Err(error) => {
+                    warn!(?error, "error saving screen");
+                    "Couldn't save the screen".to_string()
+                }
+            };
+            renderer.set_notice(notice);
+            needs_redraw = true;
+        }
+
+        // Save notes on messages if they changed
+        if persist_notes {
+            if let Err(error) = renderer.save_changed_notes() {
+                warn!(?error, "error saving notes file");
+            }
+        }
+
+        // Ring the bell if new messages need attention
+        if renderer.take_bell_request() {
+            if let Err(error) = ring_bell(terminal) {
+                warn!(?error, "error ringing bell");
+            }
+        }
 
-        // Draw terminal
-        renderer
-            .render(terminal, &event, force_redraw)
-            .context("error rendering frame")?;
+        // Open the log in an external editor if requested
+        if renderer.take_open_editor_request() {
+            if let Some(path) = source.path() {
+                // Stop reading terminal events so they go to the editor instead
+                drop(event_controller);
+                if let Err(error) = open_in_editor(terminal, path, capture_mouse) {
+                    warn!(?error, "error opening log in editor");
+                }
+                (event_rx, event_controller) = EventController::start();
+                needs_redraw = true;
+            }
+        }
+    }
+
+    Ok(LoopExit {
+        filters: renderer.saved_filters(),
+        log: renderer.into_log(),
+        idle,
+        terminated: terminated.load(Ordering::Relaxed),
+    })
+}
+
+/// Rings the terminal bell.
+fn ring_bell(terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>) -> anyhow::Result<()> {
+    let backend = terminal.backend_mut();
+    backend.write_all(b"\x07").context("error writing bell")?;
+    Write::flush(backend).context("error flushing terminal")
+}
+
+/// Offers to copy the lines around where the log couldn't be parsed, so they
+/// can be included in a bug report.
+fn offer_to_copy_parse_failure(error: &anyhow::Error) {
+    let Some(failure) = error
+        .chain()
+        .find_map(|error| error.downcast_ref::<ParseFailure>())
+    else {
+        return;
+    };
+    if !stdin().is_terminal() {
+        return;
+    }
+
+    println!(
+        "The log couldn't be parsed at line {}. To report this, press c and Enter to copy the \
+         lines around it, or press Enter to exit.",
+        failure.line
+    );
+    let mut answer = String::new();
+    if stdin().read_line(&mut answer).is_err() || !answer.trim().eq_ignore_ascii_case("c") {
+        return;
+    }
+
+    let mut clipboard = None;
+    match copy_to_clipboard(&mut clipboard, failure.report()) {
+        Ok(()) => {
+            // The copied text is lost on some platforms once the clipboard is
+            // closed, so wait until it's been pasted
+            println!("Copied. Press Enter to exit once you've pasted it.");
+            let _ = stdin().read_line(&mut answer);
+        }
+        Err(error) => eprintln!("Unable to copy to the clipboard: {error:#}"),
+    }
+}
+
+/// Reads the text in the clipboard.
+fn read_clipboard() -> anyhow::Result<String> {
+    let mut clipboard = Clipboard::new().context("unable to access the clipboard")?;
+    let contents = match clipboard.get_text() {
+        Ok(contents) => contents,
+        Err(arboard::Error::ContentNotAvailable) => bail!("the clipboard doesn't contain any text"),
+        Err(error) => return Err(error).context("error reading the clipboard"),
+    };
+    if contents.trim().is_empty() {
+        bail!("the clipboard is empty");
+    }
+
+    Ok(contents)
+}
+
+/// Copies text to the clipboard. The clipboard is kept open afterwards since
+/// on some platforms, the copied text is lost once it's closed.
+fn copy_to_clipboard(clipboard: &mut Option<Clipboard>, text: String) -> anyhow::Result<()> {
+    let clipboard = match clipboard {
+        Some(clipboard) => clipboard,
+        None => clipboard.insert(Clipboard::new().context("error opening clipboard")?),
+    };
+    clipboard.set_text(text).context("error copying text")
+}
+
+/// Switches the terminal to the alternate screen and raw mode.
+fn enter_tui(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    capture_mouse: bool,
+) -> anyhow::Result<()> {
+    terminal.backend_mut().execute(EnterAlternateScreen)?;
+    if capture_mouse {
+        terminal.backend_mut().execute(EnableMouseCapture)?;
+    }
+    crossterm::terminal::enable_raw_mode()?;
+    terminal.hide_cursor()?;
+    terminal.clear()?;
+    Ok(())
+}
+
+/// Creates the terminal and enters the TUI. If the terminal can't show the
+/// TUI, like when the output isn't a terminal or it doesn't support raw mode,
+/// the terminal is restored as well as possible and the error is returned.
+fn open_tui(capture_mouse: bool) -> anyhow::Result<Terminal<CrosstermBackend<std::io::Stdout>>> {
+    if !stdout().is_terminal() {
+        bail!("the output isn't a terminal");
+    }
+
+    let backend = CrosstermBackend::new(stdout());
+    let mut terminal = Terminal::new(backend).context("error getting terminal size")?;
+    trace!("entering alternate screen");
+    if let Err(error) = enter_tui(&mut terminal, capture_mouse) {
+        drop(leave_tui(&mut terminal, capture_mouse));
+        return Err(error);
+    }
+
+    Ok(terminal)
+}
+
+/// Prints the log as plain text instead of showing the TUI. If the log is
+/// being followed, new lines are printed as they arrive until it's idle or
+/// pufferwatch is asked to stop.
+#[instrument(skip_all)]
+fn print_loop(
+    mut log: Log,
+    mut source: Box<dyn LogSource>,
+    idle_timeout: Option<Duration>,
+    terminated: &AtomicBool,
+) -> anyhow::Result<LoopExit> {
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+    let mut stdout = stdout();
+    let mut print = |text: &str| {
+        stdout
+            .write_all(text.as_bytes())
+            .and_then(|()| stdout.flush())
+            .context("error printing log")
+    };
+    let mut last_log_update = Instant::now();
+    let mut idle = false;
+    print(log.raw())?;
+    loop {
+        if !source.is_following() || terminated.load(Ordering::Relaxed) {
+            break;
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+        if let Some(new_log) = source.update_log(&log).context("error updating log")? {
+            // Only print what hasn't been printed yet
+            print(new_log.text_added_since(&log))?;
+            log = new_log;
+            last_log_update = Instant::now();
+        } else if idle_timeout.is_some_and(|timeout| last_log_update.elapsed() >= timeout) {
+            info!("exiting because the log is idle");
+            idle = true;
+            break;
+        }
+    }
+
+    Ok(LoopExit {
+        log,
+        idle,
+        terminated: terminated.load(Ordering::Relaxed),
+        filters: None,
+    })
+}
+
+/// Restores the terminal to how it was before entering the TUI.
+fn leave_tui(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    capture_mouse: bool,
+) -> anyhow::Result<()> {
+    if capture_mouse {
+        terminal.backend_mut().execute(DisableMouseCapture)?;
+    }
+    terminal.backend_mut().execute(LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    crossterm::terminal::disable_raw_mode()?;
+    Ok(())
+}
+
+/// Suspends the TUI and opens a file in the user's editor, restoring the TUI
+/// once the editor exits.
+#[instrument(skip(terminal))]
+fn open_in_editor(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    path: &Path,
+    capture_mouse: bool,
+) -> anyhow::Result<()> {
+    // Get the editor command
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| {
+            if cfg!(windows) {
+                "notepad".to_string()
+            } else {
+                "vi".to_string()
+            }
+        });
+    let mut editor_args = editor.split_whitespace();
+    let program = editor_args.next().context("editor command is empty")?;
+
+    // Leave the TUI
+    leave_tui(terminal, capture_mouse)?;
+
+    // Run the editor
+    info!(%editor, "opening log in editor");
+    let status = std::process::Command::new(program)
+        .args(editor_args)
+        .arg(path)
+        .status();
+
+    // Restore the TUI
+    enter_tui(terminal, capture_mouse)?;
+
+    let status = status.with_context(|| format!("error starting editor: {editor}"))?;
+    if !status.success() {
+        warn!(%status, "editor exited unsuccessfully");
     }
 
     Ok(())
@@ -109,73 +792,281 @@ fn render_loop(
 #[instrument(skip_all)]
 fn get_source(
     command: AppCommand,
-) -> Result<(Box<dyn LogSource>, Log, Option<EncodedWriter<ChildStdin>>), anyhow::Error> {
+    compare_contents: bool,
+    format: TimestampFormat,
+) -> Result<
+    (
+        Box<dyn LogSource>,
+        Log,
+        Option<EncodedWriter<CommandPipe>>,
+        Option<Child>,
+    ),
+    anyhow::Error,
+> {
     fn resolve_log_path(log_path: Option<PathBuf>) -> anyhow::Result<PathBuf> {
         log_path
-            .map(PathBuf::from)
             .or_else(default_log_path)
             .context("unable to find log path")
     }
 
+    let follow = command.is_following();
     Ok(match command {
-        AppCommand::Monitor(MonitorCommand { log: path, follow }) => {
+        AppCommand::Monitor(MonitorCommand {
+            log: path,
+            from_offset,
+            command_pipe,
+            ..
+        }) => {
             let log_path = resolve_log_path(path)?;
+            if archive::is_zip(&log_path) {
+                if from_offset > 0 {
+                    bail!("--from-offset can't be used with zip archives");
+                }
+                if follow {
+                    warn!("zip archives can't be followed, reading the log once instead");
+                }
+
+                let contents = read_zip_log(&log_path)?;
+                let (source, log) = StaticLogSource::from_string(contents, format)
+                    .context("error creating log source")?;
+                return Ok((Box::new(source), log, None, None));
+            }
+            if archive::is_gzip_file(&log_path) {
+                if from_offset > 0 {
+                    bail!("--from-offset can't be used with gzipped logs");
+                }
+                if follow {
+                    warn!("gzipped logs can't be followed, reading the log once instead");
+                }
+
+                let contents = archive::read_gzip_log(&log_path)?;
+                let (source, log) = StaticLogSource::from_string(contents, format)
+                    .context("error creating log source")?;
+                return Ok((Box::new(source), log, None, None));
+            }
+
+            let stdin = command_pipe
+                .map(|command_pipe| {
+                    info!(command_pipe=%command_pipe.display(), "opening command pipe");
+                    OpenOptions::new()
+                        .write(true)
+                        .open(&command_pipe)
+                        .with_context(|| {
+                            format!("error opening command pipe: {}", command_pipe.display())
+                        })
+                })
+                .transpose()?
+                .map(|file| EncodedWriter::utf8(CommandPipe::File(file)));
             if follow {
                 let (source, log) =
-                    FollowedLogSource::new(log_path).context("error creating log source")?;
-                (Box::new(source), log, None)
+                    FollowedLogSource::from_offset(log_path, from_offset, compare_contents, format)
+                        .context("error creating log source")?;
+                (Box::new(source), log, stdin, None)
             } else {
-                let (source, log) =
-                    StaticLogSource::from_file(&log_path).context("error creating log source")?;
-                (Box::new(source), log, None)
+                let (source, log) = StaticLogSource::from_file(&log_path, from_offset, format)
+                    .context("error creating log source")?;
+                (Box::new(source), log, stdin, None)
             }
         }
-        AppCommand::Stdin(StdinCommand) => {
-            let source = ReaderLogSource::from_stdin();
+        AppCommand::Stdin(StdinCommand { merge: None }) => {
+            let source = ReaderLogSource::from_stdin(format);
             let log = Log::empty();
-            (Box::new(source), log, None)
+            (Box::new(source), log, None, None)
         }
-        AppCommand::Remote(RemoteCommand { url }) => {
+        AppCommand::Stdin(StdinCommand {
+            merge: Some(log_path),
+        }) => {
+            let (source, log) = MergedLogSource::new(log_path, compare_contents, format)
+                .context("error creating log source")?;
+            (Box::new(source), log, None, None)
+        }
+        AppCommand::Remote(RemoteCommand { url, .. }) => {
             println!("Fetching remote log...");
-            info!("fetching remote log");
-            let contents = Client::new()
-                .get(url)
-                .send()
-                .context("error retrieving remote log")?
-                .text()
-                .context("error reading remote log")?;
-            let (source, log) =
-                StaticLogSource::from_string(contents).context("error creating log source")?;
-            (Box::new(source), log, None)
+            if follow {
+                let (source, log) =
+                    RemoteLogSource::new(url, format).context("error creating log source")?;
+                (Box::new(source), log, None, None)
+            } else {
+                let contents = fetch_remote_log(url)?;
+                let (source, log) = StaticLogSource::from_string(contents, format)
+                    .context("error creating log source")?;
+                (Box::new(source), log, None, None)
+            }
+        }
+        AppCommand::Clipboard => {
+            let contents = read_clipboard()?;
+            let (source, log) = StaticLogSource::from_string(contents, format)
+                .context("error creating log source")?;
+            (Box::new(source), log, None, None)
         }
         AppCommand::Run(RunCommand {
             smapi_path,
+            attach,
+            install_index,
             smapi_args,
             log,
             encoding,
+            env_vars,
+            cwd,
+            watch_dir,
+            ..
         }) => {
-            // Start SMAPI
-            let smapi_path = smapi_path
-                .or_else(|| get_install_paths().into_iter().next().map(executable_path))
-                .context("unable to find game path")?;
-            info!(smapi_path=%smapi_path.display(), "starting SMAPI");
-            let process = spawn_smapi(&smapi_path, smapi_args.iter().map(AsRef::as_ref))?;
+            // Start SMAPI, or connect to the one that's already running
+            let (process, stdin) = if attach {
+                let stdin = attach_to_smapi()
+                    .inspect_err(|error| warn!(?error, "unable to send commands to SMAPI"))
+                    .ok()
+                    .map(CommandPipe::File);
+                (None, stdin)
+            } else {
+                let mut process =
+                    start_smapi(smapi_path, install_index, &smapi_args, &env_vars, cwd)?;
+                let stdin = process.stdin.take().map(CommandPipe::Child);
+                (Some(process), stdin)
+            };
 
             // Follow log file
             let log_path = resolve_log_path(log)?;
-            let (source, log) =
-                FollowedLogSource::new(log_path).context("error creating log source")?;
-            (
-                Box::new(source),
-                log,
-                process
-                    .stdin
-                    .map(|stdin| create_encoded_writer(stdin, encoding)),
-            )
+            let watched_dir = watch_dir
+                .then(|| {
+                    let dir = log_path.parent()?;
+                    DirectoryLogSource::new(dir, log_path.clone(), compare_contents, format)
+                        .inspect_err(|error| warn!(?error, "error watching log directory"))
+                        .ok()
+                })
+                .flatten();
+            let (source, log): (Box<dyn LogSource>, _) = if let Some((source, log)) = watched_dir {
+                (Box::new(source), log)
+            } else {
+                let (source, log) = FollowedLogSource::new(log_path, compare_contents, format)
+                    .context("error creating log source")?;
+                (Box::new(source), log)
+            };
+            let stdin = stdin.map(|stdin| create_encoded_writer(stdin, encoding));
+            (source, log, stdin, process)
+        }
+        AppCommand::Grep(_) | AppCommand::Export(_) => {
+            unreachable!("grep and export are handled before the TUI starts")
         }
     })
 }
 
+/// Reads the whole log for commands that don't start the TUI. A path of -
+/// reads the log from stdin.
+#[instrument(skip_all)]
+fn read_log(path: Option<&Path>, url: Option<Url>, format: TimestampFormat) -> anyhow::Result<Log> {
+    if let Some(url) = url {
+        return Log::parse_as(fetch_remote_log(url)?, format);
+    }
+
+    match path {
+        Some(path) if path.as_os_str() == "-" => {
+            let mut contents = Vec::new();
+            stdin()
+                .read_to_end(&mut contents)
+                .context("error reading log from stdin")?;
+            Log::parse_as(archive::decode_log(contents)?, format)
+        }
+        path => {
+            let path = path
+                .map(Path::to_path_buf)
+                .or_else(default_log_path)
+                .context("unable to find log path")?;
+            if archive::is_zip(&path) {
+                return Log::parse_as(read_zip_log(&path)?, format);
+            }
+            if archive::is_gzip_file(&path) {
+                return Log::parse_as(archive::read_gzip_log(&path)?, format);
+            }
+            Log::parse_file(&path, format)
+        }
+    }
+}
+
+/// Finds the Stardew Valley install to run SMAPI from. When several installs
+/// are found, the user is asked which one to use unless `install_index`
+/// already chose one. If there's nobody to ask, the first one is used.
+fn choose_install_path(install_index: Option<usize>) -> anyhow::Result<PathBuf> {
+    let mut installs: Vec<PathBuf> = get_install_paths().into_iter().collect();
+    let count = installs.len();
+    if let Some(index) = install_index {
+        return index
+            .checked_sub(1)
+            .filter(|&index| index < count)
+            .map(|index| installs.swap_remove(index))
+            .with_context(|| format!("install {index} not found, only found {count} installs"));
+    }
+
+    match count {
+        0 => bail!("unable to find game path"),
+        1 => return Ok(installs.remove(0)),
+        _ => {}
+    }
+    if !stdin().is_terminal() {
+        warn!(path=%installs[0].display(), "found several installs, using the first one");
+        return Ok(installs.remove(0));
+    }
+
+    println!("Found several Stardew Valley installs (use --install-index to skip this):");
+    for (index, path) in installs.iter().enumerate() {
+        println!(
+            "  {}) {:5} {}",
+            index + 1,
+            InstallKind::of(path),
+            path.display()
+        );
+    }
+    let index = ask_choice("Which one should SMAPI run from?", count)?;
+    Ok(installs.swap_remove(index))
+}
+
+/// Reads the SMAPI log out of a zip archive, like one attached to a bug
+/// report. If the archive has several SMAPI logs, the user is asked which one
+/// to read. If there's nobody to ask, the largest one is used.
+fn read_zip_log(path: &Path) -> anyhow::Result<String> {
+    let mut zip = archive::open(path)?;
+    let logs = archive::find_logs(&mut zip)?;
+    let smapi_logs = logs.iter().filter(|entry| entry.is_smapi_log()).count();
+    let index = if smapi_logs > 1 && stdin().is_terminal() {
+        println!("Found several logs in {}:", path.display());
+        for (index, entry) in logs[..smapi_logs].iter().enumerate() {
+            println!("  {}) {} ({} KB)", index + 1, entry.name, entry.size / 1024);
+        }
+        ask_choice("Which one should be opened?", smapi_logs)?
+    } else {
+        0
+    };
+
+    let entry = logs
+        .get(index)
+        .with_context(|| format!("no logs found in zip archive: {}", path.display()))?;
+    info!(path=%path.display(), entry=%entry.name, "reading log from zip archive");
+    archive::read_log(&mut zip, &entry.name)
+}
+
+/// Asks the user to pick one of several numbered choices, asking again until
+/// the answer is valid. Returns the index of the choice, starting at 0. The
+/// first choice is the default.
+fn ask_choice(question: &str, count: usize) -> anyhow::Result<usize> {
+    loop {
+        print!("{question} [1-{count}, default 1] ");
+        stdout().flush().context("error writing prompt")?;
+        let mut answer = String::new();
+        let read = stdin()
+            .read_line(&mut answer)
+            .context("error reading answer")?;
+        let answer = answer.trim();
+        if read == 0 || answer.is_empty() {
+            return Ok(0);
+        }
+
+        match answer.parse::<usize>() {
+            Ok(index) if (1..=count).contains(&index) => return Ok(index - 1),
+            _ => println!("Enter a number from 1 to {count}."),
+        }
+    }
+}
+
 #[cfg(windows)]
 fn executable_path(install_path: impl AsRef<Path>) -> PathBuf {
     install_path.as_ref().join("StardewModdingAPI.exe")
@@ -189,9 +1080,18 @@ fn executable_path(install_path: impl AsRef<Path>) -> PathBuf {
 fn spawn_smapi<'a>(
     smapi_path: &'a Path,
     args: impl IntoIterator<Item = &'a OsStr>,
+    env_vars: &[EnvVar],
+    cwd: Option<&Path>,
 ) -> anyhow::Result<Child> {
     let mut cmd = std::process::Command::new(smapi_path);
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+
     let cmd = args.into_iter().fold(&mut cmd, |cmd, arg| cmd.arg(arg));
+    let cmd = env_vars
+        .iter()
+        .fold(cmd, |cmd, var| cmd.env(&var.key, &var.value));
     let child = cmd
         .stdin(Stdio::piped())
         .stdout(Stdio::null())
@@ -200,6 +1100,84 @@ fn spawn_smapi<'a>(
     Ok(child)
 }
 
+/// Starts SMAPI, asking which install to use if no path is given.
+fn start_smapi(
+    smapi_path: Option<PathBuf>,
+    install_index: Option<usize>,
+    smapi_args: &[OsString],
+    env_vars: &[EnvVar],
+    cwd: Option<PathBuf>,
+) -> anyhow::Result<Child> {
+    let smapi_path = match smapi_path {
+        Some(smapi_path) => smapi_path,
+        None => executable_path(choose_install_path(install_index)?),
+    };
+    let smapi_path = smapi_path.canonicalize().unwrap_or(smapi_path);
+    let cwd = cwd.or_else(|| {
+        smapi_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+    });
+    info!(smapi_path=%smapi_path.display(), ?cwd, "starting SMAPI");
+    spawn_smapi(
+        &smapi_path,
+        smapi_args.iter().map(AsRef::as_ref),
+        env_vars,
+        cwd.as_deref(),
+    )
+}
+
+/// Opens the console input of the SMAPI process that's already running, so
+/// commands can be sent to it. This only works when SMAPI reads its input
+/// from a pipe, since there's no way to type into another process's terminal.
+#[cfg(target_os = "linux")]
+#[instrument(level = "debug")]
+fn attach_to_smapi() -> anyhow::Result<File> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let processes = std::fs::read_dir("/proc").context("error listing processes")?;
+    let process_dir = processes
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| {
+            std::fs::read_to_string(path.join("comm"))
+                .is_ok_and(|name| name.trim_end().starts_with("StardewModding"))
+        })
+        .context("SMAPI isn't running")?;
+    let stdin_path = process_dir.join("fd/0");
+    let is_pipe = std::fs::metadata(&stdin_path)
+        .with_context(|| {
+            format!(
+                "error reading SMAPI's console input: {}",
+                stdin_path.display()
+            )
+        })?
+        .file_type()
+        .is_fifo();
+    if !is_pipe {
+        bail!("SMAPI's console input isn't a pipe");
+    }
+
+    debug!(stdin_path=%stdin_path.display(), "attaching to SMAPI");
+    OpenOptions::new()
+        .write(true)
+        .open(&stdin_path)
+        .with_context(|| {
+            format!(
+                "error opening SMAPI's console input: {}",
+                stdin_path.display()
+            )
+        })
+}
+
+/// Opens the console input of the SMAPI process that's already running. This
+/// isn't supported outside of Linux.
+#[cfg(not(target_os = "linux"))]
+fn attach_to_smapi() -> anyhow::Result<File> {
+    bail!("sending commands to a running SMAPI is only supported on Linux")
+}
+
 fn create_encoded_writer<W>(writer: W, encoding: CommandEncoding) -> EncodedWriter<W>
 where
     W: Write,
@@ -243,61 +1221,261 @@ fn setup_tracing(log_path: Option<&Path>) -> anyhow::Result<()> {
     }
 }
 
+/// Gets the path to the SMAPI log. If there is no log in the usual place, the
+/// log for a custom installation is used instead if it exists.
 fn default_log_path() -> Option<PathBuf> {
     #[cfg(not(target_os = "macos"))]
-    let mut path = dirs::config_dir()?;
+    let data_path = dirs::config_dir();
 
     #[cfg(target_os = "macos")]
-    let mut path = {
-        let mut path = dirs::home_dir()?;
-        path.push(".config");
-        path
-    };
+    let data_path = dirs::home_dir().map(|home| home.join(".config"));
+
+    let path =
+        data_path.map(|data_path| data_path.join("StardewValley/ErrorLogs/SMAPI-latest.txt"));
+    if path.as_ref().is_some_and(|path| path.is_file()) {
+        return path;
+    }
+
+    get_custom_log_paths()
+        .into_iter()
+        .find(|path| path.is_file())
+        .or(path)
+}
 
-    path.push("StardewValley/ErrorLogs/SMAPI-latest.txt");
-    Some(path)
+/// A read-only copy of a followed log, which can be viewed while the live log
+/// keeps updating.
+#[self_referencing]
+struct Snapshot {
+    log: Log,
+    #[borrows(log)]
+    #[covariant]
+    root_state: RootState<'this>,
 }
 
 #[self_referencing]
 struct Renderer {
     log: Log,
+    /// A newer log that isn't shown yet because the view is frozen.
+    buffered: Option<Log>,
+    /// A snapshot of the log, and whether it's shown instead of the live log.
+    snapshot: Option<(Snapshot, bool)>,
     #[borrows(log)]
     #[covariant]
     root_state: Option<RootState<'this>>,
 }
 
 impl Renderer {
-    pub fn from_log(log: Log, smapi_stdin: Option<EncodedWriter<ChildStdin>>) -> Self {
-        Renderer::new(log, |log| Some(RootState::new(log, smapi_stdin)))
+    pub fn from_log(
+        log: Log,
+        smapi_stdin: Option<EncodedWriter<CommandPipe>>,
+        options: RootOptions,
+    ) -> Self {
+        Renderer::new(log, None, None, |log| {
+            Some(RootState::new(log, smapi_stdin, options))
+        })
+    }
+
+    /// Calls a function with the state of the log being shown, which is
+    /// either the snapshot or the live log.
+    fn with_shown_root_state<R>(&mut self, f: impl FnOnce(&mut RootState<'_>) -> R) -> Option<R> {
+        self.with_mut(|fields| match fields.snapshot {
+            Some((snapshot, true)) => Some(snapshot.with_root_state_mut(f)),
+            _ => fields.root_state.as_mut().map(f),
+        })
+    }
+
+    /// Updates the UI state. Returns `true` if the event was handled.
+    pub fn update(&mut self, event: &AppEvent) -> bool {
+        let (handled, snapshot_request) = self
+            .with_shown_root_state(|root_state| {
+                (root_state.update(event), root_state.take_snapshot_request())
+            })
+            .unwrap_or_default();
+        match snapshot_request {
+            Some(SnapshotRequest::Take) => self.take_snapshot(),
+            Some(SnapshotRequest::Switch) => self.with_snapshot_mut(|snapshot| {
+                if let Some((_, shown)) = snapshot {
+                    *shown = !*shown;
+                }
+            }),
+            None => {}
+        }
+        handled
+    }
+
+    /// Copies the live log into a snapshot and shows it, replacing the
+    /// previous snapshot.
+    fn take_snapshot(&mut self) {
+        self.with_mut(|fields| {
+            let Some(root_state) = fields.root_state.as_mut() else {
+                return;
+            };
+            let snapshot = Snapshot::new(fields.log.clone(), |log| root_state.snapshot(log));
+            *fields.snapshot = Some((snapshot, true));
+            root_state.set_has_snapshot(true);
+        });
+    }
+
+    pub fn draw<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> anyhow::Result<()> {
+        self.with_shown_root_state(|root_state| {
+            terminal
+                .draw(|f| f.render_stateful_widget(Root::default(), f.size(), root_state))
+                .context("error rendering frame")?;
+
+            Ok(())
+        })
+        .context("missing root state")?
     }
 
-    pub fn render<'t, B: Backend>(
+    /// Draws the shown state again and saves the frame to a file. Returns the
+    /// path of the file.
+    pub fn save_screen<B: Backend>(
         &mut self,
-        terminal: &'t mut Terminal<B>,
-        event: &AppEvent,
-        force_redraw: bool,
-    ) -> anyhow::Result<()> {
-        self.with_root_state_mut(|root_state| {
-            let root_state = root_state.as_mut().context("missing root state")?;
-            if root_state.update(event) || force_redraw {
-                terminal
-                    .draw(|f| f.render_stateful_widget(Root::default(), f.size(), root_state))
-                    .context("error rendering frame")?;
+        terminal: &mut Terminal<B>,
+    ) -> anyhow::Result<PathBuf> {
+        self.with_shown_root_state(|root_state| {
+            let frame = terminal
+                .draw(|f| f.render_stateful_widget(Root::default(), f.size(), root_state))
+                .context("error rendering frame")?;
+            save_screen(frame.buffer)
+        })
+        .context("missing root state")?
+    }
+
+    pub fn set_notice(&mut self, notice: String) {
+        self.with_shown_root_state(|root_state| root_state.set_notice(notice));
+    }
+
+    #[allow(clippy::redundant_closure_for_method_calls)]
+    pub fn take_save_screen_request(&mut self) -> bool {
+        self.with_shown_root_state(|root_state| root_state.take_save_screen_request())
+            .unwrap_or_default()
+    }
+
+    // The methods only work with one lifetime of the state, so they can't be
+    // passed in directly
+    #[allow(clippy::redundant_closure_for_method_calls)]
+    pub fn take_copy_request(&mut self) -> Option<String> {
+        self.with_shown_root_state(|root_state| root_state.take_copy_request())
+            .flatten()
+    }
+
+    /// Loads the notes saved for the shown log, replacing the current notes.
+    pub fn reload_notes(&mut self) {
+        self.with_mut(|fields| {
+            if let Some(root_state) = fields.root_state.as_mut() {
+                root_state.set_notes(load_notes(fields.log, true));
             }
+        });
+    }
 
-            Ok(())
+    /// Saves the notes on the log's messages if they changed.
+    pub fn save_changed_notes(&mut self) -> anyhow::Result<()> {
+        self.with_mut(|fields| {
+            let Some(notes) = fields
+                .root_state
+                .as_mut()
+                .and_then(RootState::take_changed_notes)
+            else {
+                return Ok(());
+            };
+            NotesFile::save(fields.log, notes)
         })
     }
 
-    pub fn update_from(mut self, source: &mut dyn LogSource) -> anyhow::Result<Self> {
-        let new_log = self.with_log(|log| source.update_log(log))?;
+    #[allow(clippy::redundant_closure_for_method_calls)]
+    pub fn take_quit_request(&mut self) -> bool {
+        self.with_shown_root_state(|root_state| root_state.take_quit_request())
+            .unwrap_or_default()
+    }
+
+    pub fn take_bell_request(&mut self) -> bool {
+        self.with_root_state_mut(|root_state| {
+            root_state
+                .as_mut()
+                .is_some_and(RootState::take_bell_request)
+        })
+    }
+
+    pub fn take_open_editor_request(&mut self) -> bool {
+        self.with_root_state_mut(|root_state| {
+            root_state
+                .as_mut()
+                .is_some_and(RootState::take_open_editor_request)
+        })
+    }
+
+    /// Updates the log from a source. Also returns whether the log changed.
+    /// While the view is frozen, the new log is buffered until it unfreezes.
+    pub fn update_from(mut self, source: &mut dyn LogSource) -> anyhow::Result<(Self, bool)> {
+        // Sources add to the newest log, even if it hasn't been shown yet
+        let new_log = match self.borrow_buffered() {
+            Some(buffered) => source.update_log(buffered)?,
+            None => self.with_log(|log| source.update_log(log))?,
+        };
+
+        let frozen = self
+            .with_root_state(|root_state| root_state.as_ref().is_some_and(RootState::is_frozen));
+        let new_log = match new_log {
+            Some(new_log) if frozen => {
+                // Buffer the new log and show how much is waiting
+                let buffered = new_log
+                    .messages()
+                    .len()
+                    .saturating_sub(self.borrow_log().messages().len());
+                self.with_mut(|fields| {
+                    *fields.buffered = Some(new_log);
+                    if let Some(root_state) = fields.root_state.as_mut() {
+                        root_state.set_buffered_messages(buffered);
+                    }
+                });
+                return Ok((self, true));
+            }
+            Some(new_log) => Some(new_log),
+            None if frozen => None,
+            None => self.with_buffered_mut(Option::take),
+        };
+
         if let Some(new_log) = new_log {
-            self.with_root_state_mut(|root_state| {
-                let root_state = root_state.take().context("missing root state")?;
-                Ok(Renderer::new(new_log, |log| Some(root_state.with_log(log))))
+            self.with_mut(|fields| {
+                let root_state = fields.root_state.take().context("missing root state")?;
+                let snapshot = fields.snapshot.take();
+                let renderer = Renderer::new(new_log, None, snapshot, |log| {
+                    Some(root_state.with_log(log))
+                });
+                Ok((renderer, true))
             })
         } else {
-            Ok(self)
+            Ok((self, false))
         }
     }
+
+    /// Gets the level and source filters of the live log.
+    pub fn saved_filters(&self) -> Option<SavedFilters> {
+        self.borrow_root_state()
+            .as_ref()
+            .map(RootState::saved_filters)
+    }
+
+    /// Consumes the renderer, returning the newest log.
+    pub fn into_log(self) -> Log {
+        let heads = self.into_heads();
+        heads.buffered.unwrap_or(heads.log)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+
+    #[test]
+    fn asks_smapi_to_exit_before_killing_it() {
+        let mut smapi = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let status = stop_smapi(&mut smapi).unwrap();
+        assert_eq!(status.signal(), Some(signal_hook::consts::SIGTERM));
+    }
 }
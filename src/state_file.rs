@@ -0,0 +1,70 @@
+use crate::ast::Level;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{io::ErrorKind, path::PathBuf};
+use tracing::{instrument, trace};
+
+/// State that is persisted between runs of pufferwatch.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct StateFile {
+    /// Whether the user has already seen the introduction screen.
+    pub seen_intro: bool,
+    /// The level and source filters from when pufferwatch was last closed.
+    pub filters: Option<SavedFilters>,
+}
+
+/// Filters that are restored when pufferwatch is opened again.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct SavedFilters {
+    /// The levels that are shown.
+    pub levels: Vec<Level>,
+    /// The sources that are hidden, including ones that weren't in the log.
+    pub disabled_sources: Vec<String>,
+}
+
+impl StateFile {
+    /// Gets the path to the state file.
+    pub fn path() -> Option<PathBuf> {
+        let mut path = dirs::config_dir()?;
+        path.push("pufferwatch/state.toml");
+        Some(path)
+    }
+
+    /// Loads the state file. If the state file does not exist, the default
+    /// state is returned instead.
+    #[instrument(level = "trace")]
+    pub fn load() -> anyhow::Result<Self> {
+        let path = Self::path().context("unable to find state file path")?;
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                trace!(?path, "state file not found");
+                return Ok(StateFile::default());
+            }
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("error reading state file: {}", path.display()))
+            }
+        };
+
+        toml::from_str(&contents)
+            .with_context(|| format!("error parsing state file: {}", path.display()))
+    }
+
+    /// Saves the state file, creating its parent directory if needed.
+    #[instrument(level = "trace", skip_all)]
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path().context("unable to find state file path")?;
+        if let Some(parent_dir) = path.parent() {
+            std::fs::create_dir_all(parent_dir).with_context(|| {
+                format!("error creating state directory: {}", parent_dir.display())
+            })?;
+        }
+
+        let contents = toml::to_string(self).context("error serializing state file")?;
+        std::fs::write(&path, contents)
+            .with_context(|| format!("error writing state file: {}", path.display()))
+    }
+}
@@ -1,19 +1,33 @@
 mod command_input;
 mod controls;
 mod formatted_log;
+mod histogram;
 mod icons;
+mod intro;
 mod lazy_paragraph;
+mod problems;
 mod raw_log;
 mod root;
 mod scrollbar;
 mod state;
+mod summary;
+#[cfg(test)]
+mod test_utils;
+mod theme;
+mod timeline;
 
 pub use command_input::*;
 pub use controls::*;
 pub use formatted_log::*;
+pub use histogram::*;
 pub use icons::*;
+pub use intro::*;
 pub use lazy_paragraph::*;
+pub use problems::*;
 pub use raw_log::*;
 pub use root::*;
 pub use scrollbar::*;
 pub use state::*;
+pub use summary::*;
+pub use theme::*;
+pub use timeline::*;
@@ -8,15 +8,16 @@ use tui::{
     buffer::Buffer,
     layout::Rect,
     style::{Modifier, Style},
-    text::Span,
     widgets::{Block, StatefulWidget, Widget},
 };
+use unicode_width::UnicodeWidthChar;
 
 #[derive(Clone, Default)]
 pub struct CommandInput<'i> {
     block: Option<Block<'i>>,
     style: Style,
     focused: bool,
+    prompt: &'i str,
 }
 
 impl<'i> CommandInput<'i> {
@@ -34,14 +35,78 @@ impl<'i> CommandInput<'i> {
         self.focused = focused;
         self
     }
+
+    /// Sets the prompt displayed before the input. The prompt is not part of
+    /// the submitted command.
+    pub fn prompt(mut self, prompt: &'i str) -> Self {
+        self.prompt = prompt;
+        self
+    }
+
+    /// Gets the number of rows needed to show the prompt and entered text
+    /// without scrolling, when long text is wrapped to a width.
+    pub fn rows(prompt: &str, state: &CommandInputState, width: u16) -> u16 {
+        let cells = CommandInput::default()
+            .prompt(prompt)
+            .cells(state)
+            .collect::<Vec<_>>();
+        wrap(&cells, width)
+            .last()
+            .map_or(1, |&(_, _, row)| row.saturating_add(1))
+    }
+
+    /// Gets each character of the prompt and entered text with its style,
+    /// including the cursor. There is always room for the cursor after the
+    /// text, so the number of rows doesn't change while typing at the end.
+    fn cells<'s>(&self, state: &'s CommandInputState) -> impl Iterator<Item = (char, Style)> + 's
+    where
+        'i: 's,
+    {
+        let style = self.style;
+        let cursor = self.focused.then_some(state.cursor);
+        let cursor_style = style.add_modifier(match state.edit_mode {
+            EditMode::Insert => Modifier::UNDERLINED,
+            EditMode::Overwrite => Modifier::REVERSED,
+        });
+        let prompt = self.prompt.chars().map(move |c| (c, style));
+        let text = state
+            .text
+            .chars()
+            .chain([' '])
+            .enumerate()
+            .map(move |(index, c)| {
+                if Some(index) == cursor {
+                    (c, cursor_style)
+                } else {
+                    (c, style)
+                }
+            });
+        prompt.chain(text)
+    }
+}
+
+/// Wraps characters to a width, getting the column and row of each one.
+fn wrap(cells: &[(char, Style)], width: u16) -> Vec<(u16, u16, u16)> {
+    let mut positions = Vec::with_capacity(cells.len());
+    let (mut x, mut y) = (0, 0);
+    for &(c, _) in cells {
+        let char_width = c.width().unwrap_or_default() as u16;
+        if x > 0 && x + char_width > width {
+            x = 0;
+            y += 1;
+        }
+        positions.push((x, char_width, y));
+        x += char_width;
+    }
+    positions
 }
 
-impl<'i> StatefulWidget for CommandInput<'i> {
+impl StatefulWidget for CommandInput<'_> {
     type State = CommandInputState;
 
-    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+    fn render(mut self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         // Render block
-        let inner_area = if let Some(block) = self.block {
+        let inner_area = if let Some(block) = self.block.take() {
             let inner = block.inner(area);
             block.render(area, buf);
             inner
@@ -49,44 +114,42 @@ impl<'i> StatefulWidget for CommandInput<'i> {
             area
         };
 
+        if inner_area.area() == 0 {
+            return;
+        }
+
+        // Wrap the input, scrolling to keep the cursor visible if it doesn't
+        // fit
+        let cells: Vec<_> = self.cells(state).collect();
+        let positions = wrap(&cells, inner_area.width);
+        let cursor_row = positions
+            .get(self.prompt.chars().count() + state.cursor)
+            .map_or(0, |&(_, _, row)| row);
+        let first_row = cursor_row.saturating_sub(inner_area.height - 1);
+
         // Render input
-        let spans = if self.focused {
-            vec![
-                Span::styled(state.before_cursor(), self.style),
-                Span::styled(
-                    state.at_cursor().map_or_else(|| " ".into(), String::from),
-                    self.style.add_modifier(match state.edit_mode {
-                        EditMode::Insert => Modifier::UNDERLINED,
-                        EditMode::Overwrite => Modifier::REVERSED,
-                    }),
-                ),
-                Span::styled(state.after_cursor(), self.style),
-            ]
-        } else {
-            vec![Span::styled(&state.text, self.style)]
-        };
-        let spans = spans.into();
-        buf.set_spans(
-            inner_area.left(),
-            inner_area.top(),
-            &spans,
-            inner_area.width,
-        );
+        for (&(c, style), &(x, width, row)) in cells.iter().zip(&positions) {
+            if row < first_row || row - first_row >= inner_area.height || width == 0 {
+                continue;
+            }
+
+            let x = inner_area.x + x;
+            let y = inner_area.y + row - first_row;
+            buf.get_mut(x, y).set_char(c).set_style(style);
+            if width > 1 && x + 1 < inner_area.right() {
+                buf.get_mut(x + 1, y).set_symbol("").set_style(style);
+            }
+        }
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
 enum EditMode {
+    #[default]
     Insert,
     Overwrite,
 }
 
-impl Default for EditMode {
-    fn default() -> Self {
-        EditMode::Insert
-    }
-}
-
 #[derive(Clone, Debug, Default)]
 pub struct CommandInputState {
     text: String,
@@ -94,24 +157,78 @@ pub struct CommandInputState {
     cursor: usize,
     submitted: Vec<String>,
     edit_mode: EditMode,
+    /// Previously submitted commands, oldest first.
+    history: Vec<String>,
+    /// The command being shown from the history, if any.
+    history_index: Option<usize>,
+    /// The text that was being entered before navigating the history.
+    draft: String,
 }
 
 impl CommandInputState {
-    fn before_cursor(&self) -> String {
-        self.text.chars().take(self.cursor).collect()
+    /// Gets the text that has been entered.
+    pub fn text(&self) -> &str {
+        &self.text
     }
 
-    fn at_cursor(&self) -> Option<char> {
-        self.text.chars().nth(self.cursor)
+    /// Replaces the entered text, moving the cursor to the end.
+    pub fn set_text(&mut self, text: String) {
+        self.cursor = text.chars().count();
+        self.text = text;
     }
 
-    fn after_cursor(&self) -> String {
-        self.text.chars().skip(self.cursor + 1).collect()
+    /// Clears the entered text and stops showing a command from the history.
+    /// The history is kept.
+    pub fn clear(&mut self) {
+        self.set_text(String::new());
+        self.history_index = None;
+        self.draft.clear();
     }
 
+    /// Takes the commands that were submitted since the last call. They're
+    /// also added to the history, skipping repeats of the previous command.
     pub fn take_submitted(&mut self) -> impl IntoIterator<Item = String> + '_ {
+        for command in &self.submitted {
+            if !command.is_empty() && self.history.last() != Some(command) {
+                self.history.push(command.clone());
+            }
+        }
         self.submitted.drain(..)
     }
+
+    /// Shows the command submitted before the one being shown, saving the
+    /// entered text to return to later.
+    fn history_previous(&mut self) -> bool {
+        let index = match self.history_index {
+            Some(0) => return true,
+            Some(index) => index - 1,
+            None if self.history.is_empty() => return false,
+            None => {
+                self.draft = std::mem::take(&mut self.text);
+                self.history.len() - 1
+            }
+        };
+        self.history_index = Some(index);
+        self.set_text(self.history[index].clone());
+        true
+    }
+
+    /// Shows the command submitted after the one being shown, or the entered
+    /// text after the most recent command.
+    fn history_next(&mut self) -> bool {
+        let Some(index) = self.history_index else {
+            return false;
+        };
+        if let Some(command) = self.history.get(index + 1) {
+            self.history_index = Some(index + 1);
+            self.set_text(command.clone());
+        } else {
+            self.history_index = None;
+            let draft = std::mem::take(&mut self.draft);
+            self.set_text(draft);
+        }
+        true
+    }
 }
 
 impl State for CommandInputState {
@@ -134,8 +251,12 @@ impl State for CommandInputState {
                 KeyCode::Enter => {
                     self.submitted.push(std::mem::take(&mut self.text));
                     self.cursor = 0;
+                    self.history_index = None;
+                    self.draft.clear();
                     true
                 }
+                KeyCode::Up => self.history_previous(),
+                KeyCode::Down => self.history_next(),
                 KeyCode::Left if self.cursor > 0 => {
                     self.cursor -= 1;
                     true
@@ -194,6 +315,9 @@ impl State for CommandInputState {
     fn add_controls<I: IconPack>(&self, controls: &mut IndexMap<BindingDisplay<I>, &'static str>) {
         controls.insert(BindingDisplay::simple_key(KeyCode::Enter), "Execute");
         controls.insert(BindingDisplay::Custom(I::LEFT_RIGHT), "Nav");
+        if !self.history.is_empty() {
+            controls.insert(BindingDisplay::Custom(I::UP_DOWN), "History");
+        }
         controls.insert(
             BindingDisplay::simple_key(KeyCode::Insert),
             match self.edit_mode {
@@ -203,3 +327,58 @@ impl State for CommandInputState {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::test_utils::{key, render_stateful, rows};
+
+    #[test]
+    fn wraps_long_commands() {
+        let mut state = CommandInputState::default();
+        state.set_text("player_add 74 999".to_string());
+        assert_eq!(CommandInput::rows("> ", &state, 20), 1);
+        assert_eq!(CommandInput::rows("> ", &state, 8), 3);
+
+        let widget = CommandInput::default().prompt("> ").focused(true);
+        let buffer = render_stateful(widget.clone(), &mut state, 8, 3);
+        assert_eq!(rows(&buffer), ["> player", "_add 74", "999"]);
+
+        // The cursor stays visible when the command doesn't fit
+        let buffer = render_stateful(widget.clone(), &mut state, 8, 1);
+        assert_eq!(rows(&buffer), ["999"]);
+        state.update(&key(KeyCode::Home));
+        let buffer = render_stateful(widget, &mut state, 8, 1);
+        assert_eq!(rows(&buffer), ["> player"]);
+        assert!(buffer.get(2, 0).modifier.contains(Modifier::UNDERLINED));
+    }
+
+    #[test]
+    fn recalls_submitted_commands() {
+        let mut state = CommandInputState::default();
+        for command in ["help", "list_items", "list_items"] {
+            state.set_text(command.to_string());
+            state.update(&key(KeyCode::Enter));
+        }
+        assert_eq!(
+            state.take_submitted().into_iter().collect::<Vec<_>>(),
+            ["help", "list_items", "list_items"]
+        );
+
+        // Repeated commands are only remembered once
+        state.set_text("debug".to_string());
+        state.update(&key(KeyCode::Up));
+        assert_eq!(state.text(), "list_items");
+        state.update(&key(KeyCode::Up));
+        assert_eq!(state.text(), "help");
+        state.update(&key(KeyCode::Up));
+        assert_eq!(state.text(), "help");
+
+        // Going past the most recent command restores the entered text
+        state.update(&key(KeyCode::Down));
+        assert_eq!(state.text(), "list_items");
+        state.update(&key(KeyCode::Down));
+        assert_eq!(state.text(), "debug");
+        assert!(!state.update(&key(KeyCode::Down)));
+    }
+}
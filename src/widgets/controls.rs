@@ -66,7 +66,7 @@ impl<I: IconPack> Display for BindingDisplay<I> {
                     .filter(|&(modifier, _)| modifiers.contains(modifier))
                     .map(|(_, modifier_icon)| modifier_icon);
                 for icon in modifier_icons {
-                    write!(f, "{}", icon)?;
+                    write!(f, "{icon}")?;
                 }
 
                 // Write key code
@@ -74,13 +74,13 @@ impl<I: IconPack> Display for BindingDisplay<I> {
                     KeyCode::BackTab => write!(f, "{}", I::BACKTAB_ICON),
                     KeyCode::Backspace => write!(f, "{}", I::BACKSPACE_ICON),
                     KeyCode::Char(' ') => write!(f, "{}", I::SPACE_ICON),
-                    KeyCode::Char(c) => write!(f, "{}", c),
+                    KeyCode::Char(c) => write!(f, "{c}"),
                     KeyCode::Delete => write!(f, "{}", I::DELETE_ICON),
                     KeyCode::Down => write!(f, "{}", I::DOWN_ICON),
                     KeyCode::End => write!(f, "{}", I::END_ICON),
                     KeyCode::Enter => write!(f, "{}", I::ENTER_ICON),
                     KeyCode::Esc => write!(f, "{}", I::ESC_ICON),
-                    KeyCode::F(n) => write!(f, "F{}", n),
+                    KeyCode::F(n) => write!(f, "F{n}"),
                     KeyCode::Home => write!(f, "{}", I::HOME_ICON),
                     KeyCode::Insert => write!(f, "{}", I::INSERT_ICON),
                     KeyCode::Left => write!(f, "{}", I::LEFT_ICON),
@@ -96,7 +96,7 @@ impl<I: IconPack> Display for BindingDisplay<I> {
             BindingDisplay::Mouse(MouseButton::Left) => write!(f, "M1"),
             BindingDisplay::Mouse(MouseButton::Right) => write!(f, "M2"),
             BindingDisplay::Mouse(MouseButton::Middle) => write!(f, "M3"),
-            BindingDisplay::Custom(label) => write!(f, "{}", label),
+            BindingDisplay::Custom(label) => write!(f, "{label}"),
             _ => Ok(()),
         }
     }
@@ -114,6 +114,41 @@ impl Controls {
     }
 }
 
+impl Controls {
+    /// Groups labels into lines that fit in the given width, stopping early if
+    /// a label doesn't fit on a line by itself.
+    fn wrap_labels<'a>(labels: &[Span<'a>], width: usize) -> Vec<Vec<Span<'a>>> {
+        let mut lines = Vec::new();
+        let mut line = Vec::new();
+        let mut remaining_width = width;
+        for label in labels {
+            let label_width = label.content.width();
+            if label_width > remaining_width {
+                // Check if empty line because area isn't big enough
+                if line.is_empty() {
+                    break;
+                }
+
+                lines.push(std::mem::take(&mut line));
+                remaining_width = width;
+                if label_width > remaining_width {
+                    break;
+                }
+            }
+
+            // Add label and padding
+            remaining_width = remaining_width.saturating_sub(label_width + 1);
+            line.push(label.clone());
+            line.push(Span::raw(" "));
+        }
+
+        if !line.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+}
+
 impl StatefulWidget for Controls {
     type State = ControlsState;
 
@@ -125,83 +160,40 @@ impl StatefulWidget for Controls {
         // Create the "More" label
         let more_label = Span::styled("More [.]", self.style);
 
-        // Get the available width for the controls, excluding the "More" label
-        let controls_width = usize::from(area.width).saturating_sub(more_label.content.width());
-        if controls_width == 0 {
-            return;
-        }
-
         // Get labels for each control
         let labels = state
             .controls
             .iter()
-            .map(|(control, label)| {
-                format!("{label} [{control}]", control = control, label = label)
-            })
-            .map(|label| Span::styled(label, self.style));
-
-        // Group labels into lines
-        let mut multi_page = false;
-        let lines = labels
-            .map(|label| {
-                let label_width = label.content.width();
-                (label, label_width)
-            })
-            .peekable()
-            .batching(|labels| {
-                let mut remaining_width = controls_width;
-                let mut line = Vec::new();
-                while let Some(&(_, label_width)) = labels.peek() {
-                    // Check if the label fits on the current line
-                    if let Some(new_remaining_width) = remaining_width.checked_sub(label_width) {
-                        // Label fits on the current line
-                        remaining_width = new_remaining_width.saturating_sub(1);
-
-                        // Add label and padding
-                        let (label, _) = labels.next().unwrap();
-                        line.push(label);
-                        line.push(Span::raw(" "));
-                    } else {
-                        // Check if empty page because area isn't big enough
-                        if line.is_empty() {
-                            return None;
-                        }
-
-                        // Add "More" label (for next page)
-                        line.push(more_label.clone());
-                        multi_page = true;
-                        return Some(Spans::from(line));
-                    }
-                }
-
-                if line.is_empty() {
-                    None
-                } else {
-                    if multi_page {
-                        // Add "More" label (for first page)
-                        line.push(more_label.clone());
-                    }
-
-                    Some(Spans::from(line))
-                }
-            })
-            .enumerate()
+            .map(|(control, label)| Span::styled(format!("{label} [{control}]"), self.style))
             .collect_vec();
 
+        // Group labels into lines, filling every row before paging. If there
+        // are too many for one page, leave room for the "More" label.
+        let height = usize::from(area.height);
+        let mut lines = Self::wrap_labels(&labels, area.width.into());
+        let multi_page = lines.len() > height;
+        if multi_page {
+            let controls_width = usize::from(area.width).saturating_sub(more_label.content.width());
+            if controls_width == 0 {
+                return;
+            }
+            lines = Self::wrap_labels(&labels, controls_width);
+        }
+
         // Get which rows to render
-        let area_height = usize::from(area.height);
-        let pages = lines.len() / area_height;
+        let pages = lines.len().div_ceil(height).max(1);
         state.page %= pages;
-        let start_row = state.page * area_height;
-
-        // Render the controls
-        let rendered_lines = lines.get(start_row..(start_row + area_height));
-        for (y, spans) in rendered_lines.into_iter().flatten() {
-            let y = match u16::try_from(y % area_height) {
-                Ok(y) => y,
-                Err(_) => break,
-            };
-            buf.set_spans(area.x, area.y.saturating_add(y), spans, area.width);
+        let page_lines = lines.into_iter().skip(state.page * height).take(height);
+
+        // Render the controls, with the "More" label at the end of the page
+        let mut page_lines = page_lines.peekable();
+        let mut y = area.y;
+        while let Some(mut line) = page_lines.next() {
+            if multi_page && page_lines.peek().is_none() {
+                line.push(more_label.clone());
+            }
+            buf.set_spans(area.x, y, &Spans::from(line), area.width);
+            y = y.saturating_add(1);
         }
     }
 }
@@ -236,3 +228,75 @@ impl State for ControlsState {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::test_utils::{key, render_stateful, rows};
+    use tui::style::Color;
+
+    fn state() -> ControlsState {
+        let mut state = ControlsState::default();
+        state.set_controls(
+            [
+                (KeyCode::Char('a'), "First"),
+                (KeyCode::Char('b'), "Second"),
+                (KeyCode::Char('c'), "Third"),
+                (KeyCode::Char('d'), "Fourth"),
+                (KeyCode::Char('e'), "Fifth"),
+            ]
+            .into_iter()
+            .map(|(code, label)| (BindingDisplay::simple_key(code), label))
+            .collect(),
+        );
+        state
+    }
+
+    fn next_page(state: &mut ControlsState) {
+        state.update(&key(KeyCode::Char('.')));
+    }
+
+    #[test]
+    fn shows_all_controls_when_they_fit() {
+        let style = Style::default().fg(Color::White).bg(Color::Blue);
+        let buffer = render_stateful(Controls::default().style(style), &mut state(), 60, 1);
+        assert_eq!(
+            rows(&buffer),
+            ["First [a] Second [b] Third [c] Fourth [d] Fifth [e]"]
+        );
+        assert_eq!(buffer.get(0, 0).bg, Color::Blue);
+    }
+
+    #[test]
+    fn pages_controls_that_dont_fit() {
+        let mut state = state();
+        let buffer = render_stateful(Controls::default(), &mut state, 40, 1);
+        assert_eq!(rows(&buffer), ["First [a] Second [b] Third [c] More [.]"]);
+
+        next_page(&mut state);
+        let buffer = render_stateful(Controls::default(), &mut state, 40, 1);
+        assert_eq!(rows(&buffer), ["Fourth [d] Fifth [e] More [.]"]);
+
+        // Paging past the last page goes back to the first
+        next_page(&mut state);
+        let buffer = render_stateful(Controls::default(), &mut state, 40, 1);
+        assert_eq!(rows(&buffer), ["First [a] Second [b] Third [c] More [.]"]);
+    }
+
+    #[test]
+    fn fills_every_row_before_paging() {
+        let mut state = state();
+        let buffer = render_stateful(Controls::default(), &mut state, 40, 2);
+        assert_eq!(
+            rows(&buffer),
+            ["First [a] Second [b] Third [c]", "Fourth [d] Fifth [e]"]
+        );
+
+        // A third row is left empty
+        let buffer = render_stateful(Controls::default(), &mut state, 40, 3);
+        assert_eq!(
+            rows(&buffer),
+            ["First [a] Second [b] Third [c]", "Fourth [d] Fifth [e]", ""]
+        );
+    }
+}
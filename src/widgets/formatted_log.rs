@@ -1,30 +1,63 @@
 use crate::{
-    ast::{Level, Message},
+    ast::{Level, Message, TimeOffset, Timestamp},
+    classify::{Category, MessageClassifier},
     events::AppEvent,
     log::Log,
-    widgets::{BindingDisplay, IconPack, LazyParagraph, LazyParagraphState, State, WithLog},
+    notes::move_notes,
+    state_file::SavedFilters,
+    widgets::{
+        highlight_matches, BindingDisplay, CommandInput, CommandInputState, IconPack,
+        LazyParagraph, LazyParagraphState, ProblemsList, ProblemsListState, SourceHistogram, State,
+        Theme, TimelineList, TimelineListState, WithLog,
+    },
 };
-use crossterm::event::{Event, KeyCode};
+use crossterm::event::{Event, KeyCode, MouseButton, MouseEventKind};
+use globset::{GlobBuilder, GlobMatcher};
 use indexmap::IndexMap;
-use itertools::{Either, Itertools};
+use itertools::Itertools;
+use regex::Regex;
+use serde::Deserialize;
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt::{Display, Formatter},
+    ops::Deref,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 use tracing::trace;
 use tui::{
     buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     text::{Span, Spans},
-    widgets::{Block, StatefulWidget},
+    widgets::{Block, Borders, Clear, StatefulWidget, Widget},
 };
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 #[derive(Clone, Debug, Default)]
 pub struct FormattedLog<'i> {
     block: Option<Block<'i>>,
     default_style: Style,
     show_colors: bool,
+    theme: Theme,
+    time_offset: Option<TimeOffset>,
+    auto_scroll_interval: Duration,
+    columns: ColumnLayout,
+    max_source_width: Option<usize>,
+    max_message_width: Option<usize>,
+    classifiers: Rc<[Box<dyn MessageClassifier>]>,
+    /// The number of bars in the histogram of a source's messages.
+    histogram_buckets: usize,
+    /// Whether the log has dates, so the timestamp column has room for them.
+    show_dates: bool,
 }
 
 impl<'i> FormattedLog<'i> {
+    /// The width of the elapsed time column, which fits gaps up to a day.
+    const ELAPSED_WIDTH: usize = 8;
+
     #[allow(dead_code)]
     pub fn block(mut self, block: Block<'i>) -> Self {
         self.block = Some(block);
@@ -41,149 +74,755 @@ impl<'i> FormattedLog<'i> {
         self
     }
 
-    fn get_level_color(level: Level) -> Color {
-        match level {
-            Level::Trace | Level::Debug => Color::DarkGray,
-            Level::Info => Color::White,
-            Level::Alert => Color::Magenta,
-            Level::Warn => Color::Yellow,
-            Level::Error => Color::Red,
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Sets the offset to shift displayed timestamps by.
+    pub fn time_offset(mut self, time_offset: Option<TimeOffset>) -> Self {
+        self.time_offset = time_offset;
+        self
+    }
+
+    pub fn auto_scroll_interval(mut self, auto_scroll_interval: Duration) -> Self {
+        self.auto_scroll_interval = auto_scroll_interval;
+        self
+    }
+
+    /// Sets which columns are shown before each message, and in what order.
+    pub fn columns(mut self, columns: ColumnLayout) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Sets the maximum width of the source column. Longer sources are cut
+    /// off with an ellipsis.
+    pub fn max_source_width(mut self, max_source_width: Option<usize>) -> Self {
+        self.max_source_width = max_source_width;
+        self
+    }
+
+    /// Sets the maximum width of each line of a message. Longer lines are cut
+    /// off with an ellipsis, except for the message at the top of the log.
+    pub fn max_message_width(mut self, max_message_width: Option<usize>) -> Self {
+        self.max_message_width = max_message_width;
+        self
+    }
+
+    /// Sets the classifiers used to color messages by category.
+    pub fn classifiers(mut self, classifiers: Rc<[Box<dyn MessageClassifier>]>) -> Self {
+        self.classifiers = classifiers;
+        self
+    }
+
+    /// Sets the number of bars the session is split into in the histogram of
+    /// a source's messages.
+    pub fn histogram_buckets(mut self, histogram_buckets: usize) -> Self {
+        self.histogram_buckets = histogram_buckets;
+        self
+    }
+
+    /// Gets the style of a message's contents. Messages in a category are
+    /// shown in the category's color instead of the level's.
+    fn contents_style(&self, message: &Message) -> Style {
+        let category = self
+            .classifiers
+            .iter()
+            .find_map(|classifier| classifier.classify(message));
+        match category {
+            Some(category) => self.default_style.fg(category.color),
+            None => self.theme.level_style(self.default_style, message.level),
         }
     }
 
-    fn render_logs(
+    /// Gets the width of the source column, given the width of the longest
+    /// source.
+    fn source_width(&self, longest_source: usize) -> usize {
+        self.max_source_width
+            .map_or(longest_source, |max| longest_source.min(max.max(1)))
+    }
+
+    /// Gets the width of the timestamp column. Shifted timestamps have room
+    /// for a marker showing whether they moved to another day, and logs with
+    /// dates have room for the date.
+    fn timestamp_width(&self) -> usize {
+        let time_width = if self.time_offset.is_some() { 10 } else { 8 };
+        if self.show_dates {
+            time_width + 11
+        } else {
+            time_width
+        }
+    }
+
+    /// Formats a timestamp. The date is only shown if `show_date` is set, and
+    /// is left blank otherwise so the times line up.
+    fn format_timestamp(&self, timestamp: Timestamp, show_date: bool) -> String {
+        let (timestamp, days) = match self.time_offset {
+            Some(time_offset) => timestamp.offset_by(time_offset),
+            None => (timestamp, 0),
+        };
+        let date = match timestamp.date.filter(|_| show_date) {
+            Some(date) => format!("{date} "),
+            None if self.show_dates => " ".repeat(11),
+            None => String::new(),
+        };
+        if self.time_offset.is_none() {
+            return format!("{date}{timestamp}");
+        }
+
+        match days.cmp(&0) {
+            Ordering::Less => format!("{date}{timestamp}{days}"),
+            Ordering::Equal => format!("{date}{timestamp}  "),
+            Ordering::Greater => format!("{date}{timestamp}+{days}"),
+        }
+    }
+
+    /// Formats the time since the previous message, leaving it blank if no
+    /// time passed so longer gaps stand out.
+    fn format_elapsed(elapsed: Option<Duration>) -> String {
+        let elapsed = elapsed
+            .filter(|elapsed| !elapsed.is_zero())
+            .map(format_gap)
+            .unwrap_or_default();
+        format!("{elapsed:>width$}", width = Self::ELAPSED_WIDTH)
+    }
+
+    /// Formats a level, with its symbol if the theme has one.
+    fn format_level(&self, level: Level) -> String {
+        match self.theme.level_symbol(level) {
+            Some(symbol) => format!("{symbol} {level:5}"),
+            None => format!("{level:5}"),
+        }
+    }
+
+    /// Gets the width of a column.
+    fn column_width(&self, column: Column, source_width: usize) -> usize {
+        match column {
+            Column::Timestamp => self.timestamp_width(),
+            Column::Elapsed => Self::ELAPSED_WIDTH,
+            Column::Level => self.theme.level_width(),
+            Column::Source => source_width,
+        }
+    }
+
+    /// Gets the offset of the source column from the start of each line, if
+    /// it's shown.
+    fn source_column_start(&self, source_width: usize, gutter_width: usize) -> Option<usize> {
+        let columns = self
+            .columns
+            .iter()
+            .take_while(|&&column| column != Column::Source);
+        let start = columns.fold(gutter_width, |start, &column| {
+            start + self.column_width(column, source_width) + 1
+        });
+        self.columns.contains(&Column::Source).then_some(start)
+    }
+
+    /// Formats a line of the log. Messages with a note attached are marked.
+    /// The message at the top of the log (`is_current`) shows its note after
+    /// the marker, and isn't cut off by the maximum message width. The source
+    /// of the latest error and matches of the search are highlighted.
+    fn format_line(
         &self,
-        area: Rect,
-        buf: &mut Buffer,
-        state: &mut <Self as StatefulWidget>::State,
-    ) {
+        formatted_line: &FormattedLine<'i>,
+        note: Option<&str>,
+        is_current: bool,
+        context: LineContext<'_>,
+    ) -> Spans<'i> {
+        let LineContext {
+            error_source,
+            source_width,
+            gutter_width,
+            ..
+        } = context;
         let style_override = (!self.show_colors).then_some(self.default_style);
-        let paragraph = LazyParagraph::new(|index| {
-            let formatted_line = state.lines.get(index)?;
-            let spans = match *formatted_line {
-                FormattedLine::Start { message, line } => {
-                    let mut spans = Vec::with_capacity(7);
-
-                    // Timestamp
+        let gutter_style = style_override.unwrap_or_else(|| self.default_style.fg(Color::DarkGray));
+        let level_style = style_override.unwrap_or_else(|| {
+            self.theme
+                .level_style(self.default_style, formatted_line.message().level)
+        });
+        let mut spans = Vec::with_capacity(2 * self.columns.len() + 2);
+        match *formatted_line {
+            FormattedLine::Start {
+                index,
+                message,
+                show_date,
+                elapsed,
+                ..
+            } => {
+                // Message number
+                if gutter_width > 0 {
                     spans.push(Span::styled(
-                        format!("{}", message.timestamp),
-                        self.default_style,
+                        format!("{:>width$} ", index + 1, width = gutter_width - 1),
+                        gutter_style,
                     ));
+                }
 
-                    // Padding
-                    spans.push(Span::styled(" ", self.default_style));
-
-                    // Level
-                    let level_style = style_override.unwrap_or_else(|| {
-                        self.default_style.fg(Self::get_level_color(message.level))
-                    });
-                    spans.push(Span::styled(format!("{:5}", message.level), level_style));
+                for &column in self.columns.iter() {
+                    match column {
+                        Column::Timestamp => spans.push(Span::styled(
+                            self.format_timestamp(message.timestamp, show_date),
+                            self.default_style,
+                        )),
+                        Column::Elapsed => {
+                            spans.push(Span::styled(Self::format_elapsed(elapsed), gutter_style));
+                        }
+                        Column::Level => {
+                            spans.push(Span::styled(self.format_level(message.level), level_style));
+                        }
+                        Column::Source => {
+                            let source = truncate_source(&message.source, source_width);
+                            let source_len = source.width();
+                            spans.push(Span::styled(
+                                source,
+                                self.source_style(message, error_source),
+                            ));
+                            spans.push(Span::styled(
+                                " ".repeat(source_width.saturating_sub(source_len)),
+                                self.default_style,
+                            ));
+                        }
+                    }
 
                     // Padding
                     spans.push(Span::styled(" ", self.default_style));
+                }
+            }
+            FormattedLine::Continued { .. } => {
+                let ellipsis_style =
+                    style_override.unwrap_or_else(|| self.default_style.fg(Color::DarkGray));
 
-                    // Source
-                    spans.push(Span::styled(
-                        message.source.as_ref(),
-                        style_override.unwrap_or_else(|| self.default_style.fg(Color::Green)),
-                    ));
+                // Message number (gutter_width)
+                if gutter_width > 0 {
+                    spans.push(Span::styled(" ".repeat(gutter_width), gutter_style));
+                }
 
-                    // Padding
+                // Placeholders with the same width as each column
+                for &column in self.columns.iter() {
                     spans.push(Span::styled(
-                        " ".repeat(
-                            state
-                                .source_width
-                                .saturating_sub(message.source.len())
-                                .saturating_add(1),
+                        format!(
+                            "{:width$}",
+                            "...",
+                            width = self.column_width(column, source_width)
                         ),
-                        self.default_style,
+                        ellipsis_style,
                     ));
 
-                    // Message
-                    spans.push(Span::styled(line, level_style));
-
-                    spans
-                }
-                FormattedLine::Continued { message, line } => {
-                    let mut spans = Vec::with_capacity(2);
-                    let ellipsis_style =
-                        style_override.unwrap_or_else(|| self.default_style.fg(Color::DarkGray));
-
-                    // Timestamp (8)
-                    spans.push(Span::styled("...     ", ellipsis_style));
-
                     // Padding (1)
                     spans.push(Span::raw(" "));
+                }
+            }
+        }
 
-                    // Level (5)
-                    spans.push(Span::styled("...  ", ellipsis_style));
+        // Message
+        self.push_contents(&mut spans, formatted_line, context.search, is_current);
 
-                    // Padding (1)
-                    spans.push(Span::raw(" "));
+        // Number of lines hidden by collapsing the message
+        if let FormattedLine::Start { hidden_lines, .. } = *formatted_line {
+            if hidden_lines > 0 {
+                let label = if hidden_lines == 1 { "line" } else { "lines" };
+                spans.push(Span::styled(
+                    format!(" ▸ {hidden_lines} more {label}"),
+                    style_override.unwrap_or_else(|| self.default_style.fg(Color::DarkGray)),
+                ));
+            }
+        }
 
-                    // Source (source_width)
-                    spans.push(Span::styled("...", ellipsis_style));
-                    spans.push(Span::raw(" ".repeat(state.source_width.saturating_sub(3))));
+        // Note attached to the message
+        if let Some(note) = note {
+            let text = if is_current {
+                format!(" ✎ {note}")
+            } else {
+                " ✎".to_string()
+            };
+            spans.push(Span::styled(
+                text,
+                style_override.unwrap_or_else(|| self.default_style.fg(Color::Cyan)),
+            ));
+        }
 
-                    // Padding (1)
-                    spans.push(Span::raw(" "));
+        spans.into()
+    }
 
-                    // Message
-                    spans.push(Span::styled(
-                        line,
-                        style_override.unwrap_or_else(|| {
-                            self.default_style.fg(Self::get_level_color(message.level))
-                        }),
-                    ));
+    /// Adds a line of a message's contents, cutting it off at the maximum
+    /// message width unless it's part of the message at the top of the log.
+    /// Text matching the search is highlighted.
+    fn push_contents(
+        &self,
+        spans: &mut Vec<Span<'i>>,
+        formatted_line: &FormattedLine<'i>,
+        search: Option<&str>,
+        is_current: bool,
+    ) {
+        let style_override = (!self.show_colors).then_some(self.default_style);
+        let style = style_override.unwrap_or_else(|| self.contents_style(formatted_line.message()));
+        let ellipsis_style =
+            style_override.unwrap_or_else(|| self.default_style.fg(Color::DarkGray));
+        let match_style = style_override.unwrap_or_else(|| self.theme.search_match_style());
+        let line = formatted_line.line();
+        let search = search.unwrap_or_default();
+        match self.max_message_width {
+            Some(max_width) if !is_current && line.width() > max_width => {
+                let fitted = fit_width(line, max_width.saturating_sub(1));
+                spans.extend(highlight_matches(fitted, search, style, match_style));
+                spans.push(Span::styled("…", ellipsis_style));
+            }
+            _ => spans.extend(highlight_matches(line, search, style, match_style)),
+        }
+    }
 
-                    spans
-                }
+    fn render_logs(
+        &self,
+        area: Rect,
+        buf: &mut Buffer,
+        state: &mut <Self as StatefulWidget>::State,
+    ) {
+        let gutter_width = state.gutter_width();
+        let source_width = self.source_width(state.source_width);
+        let current = state.current_message_index();
+        let paragraph = LazyParagraph::new(|index| {
+            let formatted_line = state.lines.get(index)?;
+            let note = match *formatted_line {
+                FormattedLine::Start { index, .. } => state.notes.get(&index),
+                FormattedLine::Continued { .. } => None,
             };
-
-            Some(spans.into())
+            let is_current = matches!(
+                *formatted_line,
+                FormattedLine::Start { index, .. } if Some(index) == current
+            );
+            Some(self.format_line(
+                formatted_line,
+                note.map(String::as_str),
+                is_current,
+                LineContext {
+                    error_source: state.error_source.map(|(source, _)| source),
+                    search: state.search.as_deref(),
+                    source_width,
+                    gutter_width,
+                },
+            ))
         })
-        .style(self.default_style.bg(Color::Black));
+        .style(self.default_style.bg(Color::Black))
+        .auto_scroll_interval(self.auto_scroll_interval)
+        .wrap(state.wrap);
         let paragraph = if let Some(block) = self.block.clone() {
             paragraph.block(block)
         } else {
             paragraph
         };
         paragraph.render(area, buf, &mut state.paragraph_state);
+
+        // Explain why nothing is shown
+        let inner_area = self.block.as_ref().map_or(area, |block| block.inner(area));
+        if state.lines.is_empty() && inner_area.height > 0 {
+            let text = if state.log.messages().is_empty() {
+                "Waiting for log output..."
+            } else {
+                "No messages match the current filters (press f to adjust)"
+            };
+            let width = (text.width() as u16).min(inner_area.width);
+            buf.set_stringn(
+                inner_area.x + (inner_area.width - width) / 2,
+                inner_area.y + inner_area.height / 2,
+                text,
+                width.into(),
+                self.default_style.fg(Color::DarkGray),
+            );
+        }
+
+        // Show the latest notice, like when a jump wraps around
+        let buffered_notice = (state.buffered_messages > 0)
+            .then(|| format!("{} new messages paused", state.buffered_messages));
+        if let Some(notice) = state
+            .notice
+            .as_deref()
+            .map(String::from)
+            .or(buffered_notice)
+        {
+            let text = format!(" {notice} ");
+            let width = (text.width() as u16).min(inner_area.width);
+            buf.set_stringn(
+                inner_area.right() - width,
+                inner_area.y,
+                text,
+                width.into(),
+                self.default_style.fg(Color::Black).bg(Color::Yellow),
+            );
+        }
+
+        // Track where the source column was rendered for mouse hovering
+        state.source_column = self
+            .source_column_start(source_width, gutter_width)
+            .map(|start| SourceColumn {
+                area: self.block.as_ref().map_or(area, |block| block.inner(area)),
+                start,
+                width: source_width,
+            });
+    }
+
+    /// Renders the histogram of a source's messages over the bottom of the
+    /// log, if it's open.
+    fn render_histogram(
+        &self,
+        area: Rect,
+        buf: &mut Buffer,
+        state: &<Self as StatefulWidget>::State,
+    ) {
+        let Some(source) = state.histogram_source.as_deref() else {
+            return;
+        };
+
+        let height = SourceHistogram::HEIGHT.min(area.height);
+        let histogram_area = Rect {
+            y: area.bottom() - height,
+            height,
+            ..area
+        };
+        let buckets = if self.histogram_buckets == 0 {
+            SourceHistogram::DEFAULT_BUCKETS
+        } else {
+            self.histogram_buckets
+        };
+        SourceHistogram::new(state.log, source)
+            .buckets(buckets)
+            .style(self.default_style)
+            .show_colors(self.show_colors)
+            .theme(self.theme.clone())
+            .render(histogram_area, buf);
+    }
+
+    fn render_tooltip(&self, buf: &mut Buffer, state: &<Self as StatefulWidget>::State) {
+        let (Some((column, row)), Some(SourceColumn { area, .. })) =
+            (state.hovered, state.source_column)
+        else {
+            return;
+        };
+        let Some(message) = state.truncated_source_at(column, row) else {
+            return;
+        };
+
+        // Place the tooltip below the cursor, keeping it within the area
+        let text = format!(" {} ", message.source);
+        let width = u16::try_from(text.width())
+            .unwrap_or(u16::MAX)
+            .min(area.width);
+        let x = column
+            .saturating_add(1)
+            .min(area.right().saturating_sub(width));
+        let y = if row.saturating_add(1) < area.bottom() {
+            row.saturating_add(1)
+        } else {
+            row.saturating_sub(1)
+        };
+        let tooltip_area = Rect {
+            x,
+            y,
+            width,
+            height: 1,
+        };
+        let style = if self.show_colors {
+            self.default_style.fg(Color::Black).bg(Color::White)
+        } else {
+            self.default_style
+        };
+        Clear.render(tooltip_area, buf);
+        buf.set_stringn(x, y, text, usize::from(width), style);
     }
 }
 
 impl<'i> StatefulWidget for FormattedLog<'i> {
     type State = FormattedLogState<'i>;
 
-    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        if state.filters_list_state.is_none() {
-            // Logs only
-            self.render_logs(area, buf, state);
-        } else {
-            // Logs + filters
+    fn render(mut self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        self.show_dates = state.show_dates;
+        self.columns = self.columns.with_elapsed(state.show_elapsed);
+        if let Some(problems_state) = state.problems_state.as_mut() {
+            // Logs + problems
             let layout = Layout::default()
                 .direction(Direction::Vertical)
-                .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+                .constraints(
+                    [
+                        Constraint::Min(0),
+                        Constraint::Length(ProblemsList::height(problems_state)),
+                    ]
+                    .as_ref(),
+                )
                 .split(area);
-            self.render_logs(layout[0], buf, state);
-            let filters_list_state = state.filters_list_state.as_mut().unwrap();
             let style_override = (!self.show_colors).then_some(self.default_style);
-            FiltersList::new(&mut state.filters)
-                .style(
-                    style_override
-                        .unwrap_or_else(|| self.default_style.fg(Color::Black).bg(Color::White)),
-                )
-                .selected_style(
-                    style_override
-                        .unwrap_or_else(|| self.default_style.fg(Color::White).bg(Color::LightRed)),
+            ProblemsList::default()
+                .style(self.default_style)
+                .selected_style(style_override.unwrap_or_else(|| {
+                    self.default_style
+                        .fg(Color::White)
+                        .bg(self.theme.selection_color)
+                }))
+                .render(layout[1], buf, problems_state);
+            self.render_logs(layout[0], buf, state);
+        } else if let Some(timeline_state) = state.timeline_state.as_mut() {
+            // Logs + timeline
+            let layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(
+                    [
+                        Constraint::Min(0),
+                        Constraint::Length(TimelineList::WIDTH.min(area.width / 2)),
+                    ]
+                    .as_ref(),
                 )
-                .enabled_style(
-                    style_override.unwrap_or_else(|| {
-                        self.default_style.fg(Color::Black).bg(Color::LightGreen)
-                    }),
+                .split(area);
+            let style_override = (!self.show_colors).then_some(self.default_style);
+            TimelineList::default()
+                .style(self.default_style)
+                .selected_style(style_override.unwrap_or_else(|| {
+                    self.default_style
+                        .fg(Color::White)
+                        .bg(self.theme.selection_color)
+                }))
+                .render(layout[1], buf, timeline_state);
+            self.render_logs(layout[0], buf, state);
+        } else if state.searching {
+            // Logs + search prompt
+            let layout = Self::prompt_layout(area);
+            CommandInput::default()
+                .style(self.default_style)
+                .focused(true)
+                .prompt("/")
+                .render(layout[1], buf, &mut state.search_input_state);
+            self.render_logs(layout[0], buf, state);
+        } else if let Some(glob_input_state) = state.glob_input_state.as_mut() {
+            // Logs + glob prompt
+            let layout = Self::prompt_layout(area);
+            CommandInput::default()
+                .style(self.default_style)
+                .focused(true)
+                .prompt("Hide sources matching: ")
+                .render(layout[1], buf, glob_input_state);
+            self.render_logs(layout[0], buf, state);
+        } else if let Some(regex_input_state) = state.regex_input_state.as_mut() {
+            // Logs + regex prompt, which turns red while the regex is invalid
+            let layout = Self::prompt_layout(area);
+            let valid = Regex::new(regex_input_state.text().trim()).is_ok();
+            CommandInput::default()
+                .style(if valid || !self.show_colors {
+                    self.default_style
+                } else {
+                    self.default_style.fg(self.theme.level_colors.error)
+                })
+                .focused(true)
+                .prompt("Show sources matching regex: ")
+                .render(layout[1], buf, regex_input_state);
+            self.render_logs(layout[0], buf, state);
+        } else if let Some(exception_input_state) = state.exception_input_state.as_mut() {
+            // Logs + exception prompt
+            let layout = Self::prompt_layout(area);
+            CommandInput::default()
+                .style(self.default_style)
+                .focused(true)
+                .prompt("Only show exceptions of type: ")
+                .render(layout[1], buf, exception_input_state);
+            self.render_logs(layout[0], buf, state);
+        } else if state.time_range_input_state.is_some() {
+            // Logs + time range prompt
+            self.render_with_time_range_prompt(area, buf, state);
+        } else if let Some((_, note_input_state)) = state.note_input_state.as_mut() {
+            // Logs + note prompt
+            let layout = Self::prompt_layout(area);
+            CommandInput::default()
+                .style(self.default_style)
+                .focused(true)
+                .prompt("Note: ")
+                .render(layout[1], buf, note_input_state);
+            self.render_logs(layout[0], buf, state);
+        } else if state.filters_list_state.is_none() {
+            // Logs only
+            self.render_logs(area, buf, state);
+        } else {
+            // Logs + filters
+            self.render_with_filters(area, buf, state);
+        }
+
+        self.render_histogram(area, buf, state);
+        self.render_tooltip(buf, state);
+    }
+}
+
+impl FormattedLog<'_> {
+    /// Gets the style of a message's source. The source of the latest error
+    /// is highlighted.
+    fn source_style(&self, message: &Message, error_source: Option<&str>) -> Style {
+        if !self.show_colors {
+            self.default_style
+        } else if error_source == Some(message.source.as_ref()) {
+            self.error_source_style()
+        } else {
+            self.default_style.fg(self.theme.source_color)
+        }
+    }
+
+    /// Gets the style of the source of the latest error.
+    fn error_source_style(&self) -> Style {
+        self.default_style
+            .fg(Color::Black)
+            .bg(self.theme.level_colors.error)
+    }
+
+    /// Splits the area into the logs and a prompt below them.
+    fn prompt_layout(area: Rect) -> Vec<Rect> {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+            .split(area)
+    }
+
+    /// Renders the logs with the prompt for the time range below them, with
+    /// the start and end fields side by side.
+    fn render_with_time_range_prompt(
+        &self,
+        area: Rect,
+        buf: &mut Buffer,
+        state: &mut <Self as StatefulWidget>::State,
+    ) {
+        let layout = Self::prompt_layout(area);
+        if let Some(time_range_input_state) = state.time_range_input_state.as_mut() {
+            let fields = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                .split(layout[1]);
+            let editing_end = time_range_input_state.editing_end;
+            CommandInput::default()
+                .style(self.default_style)
+                .focused(!editing_end)
+                .prompt("Show messages from: ")
+                .render(fields[0], buf, &mut time_range_input_state.start);
+            CommandInput::default()
+                .style(self.default_style)
+                .focused(editing_end)
+                .prompt("to: ")
+                .render(fields[1], buf, &mut time_range_input_state.end);
+        }
+        self.render_logs(layout[0], buf, state);
+    }
+
+    /// Renders the logs with the filters list open, either as a bar below the
+    /// logs or as a sidebar to their left.
+    fn render_with_filters(
+        &self,
+        area: Rect,
+        buf: &mut Buffer,
+        state: &mut <Self as StatefulWidget>::State,
+    ) {
+        let Some(filters_list_state) = state.filters_list_state.as_mut() else {
+            return;
+        };
+
+        let style_override = (!self.show_colors).then_some(self.default_style);
+        let selected_style = style_override.unwrap_or_else(|| {
+            self.default_style
+                .fg(Color::White)
+                .bg(self.theme.selection_color)
+        });
+        if filters_list_state.sidebar {
+            let width = FiltersSidebar::width(&state.filters, filters_list_state);
+            let layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(
+                    [
+                        Constraint::Length(width.min(area.width / 2)),
+                        Constraint::Min(0),
+                    ]
+                    .as_ref(),
                 )
-                .more_label_style(self.default_style.fg(Color::White))
-                .render(layout[1], buf, filters_list_state);
+                .split(area);
+            FiltersSidebar::new(&state.filters)
+                .style(self.default_style)
+                .selected_style(selected_style)
+                .highlighted_source(state.error_source.map(|(source, _)| source))
+                .highlighted_style(style_override.unwrap_or_else(|| self.error_source_style()))
+                .render(layout[0], buf, filters_list_state);
+            self.render_logs(layout[1], buf, state);
+            return;
+        }
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+            .split(area);
+        FiltersList::new(&mut state.filters)
+            .style(style_override.unwrap_or_else(|| {
+                self.default_style
+                    .fg(Color::Black)
+                    .bg(self.theme.filter_disabled_color)
+            }))
+            .selected_style(selected_style)
+            .enabled_style(style_override.unwrap_or_else(|| {
+                self.default_style
+                    .fg(Color::Black)
+                    .bg(self.theme.filter_enabled_color)
+            }))
+            .more_label_style(self.default_style.fg(Color::White))
+            .highlighted_source(state.error_source.map(|(source, _)| source))
+            .highlighted_style(style_override.unwrap_or_else(|| self.error_source_style()))
+            .render(layout[1], buf, filters_list_state);
+        self.render_logs(layout[0], buf, state);
+    }
+}
+
+/// What's highlighted in the lines of the log, and the widths that depend on
+/// the whole log. These are the same for every line in a frame.
+#[derive(Clone, Copy, Debug)]
+struct LineContext<'a> {
+    /// The source of the latest error.
+    error_source: Option<&'a str>,
+    /// The text being searched for, which is highlighted in the contents.
+    search: Option<&'a str>,
+    source_width: usize,
+    gutter_width: usize,
+}
+
+/// The prompt for the time range filter, with a field for each end of the
+/// range.
+#[derive(Clone, Debug, Default)]
+struct TimeRangeInputState {
+    start: CommandInputState,
+    end: CommandInputState,
+    /// Whether the end of the range is being edited instead of the start.
+    editing_end: bool,
+}
+
+impl TimeRangeInputState {
+    /// Creates the prompt, filled in with the current range if there is one.
+    fn new(range: Option<(Timestamp, Timestamp)>) -> Self {
+        let mut state = TimeRangeInputState::default();
+        if let Some((start, end)) = range {
+            state.start.set_text(start.to_string());
+            state.end.set_text(end.to_string());
+        }
+        state
+    }
+
+    /// Parses the entered range. An empty start or end leaves that side of
+    /// the range open, and leaving both empty removes the range.
+    fn range(&self) -> anyhow::Result<Option<(Timestamp, Timestamp)>> {
+        let parse = |input: &CommandInputState, default: Timestamp| match input.text().trim() {
+            "" => Ok(default),
+            text => text.parse(),
+        };
+        if self.start.text().trim().is_empty() && self.end.text().trim().is_empty() {
+            return Ok(None);
+        }
+
+        let start = parse(&self.start, "00:00:00".parse()?)?;
+        let end = parse(&self.end, "23:59:59".parse()?)?;
+        Ok(Some((start, end)))
+    }
+
+    /// Gets the field being edited.
+    fn focused_mut(&mut self) -> &mut CommandInputState {
+        if self.editing_end {
+            &mut self.end
+        } else {
+            &mut self.start
         }
     }
 }
@@ -193,94 +832,1374 @@ pub struct FormattedLogState<'i> {
     log: &'i Log,
     lines: Vec<FormattedLine<'i>>,
     source_width: usize,
+    /// Whether any message in the log has a date.
+    show_dates: bool,
     paragraph_state: LazyParagraphState,
     filters: LogFilters<'i>,
     filters_list_state: Option<FiltersListState>,
+    filters_sidebar: bool,
+    /// Sources with fewer messages than this are grouped together in the
+    /// filters.
+    group_sources_below: usize,
+    problems_state: Option<ProblemsListState<'i>>,
+    timeline_state: Option<TimelineListState<'i>>,
+    /// The source whose messages are shown in a histogram, if any.
+    histogram_source: Option<String>,
+    copy_request: Option<String>,
+    copy_format: CopyFormat,
+    /// The separator that joins the lines of a message when it's copied, if
+    /// any.
+    join_lines: Option<String>,
+    show_message_numbers: bool,
+    /// Whether long lines continue on the next rows instead of being cut off.
+    wrap: bool,
+    /// Whether the column with the time since the previous message is shown.
+    show_elapsed: bool,
+    /// Whether the search prompt is open.
+    searching: bool,
+    /// The search prompt, which keeps previous searches while it's closed.
+    search_input_state: CommandInputState,
+    search: Option<String>,
+    glob_input_state: Option<CommandInputState>,
+    /// The prompt for only showing sources matching a regex.
+    regex_input_state: Option<CommandInputState>,
+    exception_input_state: Option<CommandInputState>,
+    time_range_input_state: Option<TimeRangeInputState>,
+    /// Whether trace and debug messages were shown before they were hidden
+    /// with `d`, so they can be restored.
+    saved_debug_levels: Option<[bool; 2]>,
+    /// Sources with the most trace and debug messages shown, which `H`
+    /// hides.
+    noisy_sources: Vec<&'i str>,
+    /// The source of the latest error or alert that arrived while following
+    /// the log, and when it arrived. It's highlighted until it expires or a
+    /// key is pressed.
+    error_source: Option<(&'i str, Instant)>,
+    /// How long the source of the latest error is highlighted. Zero turns
+    /// the highlight off.
+    error_highlight_duration: Duration,
+    /// Notes attached to messages, keyed by the index of the message.
+    notes: HashMap<usize, String>,
+    /// Whether the notes changed since they were last taken to be saved.
+    notes_changed: bool,
+    /// The prompt for editing the note on a message, and the index of the
+    /// message.
+    note_input_state: Option<(usize, CommandInputState)>,
+    collapse_continuations: bool,
+    /// Whether to expand the message at the top of the view while the others
+    /// are collapsed.
+    auto_expand: bool,
+    /// The index of the message that is expanded while the others are
+    /// collapsed, if any.
+    expanded_message: Option<usize>,
+    wrap_navigation: bool,
+    /// Whether `e` and `E` stop at warnings too, not just errors.
+    jump_to_warnings: bool,
+    notice: Option<Cow<'static, str>>,
+    mark: Option<usize>,
+    buffered_messages: usize,
+    source_column: Option<SourceColumn>,
+    hovered: Option<(u16, u16)>,
 }
 
 impl<'i> FormattedLogState<'i> {
+    /// The levels hidden and shown with `d`.
+    const DEBUG_LEVELS: [Level; 2] = [Level::Trace, Level::Debug];
+
     pub fn new(log: &'i Log) -> Self {
         let filters = LogFilters {
-            levels: Level::ALL.into_iter().map(|level| (level, true)).collect(),
+            levels: log.levels().map(|level| (level, true)).collect(),
             sources: log
                 .sources()
-                .into_iter()
                 .sorted()
                 .map(|source| (source, true))
                 .collect(),
+            latest_launch_only: false,
+            multi_line_only: false,
+            important_only: false,
+            noisy_sources: Vec::new(),
+            since_mark: None,
+            hidden_sources: None,
+            source_pattern: None,
+            exception: None,
+            grouped_sources: Vec::new(),
+            remembered_sources: Vec::new(),
+            time_range: None,
+            errors_outside_time_range: false,
+            classifiers: Rc::default(),
+            categories: IndexMap::new(),
         };
-        let (lines, source_width) = Self::format_lines(log, filters.clone());
-        let paragraph_state = LazyParagraphState::new(lines.len(), true);
+        let (lines, source_width) = Self::format_lines(log, filters.clone(), false, None);
+        let paragraph_state = LazyParagraphState::new(lines.len(), false);
+        let noisy_sources = Self::find_noisy_sources(log, &filters);
         Self {
             log,
             lines,
             source_width,
+            show_dates: Self::has_dates(log),
             paragraph_state,
             filters,
             filters_list_state: None,
+            filters_sidebar: false,
+            group_sources_below: 0,
+            problems_state: None,
+            timeline_state: None,
+            histogram_source: None,
+            copy_request: None,
+            copy_format: CopyFormat::default(),
+            join_lines: None,
+            show_message_numbers: false,
+            wrap: false,
+            show_elapsed: false,
+            searching: false,
+            search_input_state: CommandInputState::default(),
+            search: None,
+            glob_input_state: None,
+            regex_input_state: None,
+            exception_input_state: None,
+            time_range_input_state: None,
+            saved_debug_levels: None,
+            noisy_sources,
+            error_source: None,
+            error_highlight_duration: Duration::ZERO,
+            notes: HashMap::new(),
+            notes_changed: false,
+            note_input_state: None,
+            collapse_continuations: false,
+            auto_expand: false,
+            expanded_message: None,
+            wrap_navigation: true,
+            jump_to_warnings: false,
+            notice: None,
+            mark: None,
+            buffered_messages: 0,
+            source_column: None,
+            hovered: None,
         }
     }
 
-    pub fn apply_filter(&mut self) {
-        let (lines, source_width) = Self::format_lines(self.log, self.filters.clone());
-        self.lines = lines;
-        self.source_width = source_width;
-        trace!(lines=%self.lines.len(), max_source_width=%self.source_width, "Applied filter to formatted log");
+    /// Checks whether any message in a log has a date.
+    fn has_dates(log: &Log) -> bool {
+        log.messages()
+            .iter()
+            .any(|message| message.timestamp.date.is_some())
+    }
 
-        // TODO: set the offset to the line closest to the current line's offset
-        let auto_scroll = self.paragraph_state.auto_scroll;
-        self.paragraph_state = LazyParagraphState::new(self.lines.len(), true);
-        self.paragraph_state.auto_scroll = auto_scroll;
+    /// Sets the notes attached to messages, like notes restored from a
+    /// previous session.
+    pub fn with_notes(mut self, notes: HashMap<usize, String>) -> Self {
+        self.notes = notes;
+        self
     }
 
-    fn format_lines(log: &'i Log, filters: LogFilters<'i>) -> (Vec<FormattedLine<'i>>, usize) {
-        let mut lines = Vec::new();
-        let mut source_width = 0;
-        for message in filters.apply(log) {
-            // Source width
-            let source = message.source.as_ref();
-            source_width = source_width.max(source.len());
+    /// Replaces the notes attached to messages, like when the log was
+    /// replaced and the new log's notes were loaded.
+    pub fn set_notes(&mut self, notes: HashMap<usize, String>) {
+        self.notes = notes;
+        self.notes_changed = false;
+    }
 
-            // Formatted lines
-            let mut first_line = true;
-            for contents in message.contents.lines() {
-                if first_line {
-                    first_line = false;
-                    lines.push(FormattedLine::Start {
-                        message,
-                        line: contents,
-                    });
-                } else {
-                    lines.push(FormattedLine::Continued {
-                        message,
-                        line: contents,
-                    });
-                }
-            }
-        }
+    /// Groups sources with fewer messages than the threshold together in the
+    /// filters, so they can be toggled at once. A threshold of 0 groups
+    /// nothing.
+    pub fn group_sources_below(mut self, threshold: usize) -> Self {
+        self.group_sources_below = threshold;
+        self.filters.group_sources_below(self.log, threshold);
+        self
+    }
 
-        (lines, source_width)
+    /// Sets the sources whose info messages are hidden when only showing
+    /// important messages.
+    pub fn noisy_sources(mut self, noisy_sources: Vec<String>) -> Self {
+        self.filters.noisy_sources = noisy_sources;
+        self
     }
-}
 
-impl<'i> State for FormattedLogState<'i> {
-    fn update(&mut self, event: &AppEvent) -> bool {
-        // Events handled by the formatted log widget
-        #[allow(clippy::single_match)] // TODO: Add mouse support
-        match *event {
-            AppEvent::TermEvent(Event::Key(key_event)) => match key_event.code {
-                KeyCode::Char('f') if self.filters_list_state.is_none() => {
-                    self.filters_list_state = Some(FiltersListState::levels());
-                    return true;
-                }
-                KeyCode::Char('f') => {
-                    match self.filters_list_state.take().unwrap().source {
-                        FiltersListSource::Levels => {
-                            self.filters_list_state = Some(FiltersListState::sources());
+    /// Sets what to include when copying a message.
+    pub fn copy_format(mut self, copy_format: CopyFormat) -> Self {
+        self.copy_format = copy_format;
+        self
+    }
+
+    /// Sets whether jumping past the last match, error, or message wraps
+    /// around to the other end of the log.
+    pub fn wrap_navigation(mut self, wrap_navigation: bool) -> Self {
+        self.wrap_navigation = wrap_navigation;
+        self
+    }
+
+    /// Sets the classifiers that tag messages with a category, so messages
+    /// can be filtered by their category.
+    pub fn classifiers(mut self, classifiers: Rc<[Box<dyn MessageClassifier>]>) -> Self {
+        self.filters.set_classifiers(self.log, classifiers);
+        self
+    }
+
+    /// Starts the log scrolled to the end, following new messages as they
+    /// arrive.
+    pub fn follow(mut self, follow: bool) -> Self {
+        self.paragraph_state = LazyParagraphState::new(self.lines.len(), follow);
+        self
+    }
+
+    /// Expands the message at the top of the view while the others are
+    /// collapsed.
+    pub fn auto_expand(mut self, auto_expand: bool) -> Self {
+        self.auto_expand = auto_expand;
+        self
+    }
+
+    /// Shows the column with the time since the previous message, like when
+    /// it's part of the configured columns.
+    pub fn show_elapsed(mut self, show_elapsed: bool) -> Self {
+        self.show_elapsed = show_elapsed;
+        self
+    }
+
+    /// Joins the lines of a message with a separator when it's copied, so it
+    /// fits on one line. The lines are kept as they are without a separator.
+    pub fn join_lines(mut self, separator: Option<String>) -> Self {
+        self.join_lines = separator;
+        self
+    }
+
+    /// Highlights the source of the latest error or alert that arrives for
+    /// this long. Zero turns the highlight off.
+    pub fn error_highlight_duration(mut self, duration: Duration) -> Self {
+        self.error_highlight_duration = duration;
+        self
+    }
+
+    /// Starts at the first error if `jump` is set and the log has one.
+    pub fn jump_to_first_error(mut self, jump: bool) -> Self {
+        if jump {
+            self.scroll_to_first_error();
+        }
+        self
+    }
+
+    /// Restores the filters saved in a previous session, if any.
+    pub fn with_saved_filters(mut self, saved: Option<&SavedFilters>) -> Self {
+        if let Some(saved) = saved {
+            self.filters.restore(saved);
+            self.apply_filter();
+        }
+        self
+    }
+
+    /// Gets the filters to save, so they can be restored in a later session.
+    pub fn saved_filters(&self) -> SavedFilters {
+        self.filters.to_saved()
+    }
+
+    pub fn apply_filter(&mut self) {
+        let anchor = self.current_message_index();
+        let (lines, source_width) = Self::format_lines(
+            self.log,
+            self.filters.clone(),
+            self.collapse_continuations,
+            self.expanded_message,
+        );
+        self.lines = lines;
+        self.source_width = source_width;
+        self.noisy_sources = Self::find_noisy_sources(self.log, &self.filters);
+        trace!(lines=%self.lines.len(), max_source_width=%self.source_width, "Applied filter to formatted log");
+
+        // Keep the message at the top of the view in place. If it's hidden now,
+        // the next message that's shown takes its place
+        let top = anchor.map_or(0, |anchor| {
+            self.lines
+                .iter()
+                .position(
+                    |line| matches!(*line, FormattedLine::Start { index, .. } if index >= anchor),
+                )
+                .unwrap_or(self.lines.len())
+        });
+        self.paragraph_state.lines = self.lines.len();
+        self.paragraph_state.offset.y = top.min(self.lines.len().saturating_sub(1));
+    }
+
+    /// Finds the sources with the most trace and debug messages that are
+    /// shown, to suggest hiding them. Only sources that make up a large share
+    /// of the log are suggested. SMAPI is never suggested, since it reports
+    /// the errors in mods.
+    fn find_noisy_sources(log: &'i Log, filters: &LogFilters<'i>) -> Vec<&'i str> {
+        const MAX_SUGGESTIONS: usize = 3;
+        const MIN_MESSAGES: usize = 20;
+
+        let levels: Vec<Level> = Self::DEBUG_LEVELS
+            .into_iter()
+            .filter(|&level| filters.level_enabled(level))
+            .collect();
+        let min_count = (log.messages().len() / 10).max(MIN_MESSAGES);
+        log.sources()
+            .filter(|&source| source != "SMAPI" && filters.source_enabled(source))
+            .map(|source| {
+                let count = log
+                    .messages_by_source(source)
+                    .iter()
+                    .filter(|message| levels.contains(&message.level))
+                    .count();
+                (source, count)
+            })
+            .filter(|&(_, count)| count >= min_count)
+            .sorted_by_key(|&(source, count)| (std::cmp::Reverse(count), source))
+            .take(MAX_SUGGESTIONS)
+            .map(|(source, _)| source)
+            .collect()
+    }
+
+    /// Formats the messages shown by the filters into lines. If
+    /// `collapse_continuations` is set, only the first line of each message
+    /// is included, except for the `expanded` message.
+    fn format_lines(
+        log: &'i Log,
+        filters: LogFilters<'i>,
+        collapse_continuations: bool,
+        expanded: Option<usize>,
+    ) -> (Vec<FormattedLine<'i>>, usize) {
+        let mut lines = Vec::new();
+        let mut source_width = 0;
+        let mut last_date = None;
+        let mut last_timestamp = None;
+        for (index, message) in filters.apply_indexed(log) {
+            // Source width
+            source_width = source_width.max(message.source.width());
+
+            // Dates are only shown when they change
+            let date = message.timestamp.date;
+            let show_date = date.is_some() && date != last_date;
+            last_date = date.or(last_date);
+
+            // Time since the message shown before it
+            let elapsed = last_timestamp.map(|last| message.timestamp.duration_since(last));
+            last_timestamp = Some(message.timestamp);
+
+            // Formatted lines
+            let collapse_continuations = collapse_continuations && expanded != Some(index);
+            let mut first_line = true;
+            for contents in message.contents.lines() {
+                if first_line {
+                    first_line = false;
+                    lines.push(FormattedLine::Start {
+                        index,
+                        message,
+                        line: contents,
+                        hidden_lines: if collapse_continuations {
+                            message.contents.lines().count() - 1
+                        } else {
+                            0
+                        },
+                        show_date,
+                        elapsed,
+                    });
+                    if collapse_continuations {
+                        break;
+                    }
+                } else {
+                    lines.push(FormattedLine::Continued {
+                        message,
+                        line: contents,
+                    });
+                }
+            }
+        }
+
+        (lines, source_width)
+    }
+
+    /// Gets the message at the top of the view.
+    pub fn current_message(&self) -> Option<&'i Message<'i>> {
+        self.lines
+            .get(self.paragraph_state.offset.y)
+            .map(FormattedLine::message)
+    }
+
+    /// Gets the index of the message at the top of the view.
+    fn current_message_index(&self) -> Option<usize> {
+        let top = self.paragraph_state.offset.y;
+        self.lines
+            .get(..=top)?
+            .iter()
+            .rev()
+            .find_map(|line| match *line {
+                FormattedLine::Start { index, .. } => Some(index),
+                FormattedLine::Continued { .. } => None,
+            })
+    }
+
+    /// Gets the state of the paragraph the lines are shown in.
+    pub fn paragraph_state(&self) -> &LazyParagraphState {
+        &self.paragraph_state
+    }
+
+    /// Gets the sources suggested for hiding with `H` because they log the
+    /// most trace and debug messages.
+    pub fn suggested_noisy_sources(&self) -> &[&'i str] {
+        &self.noisy_sources
+    }
+
+    /// Gets how long passed before the message at the top of the view since
+    /// the message before it, and since the previous message from the same
+    /// source. Messages hidden by the filters are included.
+    pub fn current_message_gaps(&self) -> Option<MessageGaps> {
+        let index = self.current_message_index()?;
+        let messages = self.log.messages();
+        let current = messages.get(index)?;
+        let earlier = &messages[..index];
+        let since = |message: &Message| current.timestamp.duration_since(message.timestamp);
+        Some(MessageGaps {
+            previous: earlier.last().map(since),
+            same_source: earlier
+                .iter()
+                .rfind(|message| message.source == current.source)
+                .map(since),
+        })
+    }
+
+    /// Gets the text being searched for, if any.
+    pub fn search(&self) -> Option<&str> {
+        self.search.as_deref()
+    }
+
+    /// Checks whether the filters are being edited.
+    pub fn is_editing_filters(&self) -> bool {
+        self.filters_list_state.is_some()
+    }
+
+    /// Sets the number of new messages waiting to be shown.
+    pub fn set_buffered_messages(&mut self, buffered_messages: usize) {
+        self.buffered_messages = buffered_messages;
+    }
+
+    /// Gets the width of the message number gutter, including padding. This is
+    /// 0 if message numbers are hidden.
+    fn gutter_width(&self) -> usize {
+        if self.show_message_numbers {
+            self.log.messages().len().to_string().len() + 1
+        } else {
+            0
+        }
+    }
+
+    /// Checks whether the user is typing into a prompt in the log, like the
+    /// search prompt.
+    pub fn is_typing(&self) -> bool {
+        self.searching
+            || self.glob_input_state.is_some()
+            || self.regex_input_state.is_some()
+            || self.exception_input_state.is_some()
+            || self.time_range_input_state.is_some()
+            || self.note_input_state.is_some()
+    }
+
+    /// Checks whether the user asked to copy text to the clipboard, resetting
+    /// the request.
+    pub fn take_copy_request(&mut self) -> Option<String> {
+        self.copy_request.take()
+    }
+
+    /// Checks whether the notes changed since this was last called, returning
+    /// the notes to save if they did.
+    pub fn take_changed_notes(&mut self) -> Option<&HashMap<usize, String>> {
+        std::mem::take(&mut self.notes_changed).then_some(&self.notes)
+    }
+
+    /// Scrolls to a message in the log. If the message is hidden by the
+    /// filters, this scrolls to the next visible message instead.
+    pub fn scroll_to_message(&mut self, index: usize) {
+        let Some(target) = self.log.messages().get(index) else {
+            return;
+        };
+
+        // Lines are in the same order as the messages they belong to
+        let target = std::ptr::from_ref(target);
+        let line = self
+            .lines
+            .iter()
+            .position(|line| std::ptr::from_ref(line.message()) >= target)
+            .unwrap_or(self.lines.len());
+        self.paragraph_state.scroll_to(line);
+    }
+
+    /// Scrolls to the previous or next visible message from the same source as
+    /// the current message. Returns whether there was a message to scroll to.
+    fn scroll_to_same_source(&mut self, forward: bool) -> bool {
+        let Some(current) = self.current_message() else {
+            return false;
+        };
+
+        let start = self.paragraph_state.offset.y;
+        let start = if forward {
+            start.saturating_add(1)
+        } else {
+            start
+        };
+        self.scroll_to_line(start, forward, |line| match *line {
+            FormattedLine::Start { message, .. } => {
+                message.source == current.source && !std::ptr::eq(message, current)
+            }
+            FormattedLine::Continued { .. } => false,
+        })
+    }
+
+    /// Scrolls to the previous or next visible error, or warning if those are
+    /// included. A notice is shown if there are none.
+    fn scroll_to_error(&mut self, forward: bool) {
+        let start = self.paragraph_state.offset.y;
+        let start = if forward {
+            start.saturating_add(1)
+        } else {
+            start
+        };
+        let jump_to_warnings = self.jump_to_warnings;
+        let found = self.scroll_to_line(start, forward, |line| match *line {
+            FormattedLine::Start { message, .. } => {
+                message.level.is_error() || jump_to_warnings && message.level == Level::Warn
+            }
+            FormattedLine::Continued { .. } => false,
+        });
+        if !found {
+            self.notice = Some(if jump_to_warnings {
+                "No more errors or warnings".into()
+            } else {
+                "No more errors".into()
+            });
+        }
+    }
+
+    /// Scrolls to the first visible error or alert in the log. Returns whether
+    /// there was one.
+    pub fn scroll_to_first_error(&mut self) -> bool {
+        self.scroll_to_line(0, true, |line| match *line {
+            FormattedLine::Start { message, .. } => message.level.is_error(),
+            FormattedLine::Continued { .. } => false,
+        })
+    }
+
+    /// Scrolls to the next line that matches a predicate, starting at the
+    /// given line. Searching backwards starts at the line before it. If
+    /// nothing matches and wrapping is enabled, the search continues from the
+    /// other end of the log. Returns whether a line was found.
+    fn scroll_to_line(
+        &mut self,
+        start: usize,
+        forward: bool,
+        predicate: impl Fn(&FormattedLine<'i>) -> bool,
+    ) -> bool {
+        let start = start.min(self.lines.len());
+        let matches = |&index: &usize| predicate(&self.lines[index]);
+        let found = if forward {
+            (start..self.lines.len()).find(matches)
+        } else {
+            (0..start).rev().find(matches)
+        };
+        let found = found.map(|line| (line, false)).or_else(|| {
+            if !self.wrap_navigation {
+                return None;
+            }
+
+            let wrapped = if forward {
+                (0..start).find(matches)
+            } else {
+                (start..self.lines.len()).rev().find(matches)
+            };
+            wrapped.map(|line| (line, true))
+        });
+        let Some((line, wrapped)) = found else {
+            return false;
+        };
+
+        if wrapped {
+            self.notice = Some(
+                if forward {
+                    "Wrapped to top"
+                } else {
+                    "Wrapped to bottom"
+                }
+                .into(),
+            );
+        }
+        self.paragraph_state.scroll_to(line);
+        true
+    }
+
+    /// Handles keys pressed while viewing the log. Returns whether the key was
+    /// handled.
+    fn update_log_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char('p') => {
+                self.problems_state = Some(ProblemsListState::new(self.log));
+                true
+            }
+            KeyCode::Char('T') => {
+                self.timeline_state = Some(TimelineListState::new(self.log));
+                true
+            }
+            KeyCode::Char('c') => {
+                self.copy_request = self.current_message().map(|message| {
+                    let contents = message.joined_contents(self.join_lines.as_deref());
+                    self.copy_format.format(message, &contents)
+                });
+                true
+            }
+            KeyCode::Char('C') => {
+                // The first line of an exception has its type and message
+                self.copy_request = self.current_message().and_then(|message| {
+                    let line = message.contents.lines().next()?;
+                    Some(self.copy_format.format(message, line))
+                });
+                true
+            }
+            KeyCode::Char('m') => {
+                self.copy_format = self.copy_format.next();
+                true
+            }
+            KeyCode::Char('z') => {
+                self.toggle_collapsed();
+                true
+            }
+            KeyCode::Char('x') if self.collapse_continuations => {
+                self.auto_expand = !self.auto_expand;
+                true
+            }
+            KeyCode::Char('{') => self.scroll_to_same_source(false),
+            KeyCode::Char('}') => self.scroll_to_same_source(true),
+            KeyCode::Char('#') => {
+                self.show_message_numbers = !self.show_message_numbers;
+                true
+            }
+            KeyCode::Char('w') => {
+                self.wrap = !self.wrap;
+                true
+            }
+            KeyCode::Char('+') => {
+                self.show_elapsed = !self.show_elapsed;
+                true
+            }
+            KeyCode::Char('/') => {
+                self.searching = true;
+                self.search_input_state.clear();
+                true
+            }
+            KeyCode::Char('A') => {
+                let Some(index) = self.current_message_index() else {
+                    return false;
+                };
+                let mut note_input_state = CommandInputState::default();
+                if let Some(note) = self.notes.get(&index) {
+                    note_input_state.set_text(note.clone());
+                }
+                self.note_input_state = Some((index, note_input_state));
+                true
+            }
+            KeyCode::Char('M') => {
+                self.mark = Some(self.log.messages().len());
+                self.notice = Some("Mark set".into());
+                true
+            }
+            KeyCode::Char('n') if self.search.is_some() => {
+                self.scroll_to_match(self.paragraph_state.offset.y.saturating_add(1), true);
+                true
+            }
+            KeyCode::Char('N') if self.search.is_some() => {
+                self.scroll_to_match(self.paragraph_state.offset.y, false);
+                true
+            }
+            code => self.update_error_key(code) || self.update_filter_key(code),
+        }
+    }
+
+    /// Handles the keys for jumping between errors while viewing the log.
+    fn update_error_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char('e') => {
+                self.scroll_to_error(true);
+                true
+            }
+            KeyCode::Char('E') => {
+                self.scroll_to_error(false);
+                true
+            }
+            KeyCode::Char('F') => {
+                if !self.scroll_to_first_error() {
+                    self.notice = Some("No errors".into());
+                }
+                true
+            }
+            KeyCode::Char('W') => {
+                self.jump_to_warnings = !self.jump_to_warnings;
+                self.notice = Some(if self.jump_to_warnings {
+                    "Jumping to errors and warnings".into()
+                } else {
+                    "Jumping to errors only".into()
+                });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Collapses or expands the continuation lines of every message, staying
+    /// on the same message.
+    fn toggle_collapsed(&mut self) {
+        let current = self.current_message().and_then(|message| {
+            self.log
+                .messages()
+                .iter()
+                .position(|other| std::ptr::eq(other, message))
+        });
+        self.collapse_continuations = !self.collapse_continuations;
+        self.apply_filter();
+        if let Some(index) = current {
+            self.scroll_to_message(index);
+        }
+    }
+
+    /// Handles keys for the quick filters while viewing the log. Returns
+    /// whether the key was handled.
+    fn update_filter_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char('l') => {
+                self.filters.latest_launch_only = !self.filters.latest_launch_only;
+                self.apply_filter();
+                true
+            }
+            KeyCode::Char('t') => {
+                self.filters.multi_line_only = !self.filters.multi_line_only;
+                self.apply_filter();
+                true
+            }
+            KeyCode::Char('I') => {
+                self.filters.important_only = !self.filters.important_only;
+                self.apply_filter();
+                true
+            }
+            KeyCode::Char('d') => {
+                self.toggle_debug_levels();
+                true
+            }
+            KeyCode::Char('H') if !self.noisy_sources.is_empty() => {
+                let hidden = self.noisy_sources.len();
+                for source in std::mem::take(&mut self.noisy_sources) {
+                    self.filters.sources.insert(source, false);
+                }
+                self.apply_filter();
+                self.notice = Some(if hidden == 1 {
+                    "Hid 1 noisy source".into()
+                } else {
+                    format!("Hid {hidden} noisy sources").into()
+                });
+                true
+            }
+            KeyCode::Char('X') => {
+                let mut exception_input_state = CommandInputState::default();
+                if let Some(exception) = self.filters.exception.as_ref() {
+                    exception_input_state.set_text(exception.clone());
+                }
+                self.exception_input_state = Some(exception_input_state);
+                true
+            }
+            KeyCode::Char('a') if self.mark.is_some() => {
+                self.filters.since_mark = match self.filters.since_mark {
+                    Some(_) => None,
+                    None => self.mark,
+                };
+                self.apply_filter();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Hides trace and debug messages if either is shown. Otherwise, shows
+    /// them again the way they were before they were hidden.
+    fn toggle_debug_levels(&mut self) {
+        if self.debug_levels_shown() {
+            let saved = Self::DEBUG_LEVELS.map(|level| self.filters.level_enabled(level));
+            self.saved_debug_levels = Some(saved);
+            for level in Self::DEBUG_LEVELS {
+                self.filters.levels.insert(level, false);
+            }
+        } else {
+            let saved = self.saved_debug_levels.take().unwrap_or([true; 2]);
+            for (level, enabled) in Self::DEBUG_LEVELS.into_iter().zip(saved) {
+                self.filters.levels.insert(level, enabled);
+            }
+        }
+        self.apply_filter();
+    }
+
+    /// Checks whether trace or debug messages are shown.
+    fn debug_levels_shown(&self) -> bool {
+        Self::DEBUG_LEVELS
+            .into_iter()
+            .any(|level| self.filters.level_enabled(level))
+    }
+
+    /// Scrolls to the next line that matches the current search. Returns
+    /// whether a match was found. If no visible lines match, a notice says
+    /// how many matching messages are hidden by the filters, or collapsed.
+    fn scroll_to_match(&mut self, start: usize, forward: bool) -> bool {
+        let Some(search) = self.search.as_ref() else {
+            return false;
+        };
+
+        let search = search.to_lowercase();
+        let matches = |line: &str| line.to_lowercase().contains(&search);
+        if self.scroll_to_line(start, forward, |line| matches(line.line())) {
+            return true;
+        }
+
+        if !self.lines.iter().any(|line| matches(line.line())) {
+            // Messages can match in lines that are collapsed without being
+            // hidden by the filters
+            let (hidden, collapsed) = self
+                .log
+                .messages()
+                .iter()
+                .filter(|message| message.contents.lines().any(matches))
+                .partition::<Vec<_>, _>(|message| !self.filters.matches(message));
+            self.notice = Some(match (hidden.len(), collapsed.len()) {
+                (0, 0) => "No matches".into(),
+                (0, collapsed) => {
+                    format!("0 visible matches ({collapsed} in collapsed messages)").into()
+                }
+                (hidden, _) => format!("0 visible matches ({hidden} hidden by filters)").into(),
+            });
+        }
+        false
+    }
+
+    /// Handles events while the search prompt is open.
+    fn update_search_input(&mut self, event: &AppEvent) -> bool {
+        if !self.searching {
+            return false;
+        }
+
+        if let AppEvent::TermEvent(Event::Key(key_event)) = event {
+            match key_event.code {
+                KeyCode::Enter => {
+                    // The prompt remembers the search, skipping repeated
+                    // searches
+                    self.search_input_state.update(event);
+                    let search = self.search_input_state.take_submitted().into_iter().last();
+                    self.searching = false;
+                    let Some(search) = search.filter(|search| !search.is_empty()) else {
+                        return true;
+                    };
+
+                    self.search = Some(search);
+                    self.scroll_to_match(self.paragraph_state.offset.y, true);
+                    return true;
+                }
+                KeyCode::Esc => {
+                    self.searching = false;
+                    return true;
+                }
+                _ => {}
+            }
+        }
+
+        self.search_input_state.update(event)
+    }
+
+    /// Handles events while the problems list is open.
+    fn update_problems(&mut self, event: &AppEvent) -> bool {
+        let Some(problems_state) = self.problems_state.as_mut() else {
+            return false;
+        };
+
+        if let AppEvent::TermEvent(Event::Key(key_event)) = event {
+            match key_event.code {
+                KeyCode::Enter => {
+                    if let Some(index) = problems_state.selected_message() {
+                        self.scroll_to_message(index);
+                    }
+                    return true;
+                }
+                KeyCode::Char('p') | KeyCode::Esc => {
+                    self.problems_state = None;
+                    return true;
+                }
+                _ => {}
+            }
+        }
+
+        problems_state.update(event) || self.paragraph_state.update(event)
+    }
+
+    /// Handles events while the timeline is open.
+    fn update_timeline(&mut self, event: &AppEvent) -> bool {
+        let Some(timeline_state) = self.timeline_state.as_mut() else {
+            return false;
+        };
+
+        if let AppEvent::TermEvent(Event::Key(key_event)) = event {
+            match key_event.code {
+                KeyCode::Enter => {
+                    if let Some(index) = timeline_state.selected_message() {
+                        self.scroll_to_message(index);
+                    }
+                    return true;
+                }
+                KeyCode::Char('T') | KeyCode::Esc => {
+                    self.timeline_state = None;
+                    return true;
+                }
+                _ => {}
+            }
+        }
+
+        timeline_state.update(event) || self.paragraph_state.update(event)
+    }
+
+    /// Handles events while the prompt for hiding sources with a glob is open.
+    fn update_glob_input(&mut self, event: &AppEvent) -> bool {
+        let Some(glob_input_state) = self.glob_input_state.as_mut() else {
+            return false;
+        };
+
+        if let AppEvent::TermEvent(Event::Key(key_event)) = event {
+            match key_event.code {
+                KeyCode::Enter => {
+                    let pattern = glob_input_state.text().trim().to_string();
+                    self.glob_input_state = None;
+                    if pattern.is_empty() {
+                        self.filters.hidden_sources = None;
+                    } else {
+                        match SourceGlob::new(&pattern) {
+                            Ok(glob) => self.filters.hidden_sources = Some(glob),
+                            Err(error) => {
+                                // Keep the previous glob
+                                self.notice = Some(format!("Invalid glob: {error}").into());
+                                return true;
+                            }
+                        }
+                    }
+                    self.apply_filter();
+                    return true;
+                }
+                KeyCode::Esc => {
+                    self.glob_input_state = None;
+                    return true;
+                }
+                _ => {}
+            }
+        }
+
+        glob_input_state.update(event)
+    }
+
+    /// Handles events while the prompt for showing sources matching a regex
+    /// is open.
+    fn update_regex_input(&mut self, event: &AppEvent) -> bool {
+        let Some(regex_input_state) = self.regex_input_state.as_mut() else {
+            return false;
+        };
+
+        if let AppEvent::TermEvent(Event::Key(key_event)) = event {
+            match key_event.code {
+                KeyCode::Enter => {
+                    let pattern = regex_input_state.text().trim();
+                    if pattern.is_empty() {
+                        self.filters.source_pattern = None;
+                    } else {
+                        let Ok(regex) = Regex::new(pattern) else {
+                            // Keep the prompt open so the pattern can be fixed
+                            self.notice = Some("Invalid regex".into());
+                            return true;
+                        };
+                        self.filters.source_pattern = Some(regex);
+                    }
+                    self.regex_input_state = None;
+                    self.apply_filter();
+                    return true;
+                }
+                KeyCode::Esc => {
+                    self.regex_input_state = None;
+                    return true;
+                }
+                _ => {}
+            }
+        }
+
+        regex_input_state.update(event)
+    }
+
+    /// Handles events while the prompt for filtering exceptions is open.
+    fn update_exception_input(&mut self, event: &AppEvent) -> bool {
+        let Some(exception_input_state) = self.exception_input_state.as_mut() else {
+            return false;
+        };
+
+        if let AppEvent::TermEvent(Event::Key(key_event)) = event {
+            match key_event.code {
+                KeyCode::Enter => {
+                    let exception = exception_input_state.text().trim().to_string();
+                    self.exception_input_state = None;
+                    self.filters.exception = (!exception.is_empty()).then_some(exception);
+                    self.apply_filter();
+                    return true;
+                }
+                KeyCode::Esc => {
+                    self.exception_input_state = None;
+                    return true;
+                }
+                _ => {}
+            }
+        }
+
+        exception_input_state.update(event)
+    }
+
+    /// Handles events while the prompt for the time range is open.
+    fn update_time_range_input(&mut self, event: &AppEvent) -> bool {
+        let Some(time_range_input_state) = self.time_range_input_state.as_mut() else {
+            return false;
+        };
+
+        if let AppEvent::TermEvent(Event::Key(key_event)) = event {
+            match key_event.code {
+                KeyCode::Tab | KeyCode::BackTab => {
+                    time_range_input_state.editing_end = !time_range_input_state.editing_end;
+                    return true;
+                }
+                KeyCode::Enter => {
+                    let Ok(range) = time_range_input_state.range() else {
+                        // Keep the prompt open so the times can be fixed
+                        self.notice = Some("Invalid time, use HH:MM:SS".into());
+                        return true;
+                    };
+                    self.time_range_input_state = None;
+                    self.filters.time_range = range;
+                    self.apply_filter();
+                    return true;
+                }
+                KeyCode::Esc => {
+                    self.time_range_input_state = None;
+                    return true;
+                }
+                _ => {}
+            }
+        }
+
+        time_range_input_state.focused_mut().update(event)
+    }
+
+    /// Handles events while the prompt for editing a note is open.
+    fn update_note_input(&mut self, event: &AppEvent) -> bool {
+        let Some((index, note_input_state)) = self.note_input_state.as_mut() else {
+            return false;
+        };
+
+        if let AppEvent::TermEvent(Event::Key(key_event)) = event {
+            match key_event.code {
+                KeyCode::Enter => {
+                    // Clearing the note removes it
+                    let note = note_input_state.text().trim().to_string();
+                    let index = *index;
+                    self.note_input_state = None;
+                    let changed = if note.is_empty() {
+                        self.notes.remove(&index).is_some()
+                    } else {
+                        self.notes.insert(index, note.clone()).as_ref() != Some(&note)
+                    };
+                    self.notes_changed |= changed;
+                    return true;
+                }
+                KeyCode::Esc => {
+                    self.note_input_state = None;
+                    return true;
+                }
+                _ => {}
+            }
+        }
+
+        note_input_state.update(event)
+    }
+
+    /// Gets the message whose source is rendered at the given cell, but only
+    /// if the source is cut off and can't be read in full.
+    fn truncated_source_at(&self, column: u16, row: u16) -> Option<&'i Message<'i>> {
+        let SourceColumn { area, start, width } = self.source_column?;
+        if column < area.x || column >= area.right() || row < area.y || row >= area.bottom() {
+            return None;
+        }
+
+        // Find the message on the hovered row. Only the first row of a
+        // wrapped line has the source.
+        let (index, 0) = self
+            .paragraph_state
+            .line_at_row(usize::from(row - area.y))?
+        else {
+            return None;
+        };
+        let FormattedLine::Start { message, .. } = self.lines.get(index)? else {
+            return None;
+        };
+
+        // Check if the cell is on the source
+        let full_width = message.source.width();
+        let source_width = full_width.min(width);
+        let visible_start = self.paragraph_state.offset.x;
+        let content_x = visible_start.saturating_add(usize::from(column - area.x));
+        if content_x < start || content_x >= start.saturating_add(source_width) {
+            return None;
+        }
+
+        // Check if the source is cut off by the column width, by scrolling, or
+        // by the edge of the area
+        let visible_end = visible_start.saturating_add(usize::from(area.width));
+        let truncated = full_width > width
+            || start < visible_start
+            || start.saturating_add(source_width) > visible_end;
+        truncated.then_some(message)
+    }
+}
+
+impl State for FormattedLogState<'_> {
+    fn update(&mut self, event: &AppEvent) -> bool {
+        // Notices are dismissed by the next key press
+        let dismissed_notice =
+            matches!(event, AppEvent::TermEvent(Event::Key(_))) && self.notice.take().is_some();
+
+        // The highlighted error source is dismissed by the next key press, or
+        // once it's been shown long enough
+        let dismissed_error_source = match event {
+            AppEvent::TermEvent(Event::Key(_)) => self.error_source.take().is_some(),
+            AppEvent::Ping => self
+                .error_source
+                .take_if(|(_, since)| since.elapsed() >= self.error_highlight_duration)
+                .is_some(),
+            AppEvent::TermEvent(_) => false,
+        };
+        let handled = self.update_log(event);
+        self.update_expanded_message() || handled || dismissed_notice || dismissed_error_source
+    }
+
+    fn add_controls<I: IconPack>(&self, controls: &mut IndexMap<BindingDisplay<I>, &'static str>) {
+        self.add_log_controls(controls);
+    }
+}
+
+impl FormattedLogState<'_> {
+    /// Expands the message at the top of the view if messages are collapsed
+    /// and auto-expanding is enabled, collapsing the previously expanded
+    /// message. The same line stays at the top of the view. Returns whether
+    /// the lines changed.
+    fn update_expanded_message(&mut self) -> bool {
+        // Find the message at the top of the view and where its lines start
+        let top = self.paragraph_state.offset.y;
+        let current = self
+            .lines
+            .get(..=top)
+            .and_then(|lines| {
+                lines
+                    .iter()
+                    .rposition(|line| matches!(line, FormattedLine::Start { .. }))
+            })
+            .and_then(|start| match self.lines[start] {
+                FormattedLine::Start { index, .. } => Some((start, index)),
+                FormattedLine::Continued { .. } => None,
+            });
+        let expanded = current
+            .map(|(_, index)| index)
+            .filter(|_| self.collapse_continuations && self.auto_expand);
+        if expanded == self.expanded_message {
+            return false;
+        }
+
+        self.expanded_message = expanded;
+        let (lines, source_width) = Self::format_lines(
+            self.log,
+            self.filters.clone(),
+            self.collapse_continuations,
+            self.expanded_message,
+        );
+        self.lines = lines;
+        self.source_width = source_width;
+        self.paragraph_state.lines = self.lines.len();
+
+        // Keep the same line at the top, or the start of the message if that
+        // line was collapsed
+        if let Some((start, index)) = current {
+            let new_start = self.lines.iter().position(
+                |line| matches!(*line, FormattedLine::Start { index: other, .. } if other == index),
+            );
+            if let Some(new_start) = new_start {
+                let collapsed = self.collapse_continuations && expanded != Some(index);
+                let within = if collapsed { 0 } else { top - start };
+                self.paragraph_state.offset.y = new_start + within;
+            }
+        }
+        true
+    }
+
+    /// Handles a click on the filters list. Clicking a filter selects and
+    /// toggles it, and clicking "..." shows the next or previous page.
+    /// Returns whether anything was clicked.
+    fn click_filter(&mut self, column: u16, row: u16) -> bool {
+        let Some(filters_list_state) = self.filters_list_state.as_mut() else {
+            return false;
+        };
+        match filters_list_state.click_target_at(column, row) {
+            Some(FilterClickTarget::Filter(index)) => {
+                filters_list_state.selected = index;
+                filters_list_state.toggle(&mut self.filters);
+                self.apply_filter();
+                true
+            }
+            Some(FilterClickTarget::Page(index)) => {
+                filters_list_state.selected = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Opens the prompts for filtering sources by pattern while the sources
+    /// are listed in the filters. Returns whether a prompt was opened.
+    fn open_sources_prompt(&mut self, code: KeyCode) -> bool {
+        let listing_sources = self
+            .filters_list_state
+            .as_ref()
+            .is_some_and(|state| state.source == FiltersListSource::Sources);
+        if !listing_sources {
+            return false;
+        }
+
+        match code {
+            KeyCode::Char('g') => {
+                let mut glob_input_state = CommandInputState::default();
+                if let Some(glob) = self.filters.hidden_sources.as_ref() {
+                    glob_input_state.set_text(glob.pattern().to_string());
+                }
+                self.glob_input_state = Some(glob_input_state);
+                true
+            }
+            KeyCode::Char('r') => {
+                let mut regex_input_state = CommandInputState::default();
+                if let Some(regex) = self.filters.source_pattern.as_ref() {
+                    regex_input_state.set_text(regex.as_str().to_string());
+                }
+                self.regex_input_state = Some(regex_input_state);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Opens the histogram for the source selected in the filters. Returns
+    /// whether a source was selected.
+    fn open_histogram(&mut self) -> bool {
+        let source = self
+            .filters_list_state
+            .as_ref()
+            .and_then(|state| state.selected_source(&self.filters));
+        self.histogram_source = source.map(str::to_string);
+        self.histogram_source.is_some()
+    }
+
+    /// Handles events while the histogram is open.
+    fn update_histogram(&mut self, event: &AppEvent) -> bool {
+        if let AppEvent::TermEvent(Event::Key(key_event)) = event {
+            if matches!(key_event.code, KeyCode::Char('h') | KeyCode::Esc) {
+                self.histogram_source = None;
+                return true;
+            }
+        }
+
+        self.paragraph_state.update(event)
+    }
+
+    /// Handles the keys for the time range while the filters are open.
+    fn update_time_range_key(&mut self, code: KeyCode) -> bool {
+        if self.filters_list_state.is_none() {
+            return false;
+        }
+
+        match code {
+            KeyCode::Char('t') => {
+                self.time_range_input_state =
+                    Some(TimeRangeInputState::new(self.filters.time_range));
+                true
+            }
+            KeyCode::Char('e') if self.filters.time_range.is_some() => {
+                self.filters.errors_outside_time_range = !self.filters.errors_outside_time_range;
+                self.apply_filter();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Updates the log and any widgets open in it.
+    fn update_log(&mut self, event: &AppEvent) -> bool {
+        if let AppEvent::Ping = event {
+            return self.paragraph_state.update(event);
+        }
+
+        // Events handled by the search prompt while it's open
+        if self.searching {
+            return self.update_search_input(event);
+        }
+
+        // Events handled by the glob, regex and exception prompts while
+        // they're open
+        if self.glob_input_state.is_some() {
+            return self.update_glob_input(event);
+        }
+        if self.regex_input_state.is_some() {
+            return self.update_regex_input(event);
+        }
+        if self.exception_input_state.is_some() {
+            return self.update_exception_input(event);
+        }
+        if self.time_range_input_state.is_some() {
+            return self.update_time_range_input(event);
+        }
+        if self.note_input_state.is_some() {
+            return self.update_note_input(event);
+        }
+
+        // Events handled by the problems list while it's open
+        if self.problems_state.is_some() {
+            return self.update_problems(event);
+        }
+
+        // Events handled by the timeline while it's open
+        if self.timeline_state.is_some() {
+            return self.update_timeline(event);
+        }
+
+        // Events handled by the histogram while it's open
+        if self.histogram_source.is_some() {
+            return self.update_histogram(event);
+        }
+
+        // Events handled by the formatted log widget
+        match *event {
+            AppEvent::TermEvent(Event::Key(key_event)) => match key_event.code {
+                KeyCode::Char('f') => {
+                    // Opens the levels, then the sources, then the categories
+                    // if any messages were tagged with one
+                    let source = self.filters_list_state.take().map(|state| state.source);
+                    self.filters_list_state = Some(match source {
+                        Some(FiltersListSource::Levels) => {
+                            FiltersListState::sources(self.filters_sidebar)
                         }
-                        FiltersListSource::Sources => {
-                            self.filters_list_state = Some(FiltersListState::levels());
+                        Some(FiltersListSource::Sources) if !self.filters.categories.is_empty() => {
+                            FiltersListState::categories(self.filters_sidebar)
                         }
+                        _ => FiltersListState::levels(self.filters_sidebar),
+                    });
+                    return true;
+                }
+                KeyCode::Char('s') if self.filters_list_state.is_some() => {
+                    self.filters_sidebar = !self.filters_sidebar;
+                    if let Some(filters_list_state) = self.filters_list_state.as_mut() {
+                        filters_list_state.sidebar = self.filters_sidebar;
                     }
                     return true;
                 }
@@ -292,63 +2211,608 @@ impl<'i> State for FormattedLogState<'i> {
                     self.apply_filter();
                     return true;
                 }
+                code if self.open_sources_prompt(code) => return true,
+                KeyCode::Char('h') if self.open_histogram() => return true,
+                code if self.update_time_range_key(code) => return true,
                 KeyCode::Esc if self.filters_list_state.is_some() => {
                     self.filters_list_state = None;
                     return true;
                 }
+                code if self.filters_list_state.is_none() && self.update_log_key(code) => {
+                    return true;
+                }
                 _ => {}
             },
+            AppEvent::TermEvent(Event::Mouse(mouse_event))
+                if mouse_event.kind == MouseEventKind::Down(MouseButton::Left)
+                    && self.filters_list_state.is_some() =>
+            {
+                return self.click_filter(mouse_event.column, mouse_event.row);
+            }
+            AppEvent::TermEvent(Event::Mouse(mouse_event))
+                if mouse_event.kind == MouseEventKind::Moved =>
+            {
+                // Only redraw if the tooltip changes
+                let previous = self
+                    .hovered
+                    .and_then(|(column, row)| self.truncated_source_at(column, row));
+                self.hovered = Some((mouse_event.column, mouse_event.row));
+                let current = self.truncated_source_at(mouse_event.column, mouse_event.row);
+                return previous.map(std::ptr::from_ref) != current.map(std::ptr::from_ref);
+            }
             _ => {}
         }
 
-        // Children events
-        match self.filters_list_state.as_mut() {
-            None => self.paragraph_state.update(event),
-            Some(filters_list_state) => filters_list_state.update(event),
+        // Children events
+        match self.filters_list_state.as_mut() {
+            None => self.paragraph_state.update(event),
+            Some(filters_list_state) => filters_list_state.update(event),
+        }
+    }
+
+    fn add_log_controls<I: IconPack>(
+        &self,
+        controls: &mut IndexMap<BindingDisplay<I>, &'static str>,
+    ) {
+        if self.searching {
+            self.search_input_state.add_controls(controls);
+            controls.insert(BindingDisplay::simple_key(KeyCode::Enter), "Search");
+            controls.insert(BindingDisplay::simple_key(KeyCode::Esc), "Cancel");
+            controls.insert(BindingDisplay::Custom(I::UP_DOWN), "History");
+            return;
+        }
+
+        if let Some(glob_input_state) = self.glob_input_state.as_ref() {
+            glob_input_state.add_controls(controls);
+            controls.insert(BindingDisplay::simple_key(KeyCode::Enter), "Hide matches");
+            controls.insert(BindingDisplay::simple_key(KeyCode::Esc), "Cancel");
+            return;
+        }
+
+        if let Some(regex_input_state) = self.regex_input_state.as_ref() {
+            regex_input_state.add_controls(controls);
+            controls.insert(BindingDisplay::simple_key(KeyCode::Enter), "Show matches");
+            controls.insert(BindingDisplay::simple_key(KeyCode::Esc), "Cancel");
+            return;
+        }
+
+        if let Some(exception_input_state) = self.exception_input_state.as_ref() {
+            exception_input_state.add_controls(controls);
+            controls.insert(BindingDisplay::simple_key(KeyCode::Enter), "Filter");
+            controls.insert(BindingDisplay::simple_key(KeyCode::Esc), "Cancel");
+            return;
+        }
+
+        if let Some(time_range_input_state) = self.time_range_input_state.as_ref() {
+            let field = if time_range_input_state.editing_end {
+                &time_range_input_state.end
+            } else {
+                &time_range_input_state.start
+            };
+            field.add_controls(controls);
+            controls.insert(BindingDisplay::simple_key(KeyCode::Enter), "Filter");
+            controls.insert(BindingDisplay::simple_key(KeyCode::Tab), "Switch field");
+            controls.insert(BindingDisplay::simple_key(KeyCode::Esc), "Cancel");
+            return;
+        }
+
+        if let Some((_, note_input_state)) = self.note_input_state.as_ref() {
+            note_input_state.add_controls(controls);
+            controls.insert(BindingDisplay::simple_key(KeyCode::Enter), "Save note");
+            controls.insert(BindingDisplay::simple_key(KeyCode::Esc), "Cancel");
+            return;
+        }
+
+        if let Some(problems_state) = self.problems_state.as_ref() {
+            controls.insert(BindingDisplay::simple_key(KeyCode::Enter), "Jump");
+            controls.insert(BindingDisplay::simple_key(KeyCode::Esc), "Close");
+            problems_state.add_controls(controls);
+            return;
+        }
+
+        if let Some(timeline_state) = self.timeline_state.as_ref() {
+            controls.insert(BindingDisplay::simple_key(KeyCode::Enter), "Jump");
+            controls.insert(BindingDisplay::simple_key(KeyCode::Esc), "Close");
+            timeline_state.add_controls(controls);
+            return;
+        }
+
+        if self.histogram_source.is_some() {
+            controls.insert(BindingDisplay::simple_key(KeyCode::Esc), "Close");
+            return;
         }
-    }
 
-    fn add_controls<I: IconPack>(&self, controls: &mut IndexMap<BindingDisplay<I>, &'static str>) {
         match self.filters_list_state.as_ref() {
-            None => {
-                controls.insert(BindingDisplay::simple_key(KeyCode::Char('f')), "Filters");
-                self.paragraph_state.add_controls(controls);
-            }
+            None => self.add_view_controls(controls),
             Some(filters_list_state) => {
-                controls.insert(BindingDisplay::simple_key(KeyCode::Char('f')), "Next");
-                controls.insert(BindingDisplay::simple_key(KeyCode::Char(' ')), "Toggle");
-                controls.insert(BindingDisplay::simple_key(KeyCode::Esc), "Close");
-                filters_list_state.add_controls(controls);
+                self.add_filters_list_controls(filters_list_state, controls);
+            }
+        }
+    }
+
+    /// Adds the controls for the filters list while it's open.
+    fn add_filters_list_controls<I: IconPack>(
+        &self,
+        filters_list_state: &FiltersListState,
+        controls: &mut IndexMap<BindingDisplay<I>, &'static str>,
+    ) {
+        controls.insert(BindingDisplay::simple_key(KeyCode::Char('f')), "Next");
+        controls.insert(BindingDisplay::simple_key(KeyCode::Char(' ')), "Toggle");
+        if filters_list_state.source == FiltersListSource::Sources {
+            controls.insert(BindingDisplay::simple_key(KeyCode::Char('g')), "Hide glob");
+            controls.insert(BindingDisplay::simple_key(KeyCode::Char('r')), "Regex");
+            if filters_list_state.selected_source(&self.filters).is_some() {
+                controls.insert(BindingDisplay::simple_key(KeyCode::Char('h')), "Histogram");
             }
         }
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('t')),
+            if self.filters.time_range.is_some() {
+                "Change times"
+            } else {
+                "Time range"
+            },
+        );
+        if self.filters.time_range.is_some() {
+            controls.insert(
+                BindingDisplay::simple_key(KeyCode::Char('e')),
+                if self.filters.errors_outside_time_range {
+                    "Hide other errors"
+                } else {
+                    "Keep all errors"
+                },
+            );
+        }
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('s')),
+            if filters_list_state.sidebar {
+                "Bottom bar"
+            } else {
+                "Sidebar"
+            },
+        );
+        controls.insert(BindingDisplay::simple_key(KeyCode::Esc), "Close");
+        filters_list_state.add_controls(controls);
+    }
+
+    /// Adds the controls for the quick filters while viewing the log.
+    fn add_filter_controls<I: IconPack>(
+        &self,
+        controls: &mut IndexMap<BindingDisplay<I>, &'static str>,
+    ) {
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('l')),
+            if self.filters.latest_launch_only {
+                "All launches"
+            } else {
+                "Latest launch"
+            },
+        );
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('t')),
+            if self.filters.multi_line_only {
+                "All messages"
+            } else {
+                "Traces only"
+            },
+        );
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('I')),
+            if self.filters.important_only {
+                "Show noise"
+            } else {
+                "Important only"
+            },
+        );
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('d')),
+            if self.debug_levels_shown() {
+                "Hide debug"
+            } else {
+                "Show debug"
+            },
+        );
+        if !self.noisy_sources.is_empty() {
+            controls.insert(BindingDisplay::simple_key(KeyCode::Char('H')), "Hide noisy");
+        }
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('X')),
+            if self.filters.exception.is_some() {
+                "Change exception"
+            } else {
+                "Exception type"
+            },
+        );
+    }
+
+    /// Adds the controls for viewing the log, when nothing is open in it.
+    fn add_view_controls<I: IconPack>(
+        &self,
+        controls: &mut IndexMap<BindingDisplay<I>, &'static str>,
+    ) {
+        controls.insert(BindingDisplay::simple_key(KeyCode::Char('f')), "Filters");
+        self.add_filter_controls(controls);
+        controls.insert(BindingDisplay::simple_key(KeyCode::Char('p')), "Problems");
+        controls.insert(BindingDisplay::simple_key(KeyCode::Char('T')), "Timeline");
+        controls.insert(BindingDisplay::simple_key(KeyCode::Char('c')), "Copy");
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('C')),
+            "Copy 1st line",
+        );
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('m')),
+            self.copy_format.label(),
+        );
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('z')),
+            if self.collapse_continuations {
+                "Expand all"
+            } else {
+                "Collapse all"
+            },
+        );
+        if self.collapse_continuations {
+            controls.insert(
+                BindingDisplay::simple_key(KeyCode::Char('x')),
+                if self.auto_expand {
+                    "Keep collapsed"
+                } else {
+                    "Expand current"
+                },
+            );
+        }
+        controls.insert(BindingDisplay::Custom("{}"), "Same source");
+        controls.insert(
+            BindingDisplay::Custom("eE"),
+            if self.jump_to_warnings {
+                "Next/prev error/warn"
+            } else {
+                "Next/prev error"
+            },
+        );
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('F')),
+            "First error",
+        );
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('W')),
+            if self.jump_to_warnings {
+                "Errors only"
+            } else {
+                "Include warnings"
+            },
+        );
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('#')),
+            if self.show_message_numbers {
+                "Hide numbers"
+            } else {
+                "Show numbers"
+            },
+        );
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('w')),
+            if self.wrap { "No wrap" } else { "Wrap" },
+        );
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('+')),
+            if self.show_elapsed {
+                "Hide elapsed"
+            } else {
+                "Show elapsed"
+            },
+        );
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('A')),
+            if self
+                .current_message_index()
+                .is_some_and(|index| self.notes.contains_key(&index))
+            {
+                "Edit note"
+            } else {
+                "Add note"
+            },
+        );
+        controls.insert(BindingDisplay::simple_key(KeyCode::Char('M')), "Set mark");
+        if self.mark.is_some() {
+            controls.insert(
+                BindingDisplay::simple_key(KeyCode::Char('a')),
+                if self.filters.since_mark.is_some() {
+                    "All messages"
+                } else {
+                    "After mark"
+                },
+            );
+        }
+        controls.insert(BindingDisplay::simple_key(KeyCode::Char('/')), "Search");
+        if self.search.is_some() {
+            controls.insert(BindingDisplay::simple_key(KeyCode::Char('n')), "Next match");
+            controls.insert(BindingDisplay::simple_key(KeyCode::Char('N')), "Prev match");
+        }
+        self.paragraph_state.add_controls(controls);
     }
 }
 
-impl<'i, 'j> WithLog<'j> for FormattedLogState<'i> {
+impl<'j> WithLog<'j> for FormattedLogState<'_> {
     type Result = FormattedLogState<'j>;
 
     fn with_log(self, log: &'j Log) -> Self::Result {
-        let filters = self.filters.with_log(log);
-        let (lines, source_width) = FormattedLogState::format_lines(log, filters.clone());
-        let mut paragraph_state = LazyParagraphState::new(lines.len(), true);
-        paragraph_state.offset = self.paragraph_state.offset;
-        paragraph_state.auto_scroll = self.paragraph_state.auto_scroll;
+        // A log with fewer messages than when the mark was set is a new log
+        let mark = self.mark.filter(|&mark| mark <= log.messages().len());
+        let mut filters = self.filters.with_log(log);
+        filters.group_sources_below(log, self.group_sources_below);
+        if mark.is_none() {
+            filters.since_mark = None;
+        }
+
+        let (lines, source_width) = FormattedLogState::format_lines(
+            log,
+            filters.clone(),
+            self.collapse_continuations,
+            self.expanded_message,
+        );
+        let paragraph_state = self.paragraph_state.with_lines(lines.len());
+        let noisy_sources = FormattedLogState::find_noisy_sources(log, &filters);
+
+        // Highlight the source of the latest error that just arrived
+        let new_messages = log
+            .messages()
+            .get(self.log.messages().len()..)
+            .unwrap_or_default();
+        let error_source = new_messages
+            .iter()
+            .rev()
+            .find(|message| message.level.is_error())
+            .map(|message| (message.source.as_ref(), Instant::now()))
+            .or_else(|| {
+                let (source, since) = self.error_source?;
+                Some((log.sources().find(|&other| other == source)?, since))
+            })
+            .filter(|_| !self.error_highlight_duration.is_zero());
+
+        // Notes on messages past the end of a new log don't belong to it
+        let notes = move_notes(self.log, log, self.notes);
         FormattedLogState {
             log,
             filters,
             filters_list_state: self.filters_list_state.with_log(log),
+            filters_sidebar: self.filters_sidebar,
+            group_sources_below: self.group_sources_below,
+            problems_state: self.problems_state.with_log(log),
+            timeline_state: self.timeline_state.with_log(log),
+            histogram_source: self.histogram_source,
+            copy_request: self.copy_request,
+            copy_format: self.copy_format,
+            join_lines: self.join_lines,
+            show_message_numbers: self.show_message_numbers,
+            wrap: self.wrap,
+            show_elapsed: self.show_elapsed,
+            searching: self.searching,
+            search_input_state: self.search_input_state,
+            search: self.search,
+            glob_input_state: self.glob_input_state,
+            regex_input_state: self.regex_input_state,
+            exception_input_state: self.exception_input_state,
+            time_range_input_state: self.time_range_input_state,
+            saved_debug_levels: self.saved_debug_levels,
+            noisy_sources,
+            error_source,
+            error_highlight_duration: self.error_highlight_duration,
+            notes,
+            notes_changed: self.notes_changed,
+            note_input_state: self
+                .note_input_state
+                .filter(|&(index, _)| index < log.messages().len()),
+            collapse_continuations: self.collapse_continuations,
+            auto_expand: self.auto_expand,
+            expanded_message: self.expanded_message,
+            wrap_navigation: self.wrap_navigation,
+            jump_to_warnings: self.jump_to_warnings,
+            notice: self.notice,
+            mark,
+            buffered_messages: 0,
             lines,
             source_width,
+            show_dates: FormattedLogState::has_dates(log),
             paragraph_state,
+            source_column: self.source_column,
+            hovered: self.hovered,
+        }
+    }
+}
+
+/// What to include when copying a message.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CopyFormat {
+    /// Only the contents of the message.
+    #[default]
+    Contents,
+    /// The message as it appears in the log, including its header.
+    Full,
+    /// The message as it appears in the log, but without the timestamp.
+    NoTimestamp,
+}
+
+impl CopyFormat {
+    /// Formats the contents of a message for copying.
+    pub fn format(self, message: &Message, contents: &str) -> String {
+        match self {
+            CopyFormat::Contents => contents.to_string(),
+            CopyFormat::Full => format!("{} {contents}", message.header(true)),
+            CopyFormat::NoTimestamp => format!("{} {contents}", message.header(false)),
+        }
+    }
+
+    /// Gets the next format to switch to.
+    pub fn next(self) -> Self {
+        match self {
+            CopyFormat::Contents => CopyFormat::Full,
+            CopyFormat::Full => CopyFormat::NoTimestamp,
+            CopyFormat::NoTimestamp => CopyFormat::Contents,
+        }
+    }
+
+    /// Gets the label displayed in the controls bar.
+    pub fn label(self) -> &'static str {
+        match self {
+            CopyFormat::Contents => "Copying: contents",
+            CopyFormat::Full => "Copying: full",
+            CopyFormat::NoTimestamp => "Copying: no time",
+        }
+    }
+}
+
+/// Cuts off a source with an ellipsis if it's wider than the source column.
+/// Widths are measured in terminal cells, so wide characters count as two.
+fn truncate_source(source: &str, width: usize) -> Cow<'_, str> {
+    if source.width() <= width {
+        return source.into();
+    }
+
+    // Leave room for the ellipsis
+    format!("{}…", fit_width(source, width.saturating_sub(1))).into()
+}
+
+/// Gets the longest start of some text that fits in a number of terminal
+/// cells.
+pub fn fit_width(text: &str, width: usize) -> &str {
+    let mut remaining = width;
+    let end = text
+        .char_indices()
+        .find(
+            |(_, c)| match remaining.checked_sub(c.width().unwrap_or_default()) {
+                Some(left) => {
+                    remaining = left;
+                    false
+                }
+                None => true,
+            },
+        )
+        .map_or(text.len(), |(index, _)| index);
+    &text[..end]
+}
+
+/// A column shown before each message in the log.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    Timestamp,
+    /// How long passed since the message shown before it.
+    Elapsed,
+    Level,
+    Source,
+}
+
+/// The columns shown before each message in the log, in order.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+#[serde(transparent)]
+pub struct ColumnLayout(Vec<Column>);
+
+impl Default for ColumnLayout {
+    fn default() -> Self {
+        ColumnLayout(vec![Column::Timestamp, Column::Level, Column::Source])
+    }
+}
+
+impl ColumnLayout {
+    /// Shows or hides the elapsed time column. It's shown after the timestamp
+    /// if it isn't already part of the layout.
+    fn with_elapsed(&self, show_elapsed: bool) -> Self {
+        let mut columns = self.0.clone();
+        if !show_elapsed {
+            columns.retain(|&column| column != Column::Elapsed);
+        } else if !columns.contains(&Column::Elapsed) {
+            let index = columns
+                .iter()
+                .position(|&column| column == Column::Timestamp)
+                .map_or(0, |index| index + 1);
+            columns.insert(index, Column::Elapsed);
+        }
+        ColumnLayout(columns)
+    }
+
+    /// Removes columns listed more than once, keeping the first of each.
+    /// Returns whether any were removed.
+    pub fn remove_duplicates(&mut self) -> bool {
+        let len = self.0.len();
+        let mut seen = HashSet::new();
+        self.0.retain(|&column| seen.insert(column));
+        self.0.len() != len
+    }
+}
+
+impl Deref for ColumnLayout {
+    type Target = [Column];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Formats how long passed between two messages, like `+1m 05s`.
+fn format_gap(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0..=59 => format!("+{seconds}s"),
+        60..=3599 => format!("+{}m {:02}s", seconds / 60, seconds % 60),
+        _ => format!("+{}h {:02}m", seconds / 3600, seconds / 60 % 60),
+    }
+}
+
+/// How long passed before a message since earlier messages.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct MessageGaps {
+    /// The time since the previous message, if there is one.
+    pub previous: Option<Duration>,
+    /// The time since the previous message from the same source, if there is
+    /// one.
+    pub same_source: Option<Duration>,
+}
+
+impl Display for MessageGaps {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Some(previous) = self.previous else {
+            return write!(f, "First message");
+        };
+        match self.same_source {
+            Some(same_source) => write!(
+                f,
+                "Gap: {} ({} from same source)",
+                format_gap(previous),
+                format_gap(same_source)
+            ),
+            None => write!(f, "Gap: {} (first from source)", format_gap(previous)),
         }
     }
 }
 
+/// Where the source column was last rendered.
+#[derive(Clone, Copy, Debug)]
+struct SourceColumn {
+    /// The area the log was rendered in.
+    area: Rect,
+    /// The offset of the source column from the start of each line.
+    start: usize,
+    /// The width of the source column.
+    width: usize,
+}
+
 #[derive(Clone, Debug)]
 enum FormattedLine<'i> {
     Start {
+        /// The index of the message in the log.
+        index: usize,
         message: &'i Message<'i>,
         line: &'i str,
+        /// The number of continuation lines hidden because the message is
+        /// collapsed.
+        hidden_lines: usize,
+        /// Whether the message is on a different date than the message shown
+        /// before it, so its date is shown.
+        show_date: bool,
+        /// How long passed since the message shown before it.
+        elapsed: Option<Duration>,
     },
     Continued {
         message: &'i Message<'i>,
@@ -356,13 +2820,147 @@ enum FormattedLine<'i> {
     },
 }
 
+impl<'i> FormattedLine<'i> {
+    /// Gets the text of this line.
+    fn line(&self) -> &'i str {
+        match *self {
+            FormattedLine::Start { line, .. } | FormattedLine::Continued { line, .. } => line,
+        }
+    }
+
+    /// Gets the message this line belongs to.
+    fn message(&self) -> &'i Message<'i> {
+        match *self {
+            FormattedLine::Start { message, .. } | FormattedLine::Continued { message, .. } => {
+                message
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct LogFilters<'i> {
     pub levels: IndexMap<Level, bool>,
     pub sources: IndexMap<&'i str, bool>,
+    /// Whether to hide messages from before the game was last launched.
+    pub latest_launch_only: bool,
+    /// Whether to hide messages that only have a single line. Messages with
+    /// multiple lines are usually errors with stack traces.
+    pub multi_line_only: bool,
+    /// Whether to only show messages that are likely to be important. This
+    /// hides traces and debug messages, and hides info messages from noisy
+    /// sources. This is applied on top of the other filters so they're kept
+    /// when it's turned off.
+    pub important_only: bool,
+    /// Sources that log lots of messages that are rarely useful. Warnings,
+    /// errors and alerts from these sources are still shown.
+    pub noisy_sources: Vec<String>,
+    /// The number of messages in the log when the mark was set, if only
+    /// messages logged after the mark should be shown.
+    pub since_mark: Option<usize>,
+    /// A glob matching the names of sources to hide, on top of the sources
+    /// that were toggled off.
+    pub hidden_sources: Option<SourceGlob>,
+    /// A regex that sources must match to be shown, on top of the sources
+    /// that were toggled off.
+    pub source_pattern: Option<Regex>,
+    /// The type of exception messages must mention to be shown, like
+    /// `NullReferenceException`. Matching ignores case.
+    pub exception: Option<String>,
+    /// Sources with so few messages that they're listed together as
+    /// "(other)" in the filters, so they can be toggled as a group.
+    pub grouped_sources: Vec<&'i str>,
+    /// Sources that were hidden in a previous session but aren't in the log,
+    /// so they're hidden if they show up.
+    pub remembered_sources: Vec<String>,
+    /// The first and last times of day to show messages from, inclusive.
+    pub time_range: Option<(Timestamp, Timestamp)>,
+    /// Whether errors outside the time range are still shown.
+    pub errors_outside_time_range: bool,
+    /// The classifiers that tag messages with a category, checked in order.
+    pub classifiers: Rc<[Box<dyn MessageClassifier>]>,
+    /// The categories messages in the log were tagged with, and whether
+    /// they're shown. Messages without a category are always shown.
+    pub categories: IndexMap<String, bool>,
 }
 
 impl<'i> LogFilters<'i> {
+    /// Creates filters that only show messages with the given levels and
+    /// sources. If either list is empty, every level or source is shown.
+    pub fn matching(log: &'i Log, levels: &[Level], sources: &[String]) -> Self {
+        LogFilters {
+            levels: log
+                .levels()
+                .map(|level| (level, levels.is_empty() || levels.contains(&level)))
+                .collect(),
+            sources: log
+                .sources()
+                .map(|source| {
+                    let enabled =
+                        sources.is_empty() || sources.iter().any(|enabled| enabled == source);
+                    (source, enabled)
+                })
+                .collect(),
+            latest_launch_only: false,
+            multi_line_only: false,
+            important_only: false,
+            noisy_sources: Vec::new(),
+            since_mark: None,
+            hidden_sources: None,
+            source_pattern: None,
+            exception: None,
+            grouped_sources: Vec::new(),
+            remembered_sources: Vec::new(),
+            time_range: None,
+            errors_outside_time_range: false,
+            classifiers: Rc::default(),
+            categories: IndexMap::new(),
+        }
+    }
+
+    /// Restores the levels and sources that were shown in a previous session.
+    /// Hidden sources that aren't in the log are remembered, so they're hidden
+    /// if they show up.
+    pub fn restore(&mut self, saved: &SavedFilters) {
+        // Unknown levels aren't saved, so they're always shown at first
+        for (level, enabled) in &mut self.levels {
+            *enabled = matches!(level, Level::Other(_)) || saved.levels.contains(level);
+        }
+        for (source, enabled) in &mut self.sources {
+            *enabled = !saved.disabled_sources.iter().any(|hidden| hidden == source);
+        }
+        self.remembered_sources = saved
+            .disabled_sources
+            .iter()
+            .filter(|&hidden| !self.sources.contains_key(hidden.as_str()))
+            .cloned()
+            .collect();
+    }
+
+    /// Gets the levels and sources that are shown, so they can be restored in
+    /// a later session.
+    pub fn to_saved(&self) -> SavedFilters {
+        let disabled_sources = self
+            .sources
+            .iter()
+            .filter(|&(_, &enabled)| !enabled)
+            .map(|(&source, _)| source.to_string());
+        let remembered_sources = self
+            .remembered_sources
+            .iter()
+            .filter(|&hidden| !self.sources.contains_key(hidden.as_str()))
+            .cloned();
+        SavedFilters {
+            levels: self
+                .levels
+                .iter()
+                .filter(|&(level, &enabled)| enabled && !matches!(level, Level::Other(_)))
+                .map(|(&level, _)| level)
+                .collect(),
+            disabled_sources: disabled_sources.chain(remembered_sources).collect(),
+        }
+    }
+
     /// Checks if a level is enabled for this log.
     pub fn level_enabled(&self, level: Level) -> bool {
         self.levels.get(&level).copied().unwrap_or(true)
@@ -371,29 +2969,220 @@ impl<'i> LogFilters<'i> {
     /// Checks if a source is enabled for this log.
     pub fn source_enabled(&self, source: &'i str) -> bool {
         self.sources.get(source).copied().unwrap_or(true)
+            && !self
+                .hidden_sources
+                .as_ref()
+                .is_some_and(|glob| glob.is_match(source))
+            && self
+                .source_pattern
+                .as_ref()
+                .is_none_or(|pattern| pattern.is_match(source))
+    }
+
+    /// Groups the sources with fewer messages than the threshold, as long as
+    /// there are at least two of them. A threshold of 0 groups nothing.
+    pub fn group_sources_below(&mut self, log: &'i Log, threshold: usize) {
+        self.grouped_sources = self
+            .sources
+            .keys()
+            .copied()
+            .filter(|&source| log.messages_by_source(source).len() < threshold)
+            .collect();
+        if self.grouped_sources.len() < 2 {
+            self.grouped_sources.clear();
+        }
+    }
+
+    /// Checks if any of the grouped sources are enabled.
+    pub fn grouped_sources_enabled(&self) -> bool {
+        self.grouped_sources
+            .iter()
+            .any(|&source| self.source_enabled(source))
+    }
+
+    /// Checks if a message is likely to be important.
+    pub fn is_important(&self, message: &Message) -> bool {
+        match message.level {
+            Level::Trace | Level::Debug => false,
+            Level::Info => !self
+                .noisy_sources
+                .iter()
+                .any(|source| source == message.source.as_ref()),
+            Level::Alert | Level::Warn | Level::Error | Level::Other(_) => true,
+        }
     }
 
     /// Applies the filters to the given log.
     pub fn apply(self, log: &'i Log) -> impl IntoIterator<Item = &'i Message<'i>> {
-        log.messages().iter().filter(move |&message| {
-            self.level_enabled(message.level) && self.source_enabled(message.source.as_ref())
-        })
+        self.apply_indexed(log).map(|(_, message)| message)
+    }
+
+    /// Applies the filters to the given log, including the index of each
+    /// message in the log.
+    pub fn apply_indexed(self, log: &'i Log) -> impl Iterator<Item = (usize, &'i Message<'i>)> {
+        let start = if self.latest_launch_only {
+            log.last_launch().unwrap_or(0)
+        } else {
+            0
+        };
+        let start = start.max(self.since_mark.unwrap_or(0));
+        log.messages()
+            .iter()
+            .enumerate()
+            .skip(start)
+            .filter(move |&(_, message)| self.matches(message))
+    }
+
+    /// Checks if a message passes the filters, ignoring which launch or mark
+    /// it comes after.
+    pub fn matches(&self, message: &'i Message<'i>) -> bool {
+        self.level_enabled(message.level)
+            && self.source_enabled(message.source.as_ref())
+            && (!self.multi_line_only || message.contents.contains('\n'))
+            && (!self.important_only || self.is_important(message))
+            && self
+                .exception
+                .as_ref()
+                .is_none_or(|exception| mentions_ignore_case(&message.contents, exception))
+            && self.in_time_range(message)
+            && self.category_enabled(message)
+    }
+
+    /// Checks if the category a message was tagged with is shown. Messages
+    /// without a category are always shown.
+    pub fn category_enabled(&self, message: &Message) -> bool {
+        // Skip classifying messages when every category is shown
+        if self.categories.values().all(|&enabled| enabled) {
+            return true;
+        }
+
+        self.category(message)
+            .is_none_or(|category| self.categories.get(&category.name).copied().unwrap_or(true))
+    }
+
+    /// Gets the category a message is tagged with by the first classifier
+    /// that matches it, if any.
+    fn category(&self, message: &Message) -> Option<Category> {
+        self.classifiers
+            .iter()
+            .find_map(|classifier| classifier.classify(message))
+    }
+
+    /// Sets the classifiers that tag messages with a category, and lists the
+    /// categories of the log's messages.
+    pub fn set_classifiers(&mut self, log: &Log, classifiers: Rc<[Box<dyn MessageClassifier>]>) {
+        self.classifiers = classifiers;
+        self.categories.clear();
+        self.add_categories(log);
+    }
+
+    /// Lists the categories of the log's messages that aren't listed yet,
+    /// keeping the list sorted by name.
+    fn add_categories(&mut self, log: &Log) {
+        if self.classifiers.is_empty() {
+            return;
+        }
+
+        for message in log.messages() {
+            if let Some(category) = self.category(message) {
+                self.categories.entry(category.name).or_insert(true);
+            }
+        }
+        self.categories.sort_keys();
+    }
+
+    /// Checks if a message is in the time range, or is an error that's shown
+    /// anyway.
+    fn in_time_range(&self, message: &Message) -> bool {
+        self.time_range
+            .is_none_or(|(start, end)| message.timestamp.is_between(start, end))
+            || (self.errors_outside_time_range && message.level.is_error())
     }
 }
 
-impl<'i, 'j> WithLog<'j> for LogFilters<'i> {
+impl<'j> WithLog<'j> for LogFilters<'_> {
     type Result = LogFilters<'j>;
 
     fn with_log(self, log: &'j Log) -> Self::Result {
-        LogFilters {
-            levels: self.levels,
+        let mut levels = self.levels;
+        for level in log.levels() {
+            levels.entry(level).or_insert(true);
+        }
+        let mut filters = LogFilters {
+            levels,
             sources: log
                 .sources()
-                .into_iter()
                 .sorted()
-                .map(|source| (source, self.sources.get(source).copied().unwrap_or(true)))
+                .map(|source| {
+                    let enabled = self.sources.get(source).copied().unwrap_or_else(|| {
+                        !self
+                            .remembered_sources
+                            .iter()
+                            .any(|hidden| hidden == source)
+                    });
+                    (source, enabled)
+                })
                 .collect(),
-        }
+            latest_launch_only: self.latest_launch_only,
+            multi_line_only: self.multi_line_only,
+            important_only: self.important_only,
+            noisy_sources: self.noisy_sources,
+            since_mark: self.since_mark,
+            hidden_sources: self.hidden_sources,
+            source_pattern: self.source_pattern,
+            exception: self.exception,
+            grouped_sources: Vec::new(),
+            remembered_sources: self.remembered_sources,
+            time_range: self.time_range,
+            errors_outside_time_range: self.errors_outside_time_range,
+            classifiers: self.classifiers,
+            categories: self.categories,
+        };
+        filters.add_categories(log);
+        filters
+    }
+}
+
+/// Checks whether some text contains a word, ignoring ASCII case. Nothing is
+/// copied, since this runs on every message whenever the filters change.
+fn mentions_ignore_case(text: &str, word: &str) -> bool {
+    let word = word.as_bytes();
+    word.is_empty()
+        || text
+            .as_bytes()
+            .windows(word.len())
+            .any(|window| window.eq_ignore_ascii_case(word))
+}
+
+/// A shell-style glob matching source names, like `SpaceCore*`. Matching
+/// ignores case.
+#[derive(Clone, Debug)]
+pub struct SourceGlob {
+    pattern: String,
+    matcher: GlobMatcher,
+}
+
+impl SourceGlob {
+    /// Compiles a glob, failing if it isn't a valid pattern.
+    pub fn new(pattern: &str) -> Result<Self, globset::Error> {
+        let matcher = GlobBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()?
+            .compile_matcher();
+        Ok(SourceGlob {
+            pattern: pattern.to_string(),
+            matcher,
+        })
+    }
+
+    /// Gets the pattern the glob was compiled from.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Checks whether a source matches the glob.
+    pub fn is_match(&self, source: &str) -> bool {
+        self.matcher.is_match(source)
     }
 }
 
@@ -403,6 +3192,9 @@ struct FiltersList<'f, 'i: 'f> {
     selected_style: Style,
     enabled_style: Style,
     more_label_style: Style,
+    highlighted_style: Style,
+    /// A source to highlight, like the source of the latest error.
+    highlighted_source: Option<&'f str>,
     filters: &'f mut LogFilters<'i>,
 }
 
@@ -413,6 +3205,8 @@ impl<'f, 'i: 'f> FiltersList<'f, 'i> {
             selected_style: Style::default(),
             enabled_style: Style::default(),
             more_label_style: Style::default(),
+            highlighted_style: Style::default(),
+            highlighted_source: None,
             filters,
         }
     }
@@ -436,6 +3230,16 @@ impl<'f, 'i: 'f> FiltersList<'f, 'i> {
         self.more_label_style = style;
         self
     }
+
+    pub fn highlighted_style(mut self, style: Style) -> Self {
+        self.highlighted_style = style;
+        self
+    }
+
+    pub fn highlighted_source(mut self, source: Option<&'f str>) -> Self {
+        self.highlighted_source = source;
+        self
+    }
 }
 
 impl<'f, 'i: 'f> StatefulWidget for FiltersList<'f, 'i> {
@@ -443,54 +3247,39 @@ impl<'f, 'i: 'f> StatefulWidget for FiltersList<'f, 'i> {
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         // Get labels for each control
-        let labels = match &state.source {
-            FiltersListSource::Levels => {
-                state.selected = state.selected.min(Level::ALL.len().saturating_sub(1));
-                Either::Left(self.filters.levels.iter().enumerate().map(
-                    |(index, (&level, &enabled))| {
-                        Span::styled(
-                            level.to_string(),
-                            if state.selected == index {
-                                self.selected_style
-                            } else if enabled {
-                                self.enabled_style
-                            } else {
-                                self.style
-                            },
-                        )
-                    },
-                ))
-            }
-            FiltersListSource::Sources => {
-                state.selected = state
-                    .selected
-                    .min(self.filters.sources.len().saturating_sub(1));
-                Either::Right(self.filters.sources.iter().enumerate().map(
-                    |(index, (&source, &enabled))| {
-                        Span::styled(
-                            source,
-                            if state.selected == index {
-                                self.selected_style
-                            } else if enabled {
-                                self.enabled_style
-                            } else {
-                                self.style
-                            },
-                        )
+        let entries = state.entries(self.filters);
+        state.selected = state.selected.min(entries.len().saturating_sub(1));
+        let labels = entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, (name, enabled))| {
+                let highlighted = state.source == FiltersListSource::Sources
+                    && self.highlighted_source == Some(name.as_ref());
+                Span::styled(
+                    name,
+                    if state.selected == index {
+                        self.selected_style
+                    } else if highlighted {
+                        self.highlighted_style
+                    } else if enabled {
+                        self.enabled_style
+                    } else {
+                        self.style
                     },
-                ))
-            }
-        };
+                )
+            });
         let more_label = Span::styled("...", self.more_label_style);
 
         // Get the available width for the controls, excluding the "More" label
+        state.click_targets.clear();
         let controls_width = usize::from(area.width).saturating_sub(more_label.content.width() * 2);
         if controls_width == 0 {
             return;
         }
 
-        // Group controls into lines
+        // Group controls into lines, along with what clicking each label does
         let mut start_line = 0_usize;
+        let mut line_starts = Vec::new();
         let lines = labels
             .enumerate()
             .map(|(index, label)| {
@@ -508,14 +3297,20 @@ impl<'f, 'i: 'f> StatefulWidget for FiltersList<'f, 'i> {
                         remaining_width = new_remaining_width.saturating_sub(1);
 
                         // Add "More" label (for previous page)
-                        if index > 0 && line.is_empty() {
-                            line.push(more_label.clone());
+                        if line.is_empty() {
+                            if let Some(&previous_start) = line_starts.last() {
+                                line.push((
+                                    more_label.clone(),
+                                    Some(FilterClickTarget::Page(previous_start)),
+                                ));
+                            }
+                            line_starts.push(index);
                         }
 
                         // Add label and padding
                         let (_, label, _) = labels.next().unwrap();
-                        line.push(label);
-                        line.push(Span::raw(" "));
+                        line.push((label, Some(FilterClickTarget::Filter(index))));
+                        line.push((Span::raw(" "), None));
                     } else {
                         // Check if empty page because area isn't big enough
                         if line.is_empty() {
@@ -527,32 +3322,149 @@ impl<'f, 'i: 'f> StatefulWidget for FiltersList<'f, 'i> {
                             start_line = start_line.saturating_add(1);
                         }
 
-                        // Add "More" label (for next page)
-                        line.push(more_label.clone());
-                        return Some(Spans::from(line));
-                    }
-                }
+                        // Add "More" label (for next page)
+                        line.push((more_label.clone(), Some(FilterClickTarget::Page(index))));
+                        return Some(line);
+                    }
+                }
+
+                if line.is_empty() {
+                    None
+                } else {
+                    Some(line)
+                }
+            })
+            .collect_vec();
+
+        // TODO: Get which rows to render
+        // let start_row = start_line * usize::from(area.height);
+        let start_row = start_line;
+        let line_after_end_row = start_row
+            .saturating_add(area.height.into())
+            .min(lines.len());
+        let start_row = line_after_end_row.saturating_sub(area.height.into());
+
+        // Render the controls, tracking where each label is so it can be
+        // clicked
+        let rendered_lines = lines.get(start_row..line_after_end_row);
+        for (line, y) in rendered_lines.into_iter().flatten().zip(area.y..) {
+            let mut x = area.x;
+            for (span, target) in line {
+                let width = area.right().saturating_sub(x);
+                let (next_x, _) = buf.set_span(x, y, span, width);
+                if let Some(target) = *target {
+                    let label_area = Rect::new(x, y, next_x - x, 1);
+                    state.click_targets.push((label_area, target));
+                }
+                x = next_x;
+            }
+        }
+    }
+}
+
+/// The filters in a column, with a checkbox next to each one.
+#[derive(Debug)]
+struct FiltersSidebar<'f, 'i: 'f> {
+    style: Style,
+    selected_style: Style,
+    highlighted_style: Style,
+    /// A source to highlight, like the source of the latest error.
+    highlighted_source: Option<&'f str>,
+    filters: &'f LogFilters<'i>,
+}
+
+impl<'f, 'i: 'f> FiltersSidebar<'f, 'i> {
+    pub fn new(filters: &'f LogFilters<'i>) -> Self {
+        Self {
+            style: Style::default(),
+            selected_style: Style::default(),
+            highlighted_style: Style::default(),
+            highlighted_source: None,
+            filters,
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn selected_style(mut self, style: Style) -> Self {
+        self.selected_style = style;
+        self
+    }
+
+    pub fn highlighted_style(mut self, style: Style) -> Self {
+        self.highlighted_style = style;
+        self
+    }
+
+    pub fn highlighted_source(mut self, source: Option<&'f str>) -> Self {
+        self.highlighted_source = source;
+        self
+    }
+
+    /// Gets the width needed to show every filter, including the borders.
+    pub fn width(filters: &LogFilters<'_>, state: &FiltersListState) -> u16 {
+        let longest = state
+            .entries(filters)
+            .iter()
+            .map(|(name, _)| name.width())
+            .max()
+            .unwrap_or_default();
+        // Checkbox, space, and borders
+        u16::try_from(longest.saturating_add(6)).unwrap_or(u16::MAX)
+    }
+}
 
-                if line.is_empty() {
-                    None
-                } else {
-                    Some(Spans::from(line))
-                }
-            })
-            .collect_vec();
+impl<'f, 'i: 'f> StatefulWidget for FiltersSidebar<'f, 'i> {
+    type State = FiltersListState;
 
-        // TODO: Get which rows to render
-        // let start_row = start_line * usize::from(area.height);
-        let start_row = start_line;
-        let line_after_end_row = start_row
-            .saturating_add(area.height.into())
-            .min(lines.len());
-        let start_row = line_after_end_row.saturating_sub(area.height.into());
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let entries = state.entries(self.filters);
+        state.selected = state.selected.min(entries.len().saturating_sub(1));
 
-        // Render the controls
-        let rendered_lines = lines.get(start_row..line_after_end_row);
-        for (y, spans) in rendered_lines.into_iter().flatten().enumerate() {
-            buf.set_spans(area.x, area.y.saturating_add(y as u16), spans, area.width);
+        // Render block
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(self.style)
+            .title(match state.source {
+                FiltersListSource::Levels => "Levels",
+                FiltersListSource::Sources => "Sources",
+                FiltersListSource::Categories => "Categories",
+            });
+        let inner_area = block.inner(area);
+        buf.set_style(area, self.style);
+        block.render(area, buf);
+
+        // Keep the selected filter visible
+        let height = usize::from(inner_area.height);
+        let start = state.selected.saturating_sub(height.saturating_sub(1));
+        let visible = entries.into_iter().enumerate().skip(start).take(height);
+        state.click_targets.clear();
+        for ((index, (name, enabled)), y) in visible.zip(inner_area.y..) {
+            let row = Rect::new(inner_area.x, y, inner_area.width, 1);
+            state
+                .click_targets
+                .push((row, FilterClickTarget::Filter(index)));
+            let style = if index == state.selected {
+                self.selected_style
+            } else if state.source == FiltersListSource::Sources
+                && self.highlighted_source == Some(name.as_ref())
+            {
+                self.highlighted_style
+            } else {
+                self.style
+            };
+            let checkbox = if enabled { "[x]" } else { "[ ]" };
+            buf.set_style(row, style);
+            buf.set_stringn(
+                inner_area.x,
+                y,
+                format!("{checkbox} {name}"),
+                inner_area.width.into(),
+                style,
+            );
         }
     }
 }
@@ -561,24 +3473,96 @@ impl<'f, 'i: 'f> StatefulWidget for FiltersList<'f, 'i> {
 struct FiltersListState {
     selected: usize,
     source: FiltersListSource,
+    /// Whether the filters are shown in a sidebar instead of a bar below the
+    /// log.
+    sidebar: bool,
+    /// Where each label was last rendered, and what clicking it does.
+    click_targets: Vec<(Rect, FilterClickTarget)>,
 }
 
 impl FiltersListState {
-    pub fn levels() -> Self {
+    pub fn levels(sidebar: bool) -> Self {
         Self {
             selected: 0,
             source: FiltersListSource::Levels,
+            sidebar,
+            click_targets: Vec::new(),
         }
     }
 
-    pub fn sources() -> Self {
+    pub fn sources(sidebar: bool) -> Self {
         Self {
             selected: 0,
             source: FiltersListSource::Sources,
+            sidebar,
+            click_targets: Vec::new(),
+        }
+    }
+
+    pub fn categories(sidebar: bool) -> Self {
+        Self {
+            selected: 0,
+            source: FiltersListSource::Categories,
+            sidebar,
+            click_targets: Vec::new(),
+        }
+    }
+
+    /// Gets what is under a position in the rendered list, if anything.
+    fn click_target_at(&self, column: u16, row: u16) -> Option<FilterClickTarget> {
+        self.click_targets
+            .iter()
+            .find(|(area, _)| {
+                (area.left()..area.right()).contains(&column)
+                    && (area.top()..area.bottom()).contains(&row)
+            })
+            .map(|&(_, target)| target)
+    }
+
+    /// Gets the name of each filter in the list and whether it's enabled.
+    fn entries<'i>(&self, filters: &LogFilters<'i>) -> Vec<(Cow<'i, str>, bool)> {
+        match self.source {
+            FiltersListSource::Levels => filters
+                .levels
+                .iter()
+                .map(|(level, &enabled)| (level.to_string().into(), enabled))
+                .collect(),
+            FiltersListSource::Sources => {
+                let mut entries: Vec<_> = filters
+                    .sources
+                    .keys()
+                    .filter(|source| !filters.grouped_sources.contains(source))
+                    .map(|&source| (source.into(), filters.source_enabled(source)))
+                    .collect();
+                if !filters.grouped_sources.is_empty() {
+                    entries.push(("(other)".into(), filters.grouped_sources_enabled()));
+                }
+                entries
+            }
+            FiltersListSource::Categories => filters
+                .categories
+                .iter()
+                .map(|(name, &enabled)| (name.clone().into(), enabled))
+                .collect(),
         }
     }
 
-    pub fn toggle<'i>(&self, filters: &mut LogFilters<'i>) {
+    /// Gets the selected source while the sources are listed. The grouped
+    /// sources aren't a single source, so they aren't returned.
+    fn selected_source<'i>(&self, filters: &LogFilters<'i>) -> Option<&'i str> {
+        if self.source != FiltersListSource::Sources {
+            return None;
+        }
+
+        filters
+            .sources
+            .keys()
+            .filter(|source| !filters.grouped_sources.contains(source))
+            .nth(self.selected)
+            .copied()
+    }
+
+    pub fn toggle(&self, filters: &mut LogFilters<'_>) {
         match &self.source {
             FiltersListSource::Levels => {
                 if let Some((_, enabled)) = filters.levels.get_index_mut(self.selected) {
@@ -586,7 +3570,20 @@ impl FiltersListState {
                 }
             }
             FiltersListSource::Sources => {
-                if let Some((_, enabled)) = filters.sources.get_index_mut(self.selected) {
+                if let Some(source) = self.selected_source(filters) {
+                    if let Some(enabled) = filters.sources.get_mut(source) {
+                        *enabled = !*enabled;
+                    }
+                } else {
+                    // The grouped sources are toggled together
+                    let enabled = !filters.grouped_sources_enabled();
+                    for source in &filters.grouped_sources {
+                        filters.sources.insert(source, enabled);
+                    }
+                }
+            }
+            FiltersListSource::Categories => {
+                if let Some((_, enabled)) = filters.categories.get_index_mut(self.selected) {
                     *enabled = !*enabled;
                 }
             }
@@ -598,11 +3595,19 @@ impl State for FiltersListState {
     fn update(&mut self, event: &AppEvent) -> bool {
         match event {
             AppEvent::TermEvent(Event::Key(key_event)) => match key_event.code {
-                KeyCode::Left => {
+                KeyCode::Left if !self.sidebar => {
+                    self.selected = self.selected.saturating_sub(1);
+                    true
+                }
+                KeyCode::Right if !self.sidebar => {
+                    self.selected = self.selected.saturating_add(1);
+                    true
+                }
+                KeyCode::Up if self.sidebar => {
                     self.selected = self.selected.saturating_sub(1);
                     true
                 }
-                KeyCode::Right => {
+                KeyCode::Down if self.sidebar => {
                     self.selected = self.selected.saturating_add(1);
                     true
                 }
@@ -613,7 +3618,11 @@ impl State for FiltersListState {
     }
 
     fn add_controls<I: IconPack>(&self, controls: &mut IndexMap<BindingDisplay<I>, &'static str>) {
-        controls.insert(BindingDisplay::Custom(I::LEFT_RIGHT), "Nav");
+        if self.sidebar {
+            controls.insert(BindingDisplay::Custom(I::UP_DOWN), "Nav");
+        } else {
+            controls.insert(BindingDisplay::Custom(I::LEFT_RIGHT), "Nav");
+        }
     }
 }
 
@@ -625,17 +3634,1216 @@ impl<'j> WithLog<'j> for FiltersListState {
             FiltersListSource::Levels => FiltersListState {
                 selected: self.selected,
                 source: FiltersListSource::Levels,
+                sidebar: self.sidebar,
+                click_targets: self.click_targets,
             },
             FiltersListSource::Sources => FiltersListState {
                 selected: self.selected,
                 source: FiltersListSource::Sources,
+                sidebar: self.sidebar,
+                click_targets: self.click_targets,
+            },
+            FiltersListSource::Categories => FiltersListState {
+                selected: self.selected,
+                source: FiltersListSource::Categories,
+                sidebar: self.sidebar,
+                click_targets: self.click_targets,
             },
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
 enum FiltersListSource {
     Levels,
     Sources,
+    Categories,
+}
+
+/// What clicking a label in the filters list does.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+enum FilterClickTarget {
+    /// Selects and toggles the filter with this index.
+    Filter(usize),
+    /// Shows the page starting with the filter with this index.
+    Page(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        classify::PatternClassifier,
+        widgets::test_utils::{key, render_stateful, rows},
+    };
+
+    const LOG: &str = "\
+[10:00:00 INFO  SMAPI] Starting
+[10:00:01 TRACE SMAPI] Loading mods
+[10:00:02 DEBUG Content Patcher] Applying patches
+[10:00:03 WARN  Json Assets] Missing item
+[10:00:04 ERROR Json Assets] This mod failed
+System.NullReferenceException
+[10:00:05 INFO  Content Patcher] Done
+";
+
+    fn all_enabled(log: &Log) -> LogFilters<'_> {
+        LogFilters {
+            levels: Level::ALL.into_iter().map(|level| (level, true)).collect(),
+            sources: log.sources().map(|source| (source, true)).collect(),
+            latest_launch_only: false,
+            multi_line_only: false,
+            important_only: false,
+            noisy_sources: vec!["Content Patcher".to_string()],
+            since_mark: None,
+            hidden_sources: None,
+            source_pattern: None,
+            exception: None,
+            grouped_sources: Vec::new(),
+            remembered_sources: Vec::new(),
+            time_range: None,
+            errors_outside_time_range: false,
+            classifiers: Rc::default(),
+            categories: IndexMap::new(),
+        }
+    }
+
+    fn first_lines<'i>(filters: &LogFilters<'i>, log: &'i Log) -> Vec<&'i str> {
+        filters
+            .clone()
+            .apply(log)
+            .into_iter()
+            .map(|message| message.contents.lines().next().unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn shows_everything_when_all_filters_are_enabled() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let filters = all_enabled(&log);
+        assert_eq!(
+            first_lines(&filters, &log),
+            [
+                "Starting",
+                "Loading mods",
+                "Applying patches",
+                "Missing item",
+                "This mod failed",
+                "Done",
+            ]
+        );
+    }
+
+    #[test]
+    fn hides_disabled_levels() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut filters = all_enabled(&log);
+        filters.levels.insert(Level::Info, false);
+        filters.levels.insert(Level::Trace, false);
+        assert!(!filters.level_enabled(Level::Info));
+        assert!(filters.level_enabled(Level::Error));
+        assert_eq!(
+            first_lines(&filters, &log),
+            ["Applying patches", "Missing item", "This mod failed"]
+        );
+    }
+
+    #[test]
+    fn hides_disabled_sources() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut filters = all_enabled(&log);
+        filters.sources.insert("Json Assets", false);
+        assert!(!filters.source_enabled("Json Assets"));
+        assert!(filters.source_enabled("SMAPI"));
+        assert_eq!(
+            first_lines(&filters, &log),
+            ["Starting", "Loading mods", "Applying patches", "Done"]
+        );
+    }
+
+    #[test]
+    fn hides_sources_matching_glob() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut filters = all_enabled(&log);
+        filters.hidden_sources = Some(SourceGlob::new("json*").unwrap());
+        assert!(!filters.source_enabled("Json Assets"));
+        assert!(filters.source_enabled("SMAPI"));
+        assert_eq!(
+            first_lines(&filters, &log),
+            ["Starting", "Loading mods", "Applying patches", "Done"]
+        );
+        assert!(SourceGlob::new("[json").is_err());
+    }
+
+    #[test]
+    fn shows_only_sources_matching_regex() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut filters = all_enabled(&log);
+        filters.levels.insert(Level::Info, false);
+        filters.source_pattern = Some(Regex::new("Json|Content").unwrap());
+        assert!(filters.source_enabled("Json Assets"));
+        assert!(!filters.source_enabled("SMAPI"));
+        assert_eq!(
+            first_lines(&filters, &log),
+            ["Applying patches", "Missing item", "This mod failed"]
+        );
+
+        // Sources toggled off stay hidden
+        filters.sources.insert("Json Assets", false);
+        assert_eq!(first_lines(&filters, &log), ["Applying patches"]);
+    }
+
+    #[test]
+    fn restores_saved_filters() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let saved = SavedFilters {
+            levels: vec![Level::Info, Level::Warn, Level::Error],
+            disabled_sources: vec!["Json Assets".to_string(), "SpaceCore".to_string()],
+        };
+        let mut filters = all_enabled(&log);
+        filters.restore(&saved);
+        assert_eq!(first_lines(&filters, &log), ["Starting", "Done"]);
+        assert_eq!(filters.to_saved(), saved);
+
+        // Hidden sources that weren't in the log stay hidden when they show up
+        let new_log = Log::parse(format!("{LOG}[10:00:06 INFO  SpaceCore] Loaded\n")).unwrap();
+        let filters = filters.with_log(&new_log);
+        assert!(!filters.source_enabled("SpaceCore"));
+        assert_eq!(first_lines(&filters, &new_log), ["Starting", "Done"]);
+        assert_eq!(filters.to_saved(), saved);
+    }
+
+    #[test]
+    fn filters_unknown_levels() {
+        let log = Log::parse(format!("{LOG}[10:00:06 FATAL SMAPI] Crashed\n")).unwrap();
+        let mut state = FormattedLogState::new(&log);
+        state.update(&key(KeyCode::Char('f')));
+        let widget = FormattedLog::default();
+        let buffer = render_stateful(widget, &mut state, 60, 8);
+        assert_eq!(rows(&buffer)[7], "TRACE DEBUG INFO ALERT WARN ERROR FATAL");
+
+        // Unknown levels can be hidden like any other level
+        for _ in 0..6 {
+            state.update(&key(KeyCode::Right));
+        }
+        state.update(&key(KeyCode::Char(' ')));
+        let widget = FormattedLog::default();
+        let buffer = render_stateful(widget, &mut state, 60, 8);
+        assert!(rows(&buffer)[6].ends_with("Done"));
+
+        // Levels that show up later are added to the filters
+        let filters = all_enabled(&log).with_log(&log);
+        assert!(filters
+            .levels
+            .keys()
+            .any(|level| level.to_string() == "FATAL"));
+    }
+
+    #[test]
+    fn enables_unknown_levels_and_sources() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut filters = all_enabled(&log);
+        filters.levels.clear();
+        assert!(filters.level_enabled(Level::Alert));
+        assert!(filters.source_enabled("Unknown Mod"));
+    }
+
+    #[test]
+    fn combines_level_and_source_filters() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut filters = all_enabled(&log);
+        filters.levels.insert(Level::Debug, false);
+        filters.sources.insert("SMAPI", false);
+        assert_eq!(
+            first_lines(&filters, &log),
+            ["Missing item", "This mod failed", "Done"]
+        );
+    }
+
+    #[test]
+    fn shows_only_multi_line_messages() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut filters = all_enabled(&log);
+        filters.multi_line_only = true;
+        assert_eq!(first_lines(&filters, &log), ["This mod failed"]);
+    }
+
+    #[test]
+    fn hides_noisy_sources_when_important_only() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut filters = all_enabled(&log);
+        filters.important_only = true;
+        assert_eq!(
+            first_lines(&filters, &log),
+            ["Starting", "Missing item", "This mod failed"]
+        );
+    }
+
+    #[test]
+    fn hides_messages_before_mark() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut filters = all_enabled(&log);
+        filters.since_mark = Some(3);
+        filters.levels.insert(Level::Warn, false);
+        assert_eq!(first_lines(&filters, &log), ["This mod failed", "Done"]);
+    }
+
+    #[test]
+    fn shows_only_messages_mentioning_exception() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut filters = all_enabled(&log);
+        filters.exception = Some("nullreferenceexception".to_string());
+        assert_eq!(first_lines(&filters, &log), ["This mod failed"]);
+        assert!(mentions_ignore_case(
+            "Café: System.IOException",
+            "ioEXCEPTION"
+        ));
+        assert!(!mentions_ignore_case("IOException", "IOExceptions"));
+
+        // Combines with the other filters, and is kept when reloading
+        let filters = filters.with_log(&log);
+        let mut without_errors = filters.clone();
+        without_errors.levels.insert(Level::Error, false);
+        assert!(first_lines(&without_errors, &log).is_empty());
+        assert_eq!(first_lines(&filters, &log), ["This mod failed"]);
+    }
+
+    #[test]
+    fn keeps_source_filters_when_reloading() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut filters = all_enabled(&log);
+        filters.levels.insert(Level::Trace, false);
+        filters.sources.insert("Json Assets", false);
+
+        // The reloaded log has a new source, and no longer has Content Patcher
+        let reloaded = Log::parse(format!(
+            "{}[10:00:06 ALERT SpaceCore] New source\n",
+            LOG.replace("Content Patcher", "SMAPI")
+        ))
+        .unwrap();
+        let filters = filters.with_log(&reloaded);
+        assert_eq!(
+            filters.sources.keys().copied().collect::<Vec<_>>(),
+            ["Json Assets", "SMAPI", "SpaceCore"]
+        );
+        assert!(!filters.source_enabled("Json Assets"));
+        assert!(filters.source_enabled("SpaceCore"));
+        assert!(!filters.level_enabled(Level::Trace));
+        assert_eq!(
+            first_lines(&filters, &reloaded),
+            ["Starting", "Applying patches", "Done", "New source"]
+        );
+    }
+
+    #[test]
+    fn renders_aligned_columns() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log).follow(true);
+        let widget = FormattedLog::default().show_colors(true);
+        let buffer = render_stateful(widget, &mut state, 60, 8);
+        assert_eq!(
+            rows(&buffer),
+            [
+                "10:00:00 INFO  SMAPI           Starting",
+                "10:00:01 TRACE SMAPI           Loading mods",
+                "10:00:02 DEBUG Content Patcher Applying patches",
+                "10:00:03 WARN  Json Assets     Missing item",
+                "10:00:04 ERROR Json Assets     This mod failed",
+                "...      ...   ...             System.NullReferenceException",
+                "10:00:05 INFO  Content Patcher Done",
+                "",
+            ]
+        );
+
+        // Levels are colored by the theme
+        let theme = Theme::default();
+        assert_eq!(buffer.get(9, 3).fg, theme.level_colors.warn);
+        assert_eq!(buffer.get(9, 4).fg, theme.level_colors.error);
+        assert_eq!(buffer.get(0, 5).fg, Color::DarkGray);
+    }
+
+    #[test]
+    fn aligns_wide_sources() {
+        let log = Log::parse(
+            "[10:00:00 INFO  SMAPI] Starting\n[10:00:01 INFO  星露谷助手] 你好\n".to_string(),
+        )
+        .unwrap();
+        let mut state = FormattedLogState::new(&log);
+        let buffer = render_stateful(FormattedLog::default(), &mut state, 40, 2);
+
+        // Wide characters take up two cells, the second of which is blank
+        assert_eq!(
+            rows(&buffer),
+            [
+                "10:00:00 INFO  SMAPI      Starting",
+                "10:00:01 INFO  星 露 谷 助 手  你 好",
+            ]
+        );
+        assert_eq!(buffer.get(26, 0).symbol, "S");
+        assert_eq!(buffer.get(26, 1).symbol, "你");
+
+        // Wide characters that don't fit before the ellipsis are left out
+        let widget = FormattedLog::default().max_source_width(Some(6));
+        let buffer = render_stateful(widget, &mut state, 40, 2);
+        assert_eq!(
+            rows(&buffer),
+            [
+                "10:00:00 INFO  SMAPI  Starting",
+                "10:00:01 INFO  星 露 …  你 好"
+            ]
+        );
+        assert_eq!(buffer.get(22, 1).symbol, "你");
+    }
+
+    #[test]
+    fn shows_dates_when_they_change() {
+        let log = Log::parse(
+            "\
+[2024-03-01 23:59:58 INFO  SMAPI] Before midnight
+[2024-03-01 23:59:59 INFO  SMAPI] Still before
+[2024-03-02 00:00:01 INFO  SMAPI] After midnight
+"
+            .to_string(),
+        )
+        .unwrap();
+        let mut state = FormattedLogState::new(&log);
+        let buffer = render_stateful(FormattedLog::default(), &mut state, 60, 3);
+        assert_eq!(
+            rows(&buffer),
+            [
+                "2024-03-01 23:59:58 INFO  SMAPI Before midnight",
+                "           23:59:59 INFO  SMAPI Still before",
+                "2024-03-02 00:00:01 INFO  SMAPI After midnight",
+            ]
+        );
+
+        // Logs without dates don't leave room for them
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log);
+        let buffer = render_stateful(FormattedLog::default(), &mut state, 60, 1);
+        assert!(rows(&buffer)[0].starts_with("10:00:00 INFO  SMAPI           Starting "));
+    }
+
+    #[test]
+    fn truncates_long_lines() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log);
+        let widget = FormattedLog::default()
+            .show_colors(true)
+            .max_message_width(Some(8));
+        let buffer = render_stateful(widget.clone(), &mut state, 60, 7);
+        assert_eq!(
+            rows(&buffer),
+            [
+                "10:00:00 INFO  SMAPI           Starting",
+                "10:00:01 TRACE SMAPI           Loading…",
+                "10:00:02 DEBUG Content Patcher Applyin…",
+                "10:00:03 WARN  Json Assets     Missing…",
+                "10:00:04 ERROR Json Assets     This mo…",
+                "...      ...   ...             System.…",
+                "10:00:05 INFO  Content Patcher Done",
+            ]
+        );
+        assert_eq!(buffer.get(38, 1).fg, Color::DarkGray);
+
+        // The message at the top is shown in full
+        state.update(&key(KeyCode::Down));
+        let buffer = render_stateful(widget, &mut state, 60, 6);
+        let rows = rows(&buffer);
+        assert!(rows[0].starts_with("10:00:01 TRACE SMAPI           Loading mods "));
+        assert!(rows[1].starts_with("10:00:02 DEBUG Content Patcher Applyin… "));
+    }
+
+    #[test]
+    fn renders_custom_column_layout() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log).follow(true);
+        let widget = FormattedLog::default()
+            .columns(ColumnLayout(vec![Column::Source, Column::Level]))
+            .max_source_width(Some(8));
+        let buffer = render_stateful(widget, &mut state, 60, 8);
+        assert_eq!(
+            &rows(&buffer)[2..6],
+            [
+                "Content… DEBUG Applying patches",
+                "Json As… WARN  Missing item",
+                "Json As… ERROR This mod failed",
+                "...      ...   System.NullReferenceException",
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_elapsed_column() {
+        let log = Log::parse(
+            "[10:00:00 INFO  SMAPI] Starting\n\
+             [10:00:00 INFO  SMAPI] Loading\n\
+             [10:01:05 TRACE SMAPI] Hidden\n\
+             [10:01:05 INFO  SMAPI] Loaded\n"
+                .to_string(),
+        )
+        .unwrap();
+        let mut state = FormattedLogState::new(&log);
+        state.update(&key(KeyCode::Char('+')));
+        state.filters.levels.insert(Level::Trace, false);
+        state.apply_filter();
+
+        // The gap skips hidden messages, and is blank if no time passed
+        let widget = FormattedLog::default().columns(ColumnLayout(vec![Column::Elapsed]));
+        let buffer = render_stateful(widget, &mut state, 30, 3);
+        assert_eq!(
+            rows(&buffer),
+            ["         Starting", "         Loading", " +1m 05s Loaded"]
+        );
+
+        // It's added after the timestamp when toggled on
+        let columns = ColumnLayout::default().with_elapsed(true);
+        assert_eq!(
+            *columns,
+            [
+                Column::Timestamp,
+                Column::Elapsed,
+                Column::Level,
+                Column::Source
+            ]
+        );
+        assert_eq!(columns.with_elapsed(false), ColumnLayout::default());
+    }
+
+    #[test]
+    fn removes_duplicate_columns() {
+        let mut columns = ColumnLayout(vec![Column::Source, Column::Level, Column::Source]);
+        assert!(columns.remove_duplicates());
+        assert_eq!(*columns, [Column::Source, Column::Level]);
+        assert!(!columns.remove_duplicates());
+    }
+
+    #[test]
+    fn renders_filters_list_below_log() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log).follow(true);
+        state.update(&key(KeyCode::Char('f')));
+        state.update(&key(KeyCode::Char(' ')));
+
+        // Trace messages are hidden
+        let widget = FormattedLog::default().show_colors(true);
+        let buffer = render_stateful(widget, &mut state, 60, 8);
+        let rows = rows(&buffer);
+        assert_eq!(rows[1], "10:00:02 DEBUG Content Patcher Applying patches");
+        assert_eq!(rows[7], "TRACE DEBUG INFO ALERT WARN ERROR");
+
+        // The selected filter is highlighted, and enabled ones are green
+        assert_eq!(buffer.get(0, 7).bg, Theme::default().selection_color);
+        assert_eq!(buffer.get(6, 7).bg, Color::LightGreen);
+    }
+
+    #[test]
+    fn renders_filters_list_with_theme_colors() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log).follow(true);
+        state.update(&key(KeyCode::Char('f')));
+        state.update(&key(KeyCode::Char(' ')));
+
+        let theme = Theme {
+            filter_enabled_color: Color::Green,
+            filter_disabled_color: Color::Gray,
+            ..Theme::default()
+        };
+        let widget = FormattedLog::default().show_colors(true).theme(theme);
+        let buffer = render_stateful(widget, &mut state, 60, 8);
+        assert_eq!(buffer.get(6, 7).bg, Color::Green);
+
+        // Move the selection off the disabled filter
+        state.update(&key(KeyCode::Right));
+        let theme = Theme {
+            filter_enabled_color: Color::Green,
+            filter_disabled_color: Color::Gray,
+            ..Theme::default()
+        };
+        let widget = FormattedLog::default().show_colors(true).theme(theme);
+        let buffer = render_stateful(widget, &mut state, 60, 8);
+        assert_eq!(buffer.get(0, 7).bg, Color::Gray);
+    }
+
+    #[test]
+    fn toggles_filters_by_clicking() {
+        fn click(column: u16, row: u16) -> AppEvent {
+            AppEvent::TermEvent(Event::Mouse(crossterm::event::MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column,
+                row,
+                modifiers: crossterm::event::KeyModifiers::NONE,
+            }))
+        }
+
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log).follow(true);
+        state.update(&key(KeyCode::Char('f')));
+        let buffer = render_stateful(FormattedLog::default(), &mut state, 30, 8);
+        assert_eq!(rows(&buffer)[7], "TRACE DEBUG INFO ALERT ...");
+
+        // Clicking a level selects and toggles it
+        assert!(state.update(&click(7, 7)));
+        assert!(!state.filters.level_enabled(Level::Debug));
+        assert!(!state.update(&click(5, 7)));
+
+        // Clicking "..." shows the next page, then the previous one
+        assert!(state.update(&click(24, 7)));
+        let buffer = render_stateful(FormattedLog::default(), &mut state, 30, 8);
+        assert_eq!(rows(&buffer)[7], "...WARN ERROR");
+        assert!(state.update(&click(1, 7)));
+        let buffer = render_stateful(FormattedLog::default(), &mut state, 30, 8);
+        assert_eq!(rows(&buffer)[7], "TRACE DEBUG INFO ALERT ...");
+    }
+
+    #[test]
+    fn renders_filters_sidebar() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log).follow(true);
+        state.update(&key(KeyCode::Char('f')));
+        state.update(&key(KeyCode::Char('s')));
+        state.update(&key(KeyCode::Down));
+        state.update(&key(KeyCode::Char(' ')));
+
+        // Debug messages are hidden, and the log is moved to the right
+        let widget = FormattedLog::default();
+        let buffer = render_stateful(widget, &mut state, 60, 8);
+        let levels = rows(&buffer);
+        assert_eq!(
+            levels[0],
+            "┌Levels───┐10:00:00 INFO  SMAPI           Starting"
+        );
+        assert_eq!(
+            levels[2],
+            "│[ ] DEBUG│10:00:03 WARN  Json Assets     Missing item"
+        );
+        assert_eq!(levels[7], "└─────────┘");
+
+        // The sources are listed the same way
+        state.update(&key(KeyCode::Char('f')));
+        let buffer = render_stateful(FormattedLog::default(), &mut state, 60, 8);
+        let sources = rows(&buffer);
+        assert!(sources[0].starts_with("┌Sources────────────┐"));
+        assert!(sources[1].starts_with("│[x] Content Patcher│"));
+    }
+
+    #[test]
+    fn counts_matches_hidden_by_filters() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log).follow(true);
+        state.filters.levels.insert(Level::Trace, false);
+        state.apply_filter();
+
+        let search = |state: &mut FormattedLogState, text: &str| {
+            state.update(&key(KeyCode::Char('/')));
+            for c in text.chars() {
+                state.update(&key(KeyCode::Char(c)));
+            }
+            state.update(&key(KeyCode::Enter));
+        };
+        search(&mut state, "loading");
+        assert_eq!(
+            state.notice.as_deref(),
+            Some("0 visible matches (1 hidden by filters)")
+        );
+        search(&mut state, "nothing");
+        assert_eq!(state.notice.as_deref(), Some("No matches"));
+
+        // Matches in collapsed lines aren't hidden by the filters
+        state.update(&key(KeyCode::Char('z')));
+        search(&mut state, "system.null");
+        assert_eq!(
+            state.notice.as_deref(),
+            Some("0 visible matches (1 in collapsed messages)")
+        );
+    }
+
+    #[test]
+    fn recalls_previous_searches() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log);
+        for text in ["mod", "patches"] {
+            state.update(&key(KeyCode::Char('/')));
+            for c in text.chars() {
+                state.update(&key(KeyCode::Char(c)));
+            }
+            state.update(&key(KeyCode::Enter));
+        }
+
+        state.update(&key(KeyCode::Char('/')));
+        state.update(&key(KeyCode::Up));
+        assert_eq!(state.search_input_state.text(), "patches");
+        state.update(&key(KeyCode::Up));
+        assert_eq!(state.search_input_state.text(), "mod");
+        state.update(&key(KeyCode::Down));
+        state.update(&key(KeyCode::Down));
+        assert_eq!(state.search_input_state.text(), "");
+
+        // Cancelling the prompt doesn't leave a previous search in it
+        state.update(&key(KeyCode::Up));
+        state.update(&key(KeyCode::Esc));
+        state.update(&key(KeyCode::Char('/')));
+        assert_eq!(state.search_input_state.text(), "");
+    }
+
+    #[test]
+    fn highlights_search_matches() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log);
+        state.search = Some("MOD".to_string());
+        let widget = FormattedLog::default().show_colors(true);
+        let buffer = render_stateful(widget, &mut state, 60, 7);
+        let row = &rows(&buffer)[4];
+        let start = row.find("mod").unwrap() as u16;
+        let highlighted: Vec<_> = (start - 1..start + 4)
+            .map(|x| buffer.get(x, 4).bg == Color::Yellow)
+            .collect();
+        assert_eq!(highlighted, [false, true, true, true, false]);
+    }
+
+    #[test]
+    fn expands_message_at_top_while_collapsed() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log).auto_expand(true);
+        state.update(&key(KeyCode::Char('z')));
+        assert_eq!(state.lines.len(), 6);
+
+        // Scrolling to the message with several lines expands it
+        for _ in 0..4 {
+            state.update(&key(KeyCode::Down));
+        }
+        assert_eq!(state.lines.len(), 7);
+        assert_eq!(state.lines[5].line(), "System.NullReferenceException");
+
+        // Scrolling past it collapses it again, keeping the next message at
+        // the top
+        state.update(&key(KeyCode::Down));
+        state.update(&key(KeyCode::Down));
+        assert_eq!(state.lines.len(), 6);
+        assert_eq!(state.current_message().unwrap().contents, "Done");
+
+        // Turning it off collapses the message at the top
+        state.update(&key(KeyCode::Up));
+        assert_eq!(state.lines.len(), 7);
+        state.update(&key(KeyCode::Char('x')));
+        assert_eq!(state.lines.len(), 6);
+        assert_eq!(
+            state.current_message().unwrap().contents,
+            "This mod failed\nSystem.NullReferenceException"
+        );
+    }
+
+    #[test]
+    fn keeps_the_current_message_when_filtering() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log);
+        let current =
+            |state: &FormattedLogState| state.current_message().unwrap().contents.to_string();
+        state.scroll_to_message(3);
+        assert_eq!(current(&state), "Missing item");
+
+        // Hiding messages above the view doesn't move it
+        state.filters.levels.insert(Level::Debug, false);
+        state.apply_filter();
+        assert_eq!(current(&state), "Missing item");
+
+        // Hiding the current message moves to the next one that's shown
+        state.filters.sources.insert("Json Assets", false);
+        state.apply_filter();
+        assert_eq!(current(&state), "Done");
+        state.filters.sources.insert("Json Assets", true);
+        state.apply_filter();
+        assert_eq!(current(&state), "Done");
+    }
+
+    #[test]
+    fn toggles_trace_and_debug_messages() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log);
+        state.filters.levels.insert(Level::Trace, false);
+        state.apply_filter();
+        assert_eq!(state.lines.len(), 6);
+
+        // Hides both levels
+        state.update(&key(KeyCode::Char('d')));
+        assert!(!state.filters.level_enabled(Level::Trace));
+        assert!(!state.filters.level_enabled(Level::Debug));
+        assert_eq!(state.lines.len(), 5);
+
+        // Restores the levels the way they were
+        state.update(&key(KeyCode::Char('d')));
+        assert!(!state.filters.level_enabled(Level::Trace));
+        assert!(state.filters.level_enabled(Level::Debug));
+        assert_eq!(state.lines.len(), 6);
+
+        // Shows both levels if they were hidden some other way
+        state.filters.levels.insert(Level::Debug, false);
+        state.update(&key(KeyCode::Char('d')));
+        assert!(state.filters.level_enabled(Level::Trace));
+        assert!(state.filters.level_enabled(Level::Debug));
+        assert_eq!(state.lines.len(), 7);
+    }
+
+    #[test]
+    fn suggests_hiding_noisy_sources() {
+        let mut raw = LOG.to_string();
+        for _ in 0..30 {
+            raw.push_str("[10:00:06 TRACE Farm Type Manager] Spawning forage\n");
+        }
+        for _ in 0..20 {
+            raw.push_str("[10:00:07 DEBUG SpaceCore] Patching\n");
+            raw.push_str("[10:00:07 TRACE SMAPI] Content Patcher edited Maps/Farm\n");
+        }
+        raw.push_str("[10:00:08 ERROR SpaceCore] Patch failed\n");
+        let log = Log::parse(raw).unwrap();
+        let mut state = FormattedLogState::new(&log);
+        assert_eq!(state.noisy_sources, ["Farm Type Manager", "SpaceCore"]);
+
+        // Hiding debug messages leaves too few to be noisy
+        state.update(&key(KeyCode::Char('d')));
+        assert!(state.noisy_sources.is_empty());
+        state.update(&key(KeyCode::Char('d')));
+
+        // Accepting the suggestion hides the sources
+        state.update(&key(KeyCode::Char('H')));
+        assert!(!state.filters.source_enabled("Farm Type Manager"));
+        assert!(!state.filters.source_enabled("SpaceCore"));
+        assert!(state.noisy_sources.is_empty());
+        assert_eq!(state.lines.len(), 27);
+        assert_eq!(state.notice.as_deref(), Some("Hid 2 noisy sources"));
+    }
+
+    #[test]
+    fn filters_sources_with_regex_prompt() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log);
+        state.update(&key(KeyCode::Char('f')));
+        state.update(&key(KeyCode::Char('f')));
+        state.update(&key(KeyCode::Char('r')));
+        for c in "^(SMAPI".chars() {
+            state.update(&key(KeyCode::Char(c)));
+        }
+
+        // Invalid regexes are shown in red and keep the prompt open
+        let widget = FormattedLog::default().show_colors(true);
+        let buffer = render_stateful(widget.clone(), &mut state, 60, 7);
+        assert_eq!(buffer.get(0, 6).fg, Color::Red);
+        state.update(&key(KeyCode::Enter));
+        assert!(state.regex_input_state.is_some());
+        assert_eq!(state.notice.as_deref(), Some("Invalid regex"));
+
+        state.update(&key(KeyCode::Char(')')));
+        let buffer = render_stateful(widget, &mut state, 60, 7);
+        assert_ne!(buffer.get(0, 6).fg, Color::Red);
+        state.update(&key(KeyCode::Enter));
+        assert!(state.regex_input_state.is_none());
+        assert_eq!(state.lines.len(), 2);
+
+        // Clearing the pattern shows every source again
+        state.update(&key(KeyCode::Char('r')));
+        for _ in 0.."^(SMAPI)".len() {
+            state.update(&key(KeyCode::Backspace));
+        }
+        state.update(&key(KeyCode::Enter));
+        assert!(state.filters.source_pattern.is_none());
+        assert_eq!(state.lines.len(), 7);
+    }
+
+    #[test]
+    fn filters_by_category() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let classifier = |name: &str, source: Option<&str>, contains: Option<&str>| {
+            Box::new(PatternClassifier {
+                category: Category {
+                    name: name.to_string(),
+                    color: Color::Magenta,
+                },
+                source: source.map(String::from),
+                contains: contains.map(String::from),
+            }) as Box<dyn MessageClassifier>
+        };
+        let classifiers = vec![
+            classifier("Patches", Some("Content Patcher"), None),
+            classifier("Failures", None, Some("failed")),
+        ];
+        let mut state = FormattedLogState::new(&log).classifiers(classifiers.into());
+        assert_eq!(
+            state.filters.categories.keys().collect::<Vec<_>>(),
+            ["Failures", "Patches"]
+        );
+
+        // The categories are listed after the sources
+        for _ in 0..3 {
+            state.update(&key(KeyCode::Char('f')));
+        }
+        state.update(&key(KeyCode::Char('s')));
+        let buffer = render_stateful(FormattedLog::default(), &mut state, 60, 8);
+        let sidebar = rows(&buffer);
+        assert!(sidebar[0].starts_with("┌Categories──┐"));
+        assert!(sidebar[1].starts_with("│[x] Failures│"));
+
+        // Hiding a category hides its messages, but not uncategorized ones
+        state.update(&key(KeyCode::Char(' ')));
+        assert_eq!(state.lines.len(), 5);
+        state.update(&key(KeyCode::Down));
+        state.update(&key(KeyCode::Char(' ')));
+        assert_eq!(state.lines.len(), 3);
+
+        // The list goes back to the levels after the categories
+        state.update(&key(KeyCode::Char('f')));
+        let source = state.filters_list_state.as_ref().map(|state| state.source);
+        assert_eq!(source, Some(FiltersListSource::Levels));
+    }
+
+    #[test]
+    fn filters_by_time_range() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut filters = all_enabled(&log);
+        filters.time_range = Some(("10:00:01".parse().unwrap(), "10:00:03".parse().unwrap()));
+        assert_eq!(
+            first_lines(&filters, &log),
+            ["Loading mods", "Applying patches", "Missing item"]
+        );
+
+        // Ranges that end before they start wrap past midnight
+        filters.time_range = Some(("10:00:05".parse().unwrap(), "10:00:00".parse().unwrap()));
+        assert_eq!(first_lines(&filters, &log), ["Starting", "Done"]);
+
+        // Errors outside the range can still be shown
+        filters.time_range = Some(("10:00:05".parse().unwrap(), "10:00:05".parse().unwrap()));
+        filters.errors_outside_time_range = true;
+        assert_eq!(first_lines(&filters, &log), ["This mod failed", "Done"]);
+    }
+
+    #[test]
+    fn wraps_long_lines() {
+        let log = Log::parse(format!(
+            "{LOG}[10:00:06 INFO  SMAPI] {}\n",
+            "word ".repeat(20).trim_end()
+        ))
+        .unwrap();
+        let mut state = FormattedLogState::new(&log).follow(true);
+        state.update(&key(KeyCode::Char('w')));
+        state.update(&key(KeyCode::End));
+        let buffer = render_stateful(FormattedLog::default(), &mut state, 60, 5);
+        assert_eq!(
+            rows(&buffer)[..4],
+            [
+                "10:00:05 INFO  Content Patcher Done                        █",
+                "10:00:06 INFO  SMAPI           word word word word word wor█",
+                "d word word word word word word word word word word word wo█",
+                "rd word word                                               █",
+            ]
+        );
+
+        // Only the first row of a wrapped line is part of the message columns
+        assert_eq!(state.paragraph_state.line_at_row(1), Some((7, 0)));
+        assert_eq!(state.paragraph_state.line_at_row(2), Some((7, 1)));
+
+        state.update(&key(KeyCode::Char('w')));
+        let buffer = render_stateful(FormattedLog::default(), &mut state, 60, 5);
+        assert_eq!(
+            rows(&buffer)[4],
+            "10:00:06 INFO  SMAPI           word word word word word wor█"
+        );
+    }
+
+    #[test]
+    fn copies_joined_lines() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log).copy_format(CopyFormat::Full);
+        state.scroll_to_first_error();
+        state.update(&key(KeyCode::Char('c')));
+        assert_eq!(
+            state.take_copy_request().as_deref(),
+            Some("[10:00:04 ERROR Json Assets] This mod failed\nSystem.NullReferenceException")
+        );
+
+        let mut state = state.join_lines(Some(" | ".to_string()));
+        state.update(&key(KeyCode::Char('c')));
+        assert_eq!(
+            state.take_copy_request().as_deref(),
+            Some("[10:00:04 ERROR Json Assets] This mod failed | System.NullReferenceException")
+        );
+    }
+
+    #[test]
+    fn shows_histogram_of_selected_source() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log);
+
+        // Levels don't have histograms
+        state.update(&key(KeyCode::Char('f')));
+        state.update(&key(KeyCode::Char('h')));
+        assert!(state.histogram_source.is_none());
+
+        state.update(&key(KeyCode::Char('f')));
+        state.update(&key(KeyCode::Char('h')));
+        let source = state.histogram_source.clone().unwrap();
+        let buffer = render_stateful(FormattedLog::default(), &mut state, 60, 14);
+        assert!(rows(&buffer)[2].starts_with(&format!("┌{source} (")));
+
+        state.update(&key(KeyCode::Esc));
+        assert!(state.histogram_source.is_none());
+        assert!(state.filters_list_state.is_some());
+    }
+
+    #[test]
+    fn sets_time_range_with_prompt() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log);
+        state.update(&key(KeyCode::Char('f')));
+        state.update(&key(KeyCode::Char('t')));
+        assert!(state.is_typing());
+        for c in "10:00:04".chars() {
+            state.update(&key(KeyCode::Char(c)));
+        }
+
+        // Invalid times keep the prompt open
+        state.update(&key(KeyCode::Tab));
+        state.update(&key(KeyCode::Char('x')));
+        state.update(&key(KeyCode::Enter));
+        assert!(state.time_range_input_state.is_some());
+        assert_eq!(state.notice.as_deref(), Some("Invalid time, use HH:MM:SS"));
+
+        // An empty end leaves the range open
+        state.update(&key(KeyCode::Backspace));
+        let buffer = render_stateful(FormattedLog::default(), &mut state, 60, 7);
+        assert_eq!(
+            rows(&buffer)[6].trim_end(),
+            "Show messages from: 10:00:04  to:"
+        );
+        state.update(&key(KeyCode::Enter));
+        assert!(state.time_range_input_state.is_none());
+        assert_eq!(state.lines.len(), 3);
+
+        // Errors outside the range are kept once the range is set
+        state.update(&key(KeyCode::Char('t')));
+        state.update(&key(KeyCode::Tab));
+        for _ in 0.."23:59:59".len() {
+            state.update(&key(KeyCode::Backspace));
+        }
+        for c in "10:00:04".chars() {
+            state.update(&key(KeyCode::Char(c)));
+        }
+        state.update(&key(KeyCode::Enter));
+        assert_eq!(state.lines.len(), 2);
+        state.update(&key(KeyCode::Char('e')));
+        assert!(state.filters.errors_outside_time_range);
+    }
+
+    #[test]
+    fn highlights_source_of_latest_error() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let state = FormattedLogState::new(&log).error_highlight_duration(Duration::from_secs(10));
+        assert!(state.error_source.is_none());
+
+        // Errors that arrive are highlighted
+        let grown = Log::parse(format!(
+            "{LOG}[10:00:06 ALERT Json Assets] Broke again\n[10:00:07 INFO  SMAPI] Fine\n"
+        ))
+        .unwrap();
+        let mut state = state.with_log(&grown);
+        assert_eq!(
+            state.error_source.map(|(source, _)| source),
+            Some("Json Assets")
+        );
+        let widget = FormattedLog::default().show_colors(true);
+        let buffer = render_stateful(widget.clone(), &mut state, 60, 9);
+        assert_eq!(buffer.get(15, 3).symbol, "J");
+        assert_eq!(buffer.get(15, 3).bg, widget.theme.level_colors.error);
+        assert_ne!(buffer.get(15, 0).bg, widget.theme.level_colors.error);
+
+        // The highlight expires
+        assert!(!state.update(&AppEvent::Ping));
+        state.error_source = state
+            .error_source
+            .map(|(source, since)| (source, since.checked_sub(Duration::from_secs(10)).unwrap()));
+        assert!(state.update(&AppEvent::Ping));
+        assert!(state.error_source.is_none());
+
+        // Pressing a key clears it too
+        let mut state = state.with_log(&log).with_log(&grown);
+        assert!(state.error_source.is_some());
+        state.update(&key(KeyCode::Char('q')));
+        assert!(state.error_source.is_none());
+    }
+
+    #[test]
+    fn jumps_between_errors() {
+        // Alerts are jumped to like errors
+        let log = Log::parse(format!("{LOG}[10:00:06 ALERT SMAPI] Crashed\n")).unwrap();
+        let mut state = FormattedLogState::new(&log);
+        let current =
+            |state: &FormattedLogState| state.current_message().unwrap().contents.to_string();
+        state.update(&key(KeyCode::Char('e')));
+        assert_eq!(
+            current(&state),
+            "This mod failed\nSystem.NullReferenceException"
+        );
+        state.update(&key(KeyCode::Char('e')));
+        assert_eq!(current(&state), "Crashed");
+
+        // Jumping past the end wraps around unless wrapping is disabled
+        state.wrap_navigation = false;
+        state.update(&key(KeyCode::Char('e')));
+        assert_eq!(current(&state), "Crashed");
+        assert_eq!(state.notice.as_deref(), Some("No more errors"));
+        state.wrap_navigation = true;
+        state.update(&key(KeyCode::Char('e')));
+        assert_eq!(
+            current(&state),
+            "This mod failed\nSystem.NullReferenceException"
+        );
+        assert_eq!(state.notice.as_deref(), Some("Wrapped to top"));
+
+        // Warnings can be included
+        state.update(&key(KeyCode::Char('W')));
+        state.update(&key(KeyCode::Char('E')));
+        assert_eq!(current(&state), "Missing item");
+        state.update(&key(KeyCode::Char('W')));
+        state.update(&key(KeyCode::Char('E')));
+        assert_eq!(current(&state), "Crashed");
+
+        // The first error can be jumped to from anywhere
+        state.update(&key(KeyCode::Char('F')));
+        assert_eq!(
+            current(&state),
+            "This mod failed\nSystem.NullReferenceException"
+        );
+    }
+
+    #[test]
+    fn jumps_to_first_error_on_load() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log).follow(true);
+        assert!(state.scroll_to_first_error());
+        assert_eq!(
+            state.current_message().unwrap().contents,
+            "This mod failed\nSystem.NullReferenceException"
+        );
+
+        // Logs without errors stay where they started
+        let log = Log::parse("[10:00:00 INFO  SMAPI] Starting\n".to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log);
+        assert!(!state.scroll_to_first_error());
+        state.update(&key(KeyCode::Char('F')));
+        assert_eq!(state.notice.as_deref(), Some("No errors"));
+    }
+
+    #[test]
+    fn measures_gaps_before_current_message() {
+        let log = Log::parse(format!(
+            "{LOG}[10:02:05 WARN  SMAPI] Slow\n[00:00:01 INFO  Json Assets] Next day\n"
+        ))
+        .unwrap();
+        let mut state = FormattedLogState::new(&log);
+        let gaps = |state: &FormattedLogState| state.current_message_gaps().unwrap().to_string();
+        assert_eq!(gaps(&state), "First message");
+
+        // Messages from other sources in between are skipped
+        state.scroll_to_message(5);
+        assert_eq!(gaps(&state), "Gap: +1s (+3s from same source)");
+        state.scroll_to_message(6);
+        assert_eq!(gaps(&state), "Gap: +2m 00s (+2m 04s from same source)");
+
+        // Timestamps before the previous one are on the next day
+        state.scroll_to_message(7);
+        assert_eq!(gaps(&state), "Gap: +13h 57m (+13h 59m from same source)");
+    }
+
+    #[test]
+    fn groups_sources_with_few_messages() {
+        let log = Log::parse(format!(
+            "{LOG}[10:00:06 INFO  Farm Type Manager] Loaded\n[10:00:07 INFO  Alpha] Hi\n"
+        ))
+        .unwrap();
+        let mut state = FormattedLogState::new(&log).group_sources_below(2);
+        state.update(&key(KeyCode::Char('f')));
+        state.update(&key(KeyCode::Char('f')));
+        let entries = |state: &FormattedLogState| {
+            let filters_list_state = state.filters_list_state.as_ref().unwrap();
+            filters_list_state
+                .entries(&state.filters)
+                .into_iter()
+                .map(|(name, enabled)| format!("{name}={enabled}"))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            entries(&state),
+            [
+                "Content Patcher=true",
+                "Json Assets=true",
+                "SMAPI=true",
+                "(other)=true"
+            ]
+        );
+
+        // Toggling the group toggles every source in it
+        for _ in 0..3 {
+            state.update(&key(KeyCode::Right));
+        }
+        state.update(&key(KeyCode::Char(' ')));
+        assert_eq!(entries(&state)[3], "(other)=false");
+        assert!(!state.filters.source_enabled("Alpha"));
+        assert!(!state.filters.source_enabled("Farm Type Manager"));
+        assert_eq!(state.lines.len(), 7);
+
+        // The group is kept when reloading
+        let state = state.with_log(&log);
+        assert_eq!(entries(&state)[3], "(other)=false");
+    }
+
+    #[test]
+    fn attaches_notes_to_messages() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = FormattedLogState::new(&log);
+        state.update(&key(KeyCode::Down));
+        state.update(&key(KeyCode::Char('A')));
+        assert!(state.is_typing());
+        for c in "slow".chars() {
+            state.update(&key(KeyCode::Char(c)));
+        }
+        state.update(&key(KeyCode::Enter));
+        assert_eq!(state.notes.get(&1).map(String::as_str), Some("slow"));
+        assert_eq!(state.take_changed_notes().map(HashMap::len), Some(1));
+        assert!(state.take_changed_notes().is_none());
+
+        // The note is shown for the message at the top, and only marked on
+        // the others
+        let buffer = render_stateful(FormattedLog::default(), &mut state, 60, 3);
+        let lines = rows(&buffer);
+        assert!(lines[0].contains("Loading mods ✎ slow"), "{lines:?}");
+        state.update(&key(KeyCode::Up));
+        let buffer = render_stateful(FormattedLog::default(), &mut state, 60, 3);
+        let lines = rows(&buffer);
+        assert!(lines[1].contains("Loading mods ✎ "), "{lines:?}");
+        assert!(!lines[1].contains("slow"), "{lines:?}");
+
+        // Clearing the note removes it
+        state.update(&key(KeyCode::Down));
+        state.update(&key(KeyCode::Char('A')));
+        for _ in 0..4 {
+            state.update(&key(KeyCode::Backspace));
+        }
+        state.update(&key(KeyCode::Enter));
+        assert!(state.notes.is_empty());
+        assert!(state.take_changed_notes().is_some());
+    }
+
+    #[test]
+    fn keeps_notes_on_the_same_message() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let state = FormattedLogState::new(&log).with_notes(HashMap::from([(1, "slow".into())]));
+
+        // The note follows its message when it moves
+        let moved = Log::parse(format!("[09:59:59 INFO  SMAPI] Earlier\n{LOG}")).unwrap();
+        let state = state.with_log(&moved);
+        assert_eq!(state.notes, HashMap::from([(2, "slow".into())]));
+
+        // A new session doesn't have the note
+        let replaced = Log::parse(LOG.replace("Loading mods", "Loading")).unwrap();
+        let state = state.with_log(&replaced);
+        assert!(state.notes.is_empty());
+    }
 }
@@ -0,0 +1,258 @@
+use crate::{
+    ast::{Level, Timestamp},
+    log::Log,
+    widgets::Theme,
+};
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{BarChart, Block, Borders, Clear, Widget},
+};
+
+/// A chart of when a source logged its messages during the session. Each bar
+/// covers the same amount of time, and is colored by the most severe level
+/// logged in it.
+#[derive(Clone, Debug)]
+pub struct SourceHistogram<'a> {
+    log: &'a Log,
+    source: &'a str,
+    buckets: usize,
+    style: Style,
+    show_colors: bool,
+    theme: Theme,
+}
+
+impl<'a> SourceHistogram<'a> {
+    /// The height of the chart, including its borders.
+    pub const HEIGHT: u16 = 12;
+
+    /// The default number of bars the session is split into.
+    pub const DEFAULT_BUCKETS: usize = 24;
+
+    /// The most bars the session can be split into.
+    pub const MAX_BUCKETS: usize = 200;
+
+    pub fn new(log: &'a Log, source: &'a str) -> Self {
+        SourceHistogram {
+            log,
+            source,
+            buckets: Self::DEFAULT_BUCKETS,
+            style: Style::default(),
+            show_colors: false,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Sets the number of bars the session is split into. There are never
+    /// more bars than columns to draw them in.
+    pub fn buckets(mut self, buckets: usize) -> Self {
+        self.buckets = buckets.clamp(1, Self::MAX_BUCKETS);
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn show_colors(mut self, show_colors: bool) -> Self {
+        self.show_colors = show_colors;
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Counts the source's messages in each of a number of buckets. Also
+    /// returns how many seconds each bucket covers.
+    fn count_buckets(&self, bucket_count: usize) -> (Vec<Bucket>, u64) {
+        let messages = self.log.messages();
+        let (Some(first), Some(last)) = (messages.first(), messages.last()) else {
+            return (Vec::new(), 1);
+        };
+
+        // The buckets cover the whole session, so charts for different
+        // sources line up
+        let span = last.timestamp.duration_since(first.timestamp).as_secs();
+        let bucket_seconds = span / bucket_count as u64 + 1;
+        let start = seconds_of_day(first.timestamp);
+        let mut buckets: Vec<_> = (0..bucket_count as u64)
+            .map(|index| Bucket {
+                start: start + index * bucket_seconds,
+                count: 0,
+                level: None,
+            })
+            .collect();
+        for message in self.log.messages_by_source(self.source) {
+            let elapsed = message.timestamp.duration_since(first.timestamp).as_secs();
+            let Some(bucket) = usize::try_from(elapsed / bucket_seconds)
+                .ok()
+                .and_then(|index| buckets.get_mut(index))
+            else {
+                continue;
+            };
+            bucket.count += 1;
+            if bucket
+                .level
+                .is_none_or(|level| severity(message.level) > severity(level))
+            {
+                bucket.level = Some(message.level);
+            }
+        }
+        (buckets, bucket_seconds)
+    }
+
+    /// Gets the style of a bar and of the count shown on it.
+    fn bar_styles(&self, level: Option<Level>) -> (Style, Style) {
+        match level {
+            Some(level) if self.show_colors => {
+                let color = self.theme.level_color(level);
+                (
+                    self.style.fg(color),
+                    Style::default().fg(Color::Black).bg(color),
+                )
+            }
+            _ => (self.style, self.style.add_modifier(Modifier::REVERSED)),
+        }
+    }
+}
+
+impl Widget for SourceHistogram<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Render block. Each bar needs at least one column, so the whole
+        // session fits.
+        let block = Block::default().borders(Borders::ALL);
+        let columns = usize::from(block.inner(area).width).max(1);
+        let (buckets, bucket_seconds) = self.count_buckets(self.buckets.min(columns));
+        let count = self.log.messages_by_source(self.source).len();
+        let block = block.border_style(self.style).title(format!(
+            "{} ({count} messages, {} per bar)",
+            self.source,
+            format_duration(bucket_seconds)
+        ));
+        let inner_area = block.inner(area);
+        Clear.render(area, buf);
+        buf.set_style(area, self.style);
+        block.render(area, buf);
+
+        // Each bar is its own chart so it can have its own color
+        let max = buckets.iter().map(|bucket| bucket.count).max().unwrap_or(1);
+        let slot_width = u16::try_from(usize::from(inner_area.width) / buckets.len().max(1))
+            .unwrap_or(1)
+            .max(1);
+        let bar_width = slot_width.saturating_sub(1).max(1);
+        let bars = buckets
+            .iter()
+            .zip((inner_area.left()..inner_area.right()).step_by(usize::from(slot_width)));
+        for (bucket, x) in bars {
+            let label = if bar_width >= 5 {
+                format_time_of_day(bucket.start)
+            } else {
+                String::new()
+            };
+            let data = [(label.as_str(), bucket.count)];
+            let (bar_style, value_style) = self.bar_styles(bucket.level);
+            BarChart::default()
+                .data(&data)
+                .max(max)
+                .bar_width(bar_width)
+                .bar_gap(0)
+                .bar_style(bar_style)
+                .value_style(value_style)
+                .label_style(self.style)
+                .style(self.style)
+                .render(
+                    Rect::new(
+                        x,
+                        inner_area.y,
+                        bar_width.min(inner_area.right() - x),
+                        inner_area.height,
+                    ),
+                    buf,
+                );
+        }
+    }
+}
+
+/// The messages logged during part of the session.
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    /// When the bucket starts, in seconds since midnight. This can be more
+    /// than a day if the session continues past midnight.
+    start: u64,
+    count: u64,
+    /// The most severe level logged in the bucket.
+    level: Option<Level>,
+}
+
+/// Gets how severe a level is. Unknown levels are the least severe.
+fn severity(level: Level) -> usize {
+    Level::ALL
+        .iter()
+        .position(|&known| known == level)
+        .map_or(0, |index| index + 1)
+}
+
+fn seconds_of_day(timestamp: Timestamp) -> u64 {
+    u64::from(timestamp.hour) * 3600
+        + u64::from(timestamp.minute) * 60
+        + u64::from(timestamp.second)
+}
+
+/// Formats a number of seconds since midnight as "HH:MM".
+fn format_time_of_day(seconds: u64) -> String {
+    format!("{:02}:{:02}", seconds / 3600 % 24, seconds / 60 % 60)
+}
+
+/// Formats a duration in seconds, like "5m 30s".
+fn format_duration(seconds: u64) -> String {
+    match (seconds / 60, seconds % 60) {
+        (0, seconds) => format!("{seconds}s"),
+        (minutes, 0) => format!("{minutes}m"),
+        (minutes, seconds) => format!("{minutes}m {seconds}s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::test_utils::{render, rows};
+
+    #[test]
+    fn counts_messages_over_the_session() {
+        let log = Log::parse(
+            "\
+[10:00:00 INFO  SMAPI] Starting
+[10:00:10 WARN  Json Assets] Missing item
+[10:00:20 ERROR Json Assets] Failed
+[10:00:25 INFO  Json Assets] Loaded
+[10:01:59 INFO  SMAPI] Done
+"
+            .to_string(),
+        )
+        .unwrap();
+        let histogram = SourceHistogram::new(&log, "Json Assets").buckets(4);
+        let (buckets, bucket_seconds) = histogram.count_buckets(4);
+        assert_eq!(bucket_seconds, 30);
+        let counts: Vec<_> = buckets
+            .iter()
+            .map(|bucket| (bucket.count, bucket.level))
+            .collect();
+        assert_eq!(
+            counts,
+            [(3, Some(Level::Error)), (0, None), (0, None), (0, None)]
+        );
+
+        let buffer = render(histogram, 40, 6);
+        assert_eq!(rows(&buffer)[0], "┌Json Assets (3 messages, 30s per bar)─┐");
+        assert_eq!(rows(&buffer)[4], "│10:00    10:00    10:01    10:01      │");
+
+        // There are never more bars than columns
+        let histogram = SourceHistogram::new(&log, "Json Assets").buckets(usize::MAX);
+        let buffer = render(histogram, 12, 6);
+        assert_eq!(rows(&buffer)[1], "│███       │");
+    }
+}
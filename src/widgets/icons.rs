@@ -59,6 +59,7 @@ impl IconPack for UnicodeIconPack {
     const ARROWS: &'static str = "↑↓→←";
 }
 
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
 pub struct NonUnicodeIconPack;
 
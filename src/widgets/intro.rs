@@ -0,0 +1,99 @@
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Widget, Wrap},
+};
+
+/// A welcome overlay shown the first time pufferwatch is launched.
+#[derive(Clone, Debug, Default)]
+pub struct Intro {
+    style: Style,
+}
+
+impl Intro {
+    const WIDTH: u16 = 64;
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    fn text(&self) -> Text<'static> {
+        let heading = self.style.add_modifier(Modifier::BOLD);
+        let lines = [
+            Spans::from(Span::styled("Tabs", heading)),
+            Spans::from(
+                "The Log tab shows each parsed message with its level and source. The Raw tab \
+                 shows the log exactly as it was written. Press Tab to switch between them.",
+            ),
+            Spans::default(),
+            Spans::from(Span::styled("Filters", heading)),
+            Spans::from(
+                "Press f in the Log tab to open the filters. Press f again to switch between \
+                 levels and sources, and Space to toggle the selected filter.",
+            ),
+            Spans::default(),
+            Spans::from(Span::styled("Commands", heading)),
+            Spans::from(
+                "When SMAPI is started with `pufferwatch run`, press i to type console commands \
+                 and Esc to return to the log.",
+            ),
+            Spans::default(),
+            Spans::from(
+                "The bar at the bottom lists the available controls. Press . to see more of them.",
+            ),
+            Spans::default(),
+            Spans::from(Span::styled("Press any key to continue.", heading)),
+        ];
+        lines.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+impl Widget for Intro {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Get the popup area, leaving room for the borders and horizontal padding
+        let text = self.text();
+        let width = Self::WIDTH.min(area.width);
+        let text_height = wrapped_height(&text, usize::from(width.saturating_sub(4)).max(1));
+        let height = u16::try_from(text_height.saturating_add(2))
+            .unwrap_or(u16::MAX)
+            .min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        // Render popup
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(self.style)
+            .title("Welcome to pufferwatch");
+        let inner_area = block.inner(popup_area);
+        let inner_area = Rect {
+            x: inner_area.x.saturating_add(1),
+            width: inner_area.width.saturating_sub(2),
+            ..inner_area
+        };
+        Clear.render(popup_area, buf);
+        buf.set_style(popup_area, self.style);
+        block.render(popup_area, buf);
+        Paragraph::new(text)
+            .style(self.style)
+            .wrap(Wrap { trim: true })
+            .render(inner_area, buf);
+    }
+}
+
+/// Estimates the number of rows the text takes up when wrapped to the given
+/// width.
+fn wrapped_height(text: &Text, width: usize) -> usize {
+    text.lines
+        .iter()
+        .map(|spans| spans.width().max(1).div_ceil(width))
+        .sum()
+}
@@ -2,13 +2,14 @@ use crate::{
     events::AppEvent,
     widgets::{BindingDisplay, IconPack, Scrollbar, State},
 };
-use crossterm::event::{Event, KeyCode};
+use crossterm::event::{Event, KeyCode, MouseEventKind};
 use indexmap::IndexMap;
+use std::time::{Duration, Instant};
 use tui::{
     buffer::Buffer,
     layout::Rect,
     style::Style,
-    text::Spans,
+    text::{Span, Spans, StyledGrapheme},
     widgets::{Block, Clear, StatefulWidget, Widget},
 };
 use unicode_width::UnicodeWidthStr;
@@ -18,6 +19,8 @@ pub struct LazyParagraph<'i, F> {
     get_line: F,
     block: Option<Block<'i>>,
     style: Style,
+    auto_scroll_interval: Duration,
+    wrap: bool,
 }
 
 impl<'i, F> LazyParagraph<'i, F>
@@ -30,6 +33,8 @@ where
             get_line,
             block: None,
             style: Style::default(),
+            auto_scroll_interval: Duration::ZERO,
+            wrap: false,
         }
     }
 
@@ -44,6 +49,83 @@ where
         self.style = style;
         self
     }
+
+    /// Sets the minimum time between auto-scrolling to new lines. While many
+    /// lines are being added, this keeps the view still long enough to read.
+    pub fn auto_scroll_interval(mut self, auto_scroll_interval: Duration) -> Self {
+        self.auto_scroll_interval = auto_scroll_interval;
+        self
+    }
+
+    /// Sets whether long lines continue on the next rows instead of being
+    /// cut off. Wrapped lines can't be scrolled horizontally.
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Splits a line into the rows it's rendered on, along with the x offset
+    /// of each grapheme in its row. Without wrapping, the line is a single
+    /// row scrolled horizontally by `offset_x`.
+    fn layout_line<'a>(
+        &self,
+        line: &'a Spans<'a>,
+        area: Rect,
+        offset_x: usize,
+    ) -> Vec<Vec<(usize, StyledGrapheme<'a>)>> {
+        let graphemes = line
+            .0
+            .iter()
+            .flat_map(|span| span.styled_graphemes(self.style));
+        if !self.wrap {
+            let row = graphemes
+                // Calculate x offset for each grapheme
+                .scan(0_usize, |cur_x, grapheme| {
+                    let width = grapheme.symbol.width();
+                    let x = *cur_x;
+                    *cur_x = cur_x.saturating_add(width);
+                    Some((x, *cur_x, grapheme))
+                })
+                // Ignore content that is to the left of the area
+                .filter(|&(_, cur_x, _)| cur_x >= offset_x)
+                // Offset the remaining graphemes
+                .map(|(x, _, grapheme)| (x.saturating_sub(offset_x), grapheme))
+                .collect();
+            return vec![row];
+        }
+
+        // Start a new row when a grapheme doesn't fit, keeping its style
+        let width = usize::from(area.width).max(1);
+        let mut rows = vec![Vec::new()];
+        let mut x = 0;
+        for grapheme in graphemes {
+            let grapheme_width = grapheme.symbol.width();
+            if x > 0 && x + grapheme_width > width {
+                rows.push(Vec::new());
+                x = 0;
+            }
+            rows.last_mut().unwrap().push((x, grapheme));
+            x += grapheme_width;
+        }
+        rows
+    }
+
+    /// Gets the first line shown when scrolled to the bottom while wrapping,
+    /// so the last line ends on the last row.
+    fn first_line_at_bottom(&self, lines: usize, area: Rect) -> usize {
+        let mut rows = 0;
+        let mut first_line = lines;
+        while first_line > 0 {
+            let line_rows = (self.get_line)(first_line - 1)
+                .map_or(1, |line| self.layout_line(&line, area, 0).len());
+            if rows + line_rows > usize::from(area.height) && first_line < lines {
+                break;
+            }
+            rows += line_rows;
+            first_line -= 1;
+        }
+        first_line
+    }
 }
 
 impl<'i, F> StatefulWidget for LazyParagraph<'i, F>
@@ -52,10 +134,10 @@ where
 {
     type State = LazyParagraphState;
 
-    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+    fn render(mut self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         // Render block
         let has_block = self.block.is_some();
-        let inner_area = if let Some(block) = self.block {
+        let inner_area = if let Some(block) = self.block.take() {
             let inner = block.inner(area);
             block.render(area, buf);
             inner
@@ -63,24 +145,11 @@ where
             area
         };
 
-        // Update auto-scroll state
-        let height = inner_area.height.into();
-        let mut line_after_last_line = state.offset.y.saturating_add(height);
-        if state.auto_scroll.is_scrolling() || line_after_last_line >= state.lines {
-            line_after_last_line = state.lines;
-            state.offset.y = state.lines.saturating_sub(height);
-            state.auto_scroll.scroll();
-        } else {
-            state.auto_scroll.reset();
-        };
-
-        // Get rendered lines
-        let first_line = line_after_last_line.saturating_sub(height);
-        state.offset.y = first_line;
-
-        // Render paragraph
+        // Wrapped lines can't be counted without rendering them, so there's
+        // always room for the scrollbar while wrapping
+        let height: usize = inner_area.height.into();
         let render_scrollbar = state.lines > height;
-        let text_area = if render_scrollbar && !has_block {
+        let text_area = if (render_scrollbar || self.wrap) && !has_block {
             Rect {
                 width: inner_area.width.saturating_sub(1),
                 ..inner_area
@@ -88,71 +157,88 @@ where
         } else {
             inner_area
         };
-        for (line, i) in (first_line..line_after_last_line).zip(0..) {
-            // Get line area
-            let line_area = Rect::new(
-                text_area.left(),
-                text_area.top().saturating_add(i),
-                text_area.width,
-                1,
-            );
-
-            // Clear line area
-            Clear.render(line_area, buf);
-
-            // Get line contents
-            let line_contents = match (self.get_line)(line) {
-                Some(line_contents) => line_contents,
-                None => continue,
-            };
 
-            // Render line contents
-            let rendered_contents = line_contents
-                .0
-                .iter()
-                .flat_map(|span| span.styled_graphemes(self.style))
-                // Calculate x offset for each grapheme
-                .scan(0_usize, |cur_x, grapheme| {
-                    let width = grapheme.symbol.width();
-                    let x = *cur_x;
-                    *cur_x = cur_x.saturating_add(width);
-                    Some((x, *cur_x, grapheme))
-                })
-                // Ignore content that is to the left of the area
-                .filter(|&(_, cur_x, _)| cur_x >= state.offset.x)
-                // Offset the remaining graphemes
-                .map(|(x, _, grapheme)| {
-                    let x = x.saturating_sub(state.offset.x);
-                    (x, grapheme)
-                });
-
-            for (x, grapheme) in rendered_contents {
-                let x: u16 = match x.try_into() {
-                    Ok(x) => x,
-                    Err(_) => break,
+        // Get rendered lines. While wrapping, fewer lines fit at the bottom.
+        let visible_lines = if self.wrap {
+            state.lines - self.first_line_at_bottom(state.lines, text_area)
+        } else {
+            height
+        };
+        let line_after_last_line =
+            state.update_auto_scroll(visible_lines, self.auto_scroll_interval);
+        let first_line = line_after_last_line.saturating_sub(visible_lines);
+        state.offset.y = first_line;
+
+        // Render paragraph
+        let offset_x = if self.wrap { 0 } else { state.offset.x };
+        state.rendered_rows.clear();
+        let mut line = first_line;
+        while state.rendered_rows.len() < height {
+            let line_contents = (self.get_line)(line);
+            let rows = match line_contents.as_ref() {
+                Some(line_contents) => self.layout_line(line_contents, text_area, offset_x),
+                None => vec![Vec::new()],
+            };
+            for (row, graphemes) in rows.into_iter().enumerate() {
+                let Ok(y) = u16::try_from(state.rendered_rows.len()) else {
+                    break;
                 };
-                let x = x.saturating_add(line_area.x);
-                let y = line_area.y;
-                let remaining_width = line_area.width.saturating_add(1).saturating_sub(x);
-                if remaining_width == 0 {
+                if y >= text_area.height {
                     break;
                 }
 
-                buf.set_stringn(
-                    x,
-                    y,
-                    grapheme.symbol,
-                    remaining_width.into(),
-                    grapheme.style,
-                );
+                // Clear line area
+                let line_area =
+                    Rect::new(text_area.left(), text_area.top() + y, text_area.width, 1);
+                Clear.render(line_area, buf);
+                state.rendered_rows.push((line, row));
+
+                // Render line contents
+                for (x, grapheme) in graphemes {
+                    let Ok(x) = u16::try_from(x) else {
+                        break;
+                    };
+                    let x = x.saturating_add(line_area.x);
+                    let remaining_width = line_area.right().saturating_sub(x);
+                    if remaining_width == 0 {
+                        break;
+                    }
+
+                    buf.set_stringn(
+                        x,
+                        line_area.y,
+                        grapheme.symbol,
+                        remaining_width.into(),
+                        grapheme.style,
+                    );
+                }
             }
+            line = line.saturating_add(1);
         }
+        let line_after_rendered = line.min(state.lines);
 
         // Render scrollbar
+        let render_scrollbar = if self.wrap {
+            first_line > 0 || line_after_rendered < state.lines
+        } else {
+            render_scrollbar
+        };
         if render_scrollbar {
-            let scrollbar_area = Rect::new(inner_area.right(), inner_area.y, 1, inner_area.height);
-            let y = state.offset.y as f32;
-            Scrollbar::new(y..(y + height as f32), state.lines as f32).render(scrollbar_area, buf);
+            // The scrollbar replaces the right border, or takes the last column
+            // if there's no border
+            let scrollbar_x = if has_block {
+                inner_area.right()
+            } else {
+                text_area.right()
+            };
+            let scrollbar_area = Rect::new(scrollbar_x, inner_area.y, 1, inner_area.height);
+            let y = first_line as f32;
+            let visible = if self.wrap {
+                line_after_rendered.saturating_sub(first_line)
+            } else {
+                height
+            };
+            Scrollbar::new(y..(y + visible as f32), state.lines as f32).render(scrollbar_area, buf);
         }
     }
 }
@@ -162,6 +248,11 @@ pub struct LazyParagraphState {
     pub lines: usize,
     pub offset: Offset,
     pub auto_scroll: AutoScroll,
+    last_auto_scroll: Option<Instant>,
+    auto_scroll_pending: bool,
+    /// The line shown on each row when the paragraph was last rendered, and
+    /// which row of that line it is.
+    rendered_rows: Vec<(usize, usize)>,
 }
 
 impl LazyParagraphState {
@@ -172,9 +263,54 @@ impl LazyParagraphState {
             auto_scroll: auto_scroll
                 .then(AutoScroll::enabled)
                 .unwrap_or_else(AutoScroll::disabled),
+            last_auto_scroll: None,
+            auto_scroll_pending: false,
+            rendered_rows: Vec::new(),
         }
     }
 
+    /// Gets the line shown on a row of the text when the paragraph was last
+    /// rendered, and which row of that line it is. Wrapped lines take up
+    /// several rows.
+    pub fn line_at_row(&self, row: usize) -> Option<(usize, usize)> {
+        self.rendered_rows.get(row).copied()
+    }
+
+    /// Gets the line at the top of the view and the total number of lines,
+    /// like "4210/90000".
+    pub fn position(&self) -> String {
+        format!("{}/{}", self.offset.y.saturating_add(1), self.lines)
+    }
+
+    /// Updates the auto-scroll state and gets the index of the line after the
+    /// last visible line.
+    fn update_auto_scroll(&mut self, height: usize, interval: Duration) -> usize {
+        let line_after_last_line = self.offset.y.saturating_add(height);
+        let auto_scroll_due = self
+            .last_auto_scroll
+            .is_none_or(|last_auto_scroll| last_auto_scroll.elapsed() >= interval);
+        if self.auto_scroll.is_scrolling() && line_after_last_line <= self.lines && !auto_scroll_due
+        {
+            // Wait to scroll to the new lines
+            self.auto_scroll_pending = true;
+            line_after_last_line
+        } else if self.auto_scroll.is_scrolling() || line_after_last_line >= self.lines {
+            self.offset.y = self.lines.saturating_sub(height);
+            self.auto_scroll.scroll();
+            self.auto_scroll_pending = false;
+            self.last_auto_scroll = Some(Instant::now());
+            self.lines
+        } else {
+            self.auto_scroll.reset();
+            line_after_last_line
+        }
+    }
+
+    /// Changes the number of lines, keeping the rest of the state.
+    pub fn with_lines(self, lines: usize) -> Self {
+        LazyParagraphState { lines, ..self }
+    }
+
     /// Scrolls the paragraph down by the given amount.
     pub fn scroll_down(&mut self, lines: usize) {
         self.offset.y = self.offset.y.saturating_add(lines);
@@ -201,6 +337,12 @@ impl LazyParagraphState {
         self.offset.x = self.offset.x.saturating_add(lines);
     }
 
+    /// Scrolls the paragraph so the given line is at the top.
+    pub fn scroll_to(&mut self, line: usize) {
+        self.offset.y = line.min(self.lines.saturating_sub(1));
+        self.auto_scroll.reset();
+    }
+
     /// Scrolls the paragraph to the top.
     pub fn scroll_to_top(&mut self) {
         self.offset.y = 0;
@@ -217,6 +359,8 @@ impl LazyParagraphState {
 impl State for LazyParagraphState {
     fn update(&mut self, event: &AppEvent) -> bool {
         match event {
+            // Redraw to catch up on new lines if auto-scrolling was delayed
+            AppEvent::Ping => self.auto_scroll_pending,
             AppEvent::TermEvent(Event::Key(key_event)) => match key_event.code {
                 KeyCode::Up => {
                     self.scroll_up(1);
@@ -252,7 +396,18 @@ impl State for LazyParagraphState {
                 }
                 _ => false,
             },
-            _ => false,
+            AppEvent::TermEvent(Event::Mouse(mouse_event)) => match mouse_event.kind {
+                MouseEventKind::ScrollUp => {
+                    self.scroll_up(3);
+                    true
+                }
+                MouseEventKind::ScrollDown => {
+                    self.scroll_down(3);
+                    true
+                }
+                _ => false,
+            },
+            AppEvent::TermEvent(_) => false,
         }
     }
 
@@ -271,10 +426,13 @@ pub struct Offset {
     pub y: usize,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
 pub enum AutoScroll {
+    #[default]
     Disabled,
-    Enabled { scrolling: bool },
+    Enabled {
+        scrolling: bool,
+    },
 }
 
 impl AutoScroll {
@@ -306,9 +464,32 @@ impl AutoScroll {
         }
     }
 }
+/// Splits text into spans, styling the parts that match a search with
+/// `match_style`. Matching ignores ASCII case.
+pub fn highlight_matches<'a>(
+    text: &'a str,
+    search: &str,
+    style: Style,
+    match_style: Style,
+) -> Vec<Span<'a>> {
+    if search.is_empty() {
+        return vec![Span::styled(text, style)];
+    }
 
-impl Default for AutoScroll {
-    fn default() -> Self {
-        AutoScroll::Disabled
+    // Lowercasing ASCII keeps the byte offsets the same as in the text
+    let lowercase = text.to_ascii_lowercase();
+    let search = search.to_ascii_lowercase();
+    let mut spans = Vec::new();
+    let mut end = 0;
+    for (start, _) in lowercase.match_indices(&search) {
+        if start > end {
+            spans.push(Span::styled(&text[end..start], style));
+        }
+        end = start + search.len();
+        spans.push(Span::styled(&text[start..end], match_style));
+    }
+    if end < text.len() || spans.is_empty() {
+        spans.push(Span::styled(&text[end..], style));
     }
+    spans
 }
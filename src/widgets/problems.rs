@@ -0,0 +1,152 @@
+use crate::{
+    ast::{Message, Problem},
+    events::AppEvent,
+    log::Log,
+    widgets::{BindingDisplay, IconPack, State, WithLog},
+};
+use crossterm::event::{Event, KeyCode};
+use indexmap::IndexMap;
+use std::marker::PhantomData;
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::{Span, Spans},
+    widgets::{Block, Borders, StatefulWidget, Widget},
+};
+
+/// A list of the problems SMAPI reported in the log.
+#[derive(Clone, Debug, Default)]
+pub struct ProblemsList<'i> {
+    style: Style,
+    selected_style: Style,
+    marker: PhantomData<&'i Log>,
+}
+
+impl ProblemsList<'_> {
+    /// The maximum number of problems shown at once.
+    const MAX_VISIBLE: usize = 5;
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn selected_style(mut self, style: Style) -> Self {
+        self.selected_style = style;
+        self
+    }
+
+    /// Gets the height needed to render the list, including its borders.
+    pub fn height(state: &ProblemsListState<'_>) -> u16 {
+        state.problems.len().clamp(1, Self::MAX_VISIBLE) as u16 + 2
+    }
+}
+
+impl<'i> StatefulWidget for ProblemsList<'i> {
+    type State = ProblemsListState<'i>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        // Render block
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(self.style)
+            .title(format!("Problems ({})", state.problems.len()));
+        let inner_area = block.inner(area);
+        buf.set_style(area, self.style);
+        block.render(area, buf);
+        if state.problems.is_empty() {
+            buf.set_stringn(
+                inner_area.x,
+                inner_area.y,
+                "No problems found",
+                inner_area.width.into(),
+                self.style,
+            );
+            return;
+        }
+
+        // Keep the selected problem visible
+        let height = usize::from(inner_area.height);
+        let start = state.selected.saturating_sub(height.saturating_sub(1));
+        let visible = state.problems.iter().enumerate().skip(start).take(height);
+        for ((index, (_, message, problem)), y) in visible.zip(inner_area.y..) {
+            let style = if index == state.selected {
+                self.selected_style
+            } else {
+                self.style
+            };
+            let description = message.contents.trim().trim_start_matches("- ");
+            let spans = Spans::from(vec![
+                Span::styled(format!("{} {problem:18} ", message.timestamp), style),
+                Span::styled(description, style),
+            ]);
+            buf.set_style(Rect::new(inner_area.x, y, inner_area.width, 1), style);
+            buf.set_spans(inner_area.x, y, &spans, inner_area.width);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProblemsListState<'i> {
+    problems: Vec<(usize, &'i Message<'i>, Problem)>,
+    selected: usize,
+}
+
+impl<'i> ProblemsListState<'i> {
+    pub fn new(log: &'i Log) -> Self {
+        ProblemsListState {
+            problems: Self::find_problems(log),
+            selected: 0,
+        }
+    }
+
+    fn find_problems(log: &'i Log) -> Vec<(usize, &'i Message<'i>, Problem)> {
+        log.messages()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, message)| Some((index, message, message.problem()?)))
+            .collect()
+    }
+
+    /// Gets the index of the selected problem's message in the log.
+    pub fn selected_message(&self) -> Option<usize> {
+        self.problems.get(self.selected).map(|&(index, _, _)| index)
+    }
+}
+
+impl State for ProblemsListState<'_> {
+    fn update(&mut self, event: &AppEvent) -> bool {
+        match event {
+            AppEvent::TermEvent(Event::Key(key_event)) => match key_event.code {
+                KeyCode::Up => {
+                    self.selected = self.selected.saturating_sub(1);
+                    true
+                }
+                KeyCode::Down => {
+                    self.selected = self
+                        .selected
+                        .saturating_add(1)
+                        .min(self.problems.len().saturating_sub(1));
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn add_controls<I: IconPack>(&self, controls: &mut IndexMap<BindingDisplay<I>, &'static str>) {
+        controls.insert(BindingDisplay::Custom(I::UP_DOWN), "Nav");
+    }
+}
+
+impl<'j> WithLog<'j> for ProblemsListState<'_> {
+    type Result = ProblemsListState<'j>;
+
+    fn with_log(self, log: &'j Log) -> Self::Result {
+        let problems = ProblemsListState::find_problems(log);
+        let selected = self.selected.min(problems.len().saturating_sub(1));
+        ProblemsListState { problems, selected }
+    }
+}
@@ -1,10 +1,14 @@
 use crate::{
     events::AppEvent,
     log::Log,
-    widgets::{BindingDisplay, IconPack, LazyParagraph, LazyParagraphState, State, WithLog},
+    widgets::{
+        highlight_matches, BindingDisplay, IconPack, LazyParagraph, LazyParagraphState, State,
+        WithLog,
+    },
 };
+use crossterm::event::{Event, KeyCode};
 use indexmap::IndexMap;
-use std::marker::PhantomData;
+use std::{marker::PhantomData, time::Duration};
 use tui::{
     buffer::Buffer,
     layout::Rect,
@@ -16,6 +20,8 @@ use tui::{
 pub struct RawLog<'i> {
     block: Option<Block<'i>>,
     style: Style,
+    auto_scroll_interval: Duration,
+    search_match_style: Style,
     marker: PhantomData<&'i Log>,
 }
 
@@ -30,14 +36,37 @@ impl<'i> RawLog<'i> {
         self.style = style;
         self
     }
+
+    pub fn auto_scroll_interval(mut self, auto_scroll_interval: Duration) -> Self {
+        self.auto_scroll_interval = auto_scroll_interval;
+        self
+    }
+
+    /// Sets the style of text matching the search.
+    pub fn search_match_style(mut self, search_match_style: Style) -> Self {
+        self.search_match_style = search_match_style;
+        self
+    }
 }
 
 impl<'i> StatefulWidget for RawLog<'i> {
     type State = RawLogState<'i>;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        let paragraph = LazyParagraph::new(|index| state.lines.get(index).copied().map(Into::into))
-            .style(self.style);
+        let search = state.search.as_deref();
+        let paragraph = LazyParagraph::new(|index| {
+            let line = *state.lines.get(index)?;
+            Some(match search {
+                Some(search) => {
+                    highlight_matches(line, search, Style::default(), self.search_match_style)
+                        .into()
+                }
+                None => line.into(),
+            })
+        })
+        .style(self.style)
+        .auto_scroll_interval(self.auto_scroll_interval)
+        .wrap(state.wrap);
         let paragraph = if let Some(block) = self.block {
             paragraph.block(block)
         } else {
@@ -51,40 +80,213 @@ impl<'i> StatefulWidget for RawLog<'i> {
 pub struct RawLogState<'i> {
     lines: Vec<&'i str>,
     paragraph_state: LazyParagraphState,
+    /// Whether long lines continue on the next rows.
+    wrap: bool,
+    /// The text searched for in the formatted log, which is highlighted here
+    /// too.
+    search: Option<String>,
 }
 
 impl<'i> RawLogState<'i> {
-    pub fn new(log: &'i Log) -> Self {
+    /// Creates the state for a log. If `auto_scroll` is set, the log starts
+    /// scrolled to the end and follows new lines.
+    pub fn new(log: &'i Log, auto_scroll: bool) -> Self {
         let lines: Vec<_> = log.raw().lines().collect();
-        let paragraph_state = LazyParagraphState::new(lines.len(), true);
+        let paragraph_state = LazyParagraphState::new(lines.len(), auto_scroll);
         RawLogState {
             lines,
             paragraph_state,
+            wrap: false,
+            search: None,
+        }
+    }
+
+    /// Gets the state of the paragraph the lines are shown in.
+    pub fn paragraph_state(&self) -> &LazyParagraphState {
+        &self.paragraph_state
+    }
+
+    /// Sets the text to highlight and jump between with `n` and `N`.
+    pub fn set_search(&mut self, search: Option<&str>) {
+        if self.search.as_deref() != search {
+            self.search = search.map(str::to_string);
         }
     }
+
+    /// Scrolls to the next line containing the search, starting at the given
+    /// line. Searching backwards starts at the line before it. Returns
+    /// whether a line was found.
+    fn scroll_to_match(&mut self, start: usize, forward: bool) -> bool {
+        let Some(search) = self.search.as_ref() else {
+            return false;
+        };
+
+        let search = search.to_lowercase();
+        let matches = |&index: &usize| self.lines[index].to_lowercase().contains(&search);
+        let start = start.min(self.lines.len());
+        let found = if forward {
+            (start..self.lines.len()).find(matches)
+        } else {
+            (0..start).rev().find(matches)
+        };
+        let Some(line) = found else {
+            return false;
+        };
+
+        self.paragraph_state.scroll_to(line);
+        true
+    }
 }
 
-impl<'i> State for RawLogState<'i> {
+impl State for RawLogState<'_> {
     fn update(&mut self, event: &AppEvent) -> bool {
+        if let AppEvent::TermEvent(Event::Key(key_event)) = event {
+            match key_event.code {
+                KeyCode::Char('w') => {
+                    self.wrap = !self.wrap;
+                    self.paragraph_state.offset.x = 0;
+                    return true;
+                }
+                KeyCode::Char('n') if self.search.is_some() => {
+                    let start = self.paragraph_state.offset.y.saturating_add(1);
+                    return self.scroll_to_match(start, true);
+                }
+                KeyCode::Char('N') if self.search.is_some() => {
+                    return self.scroll_to_match(self.paragraph_state.offset.y, false);
+                }
+                _ => {}
+            }
+        }
+
         self.paragraph_state.update(event)
     }
 
     fn add_controls<I: IconPack>(&self, controls: &mut IndexMap<BindingDisplay<I>, &'static str>) {
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('w')),
+            if self.wrap { "No wrap" } else { "Wrap" },
+        );
+        if self.search.is_some() {
+            controls.insert(BindingDisplay::simple_key(KeyCode::Char('n')), "Next match");
+            controls.insert(BindingDisplay::simple_key(KeyCode::Char('N')), "Prev match");
+        }
         self.paragraph_state.add_controls(controls);
     }
 }
 
-impl<'i, 'j> WithLog<'j> for RawLogState<'i> {
+impl<'j> WithLog<'j> for RawLogState<'_> {
     type Result = RawLogState<'j>;
 
     fn with_log(self, log: &'j Log) -> Self::Result {
+        let lines: Vec<_> = log.raw().lines().collect();
         RawLogState {
-            paragraph_state: LazyParagraphState {
-                offset: self.paragraph_state.offset,
-                auto_scroll: self.paragraph_state.auto_scroll,
-                ..LazyParagraphState::new(log.raw().lines().count(), true)
-            },
-            ..RawLogState::new(log)
+            paragraph_state: self.paragraph_state.with_lines(lines.len()),
+            lines,
+            wrap: self.wrap,
+            search: self.search,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::test_utils::{key, render_stateful, rows};
+    use tui::style::Color;
+
+    const LOG: &str = "\
+[10:00:00 INFO  SMAPI] Starting
+[10:00:01 INFO  SMAPI] A very long line that does not fit
+[10:00:02 INFO  SMAPI] Done
+";
+
+    fn press(state: &mut RawLogState<'_>, code: KeyCode) {
+        state.update(&key(code));
+    }
+
+    fn toggle_wrap(state: &mut RawLogState<'_>) {
+        press(state, KeyCode::Char('w'));
+    }
+
+    #[test]
+    fn renders_raw_lines() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = RawLogState::new(&log, true);
+        let buffer = render_stateful(RawLog::default(), &mut state, 41, 4);
+        assert_eq!(
+            rows(&buffer),
+            [
+                "[10:00:00 INFO  SMAPI] Starting",
+                "[10:00:01 INFO  SMAPI] A very long line t",
+                "[10:00:02 INFO  SMAPI] Done",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn highlights_and_jumps_to_search_matches() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = RawLogState::new(&log, false);
+        state.set_search(Some("smapi] d"));
+        let widget = RawLog::default().search_match_style(Style::default().bg(Color::Yellow));
+        let buffer = render_stateful(widget.clone(), &mut state, 41, 4);
+        let highlighted: Vec<_> = (15..25)
+            .map(|x| buffer.get(x, 2).bg == Color::Yellow)
+            .collect();
+        assert_eq!(
+            highlighted,
+            [false, true, true, true, true, true, true, true, true, false]
+        );
+        assert_eq!(buffer.get(16, 0).bg, Color::Reset);
+
+        // n and N jump between lines with matches
+        press(&mut state, KeyCode::Char('n'));
+        assert_eq!(state.paragraph_state.offset.y, 2);
+        press(&mut state, KeyCode::Char('N'));
+        assert_eq!(state.paragraph_state.offset.y, 2);
+        state.set_search(Some("starting"));
+        press(&mut state, KeyCode::Char('N'));
+        assert_eq!(state.paragraph_state.offset.y, 0);
+    }
+
+    #[test]
+    fn wraps_long_lines() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = RawLogState::new(&log, true);
+        toggle_wrap(&mut state);
+        let buffer = render_stateful(RawLog::default(), &mut state, 41, 4);
+        assert_eq!(
+            rows(&buffer),
+            [
+                "[10:00:00 INFO  SMAPI] Starting",
+                "[10:00:01 INFO  SMAPI] A very long line",
+                "that does not fit",
+                "[10:00:02 INFO  SMAPI] Done",
+            ]
+        );
+    }
+
+    #[test]
+    fn scrollbar_counts_lines_of_the_log_while_wrapping() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = RawLogState::new(&log, false);
+        toggle_wrap(&mut state);
+        let buffer = render_stateful(RawLog::default(), &mut state, 21, 3);
+        assert_eq!(state.paragraph_state.lines, 3);
+
+        // Parts of two of the three lines are visible
+        let bar: Vec<_> = (0..3)
+            .map(|y| buffer.get(20, y).fg == Color::White)
+            .collect();
+        assert_eq!(bar, [true, true, false]);
+        assert_eq!(
+            rows(&buffer),
+            [
+                "[10:00:00 INFO  SMAP█",
+                "I] Starting         █",
+                "[10:00:01 INFO  SMAP█",
+            ]
+        );
+    }
+}
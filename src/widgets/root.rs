@@ -1,46 +1,241 @@
 use crate::{
-    encoded_writer::EncodedWriter,
+    alias::Aliases,
+    ast::{Level, TimeOffset},
+    classify::MessageClassifier,
+    encoded_writer::{CommandPipe, EncodedWriter},
     events::AppEvent,
     log::Log,
+    state_file::SavedFilters,
     widgets::{
-        BindingDisplay, CommandInput, CommandInputState, Controls, ControlsState, FormattedLog,
-        FormattedLogState, IconPack, RawLog, RawLogState, State, WithLog,
+        BindingDisplay, Column, ColumnLayout, CommandInput, CommandInputState, Controls,
+        ControlsState, CopyFormat, FormattedLog, FormattedLogState, IconPack, Intro, MessageGaps,
+        RawLog, RawLogState, State, SummaryLog, SummaryState, Theme, WithLog,
     },
 };
+use anyhow::bail;
+use chrono::Local;
 use crossterm::event::{Event, KeyCode, KeyModifiers};
 use indexmap::IndexMap;
-use std::{marker::PhantomData, process::ChildStdin};
-use tracing::debug;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Write,
+    marker::PhantomData,
+    rc::Rc,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
 use tui::{
     buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     widgets::{Block, BorderType, Borders, StatefulWidget, Tabs, Widget},
 };
+use unicode_width::UnicodeWidthStr;
 
 #[derive(Clone, Debug, Default)]
 pub struct Root<'i> {
     marker: PhantomData<&'i Log>,
 }
 
+impl<'i> Root<'i> {
+    /// Gets the styles of active and inactive widgets.
+    fn styles() -> (Style, Style) {
+        let active_style = Style::default().fg(Color::White).bg(Color::Black);
+        let inactive_style = active_style.fg(Color::DarkGray);
+        (active_style, inactive_style)
+    }
+
+    /// Renders the contents of the selected tab.
+    fn render_selected_tab(area: Rect, buf: &mut Buffer, state: &mut RootState<'i>) {
+        let (active_style, inactive_style) = Self::styles();
+        match state.selected_tab {
+            SelectedTab::FormattedLog => {
+                // Draw formatted log
+                FormattedLog::default()
+                    .default_style(if state.selected_widget == SelectedWidget::Log {
+                        active_style
+                    } else {
+                        inactive_style
+                    })
+                    .show_colors(state.selected_widget == SelectedWidget::Log)
+                    .theme(state.theme.clone())
+                    .time_offset(state.time_offset)
+                    .auto_scroll_interval(state.auto_scroll_interval)
+                    .columns(state.columns.clone())
+                    .max_source_width(state.max_source_width)
+                    .max_message_width(state.max_message_width)
+                    .histogram_buckets(state.histogram_buckets)
+                    .classifiers(state.classifiers.clone())
+                    .render(area, buf, &mut state.formatted_log_state);
+            }
+            SelectedTab::RawLog => {
+                // Draw raw log
+                RawLog::default()
+                    .style(if state.selected_widget == SelectedWidget::Log {
+                        active_style
+                    } else {
+                        inactive_style
+                    })
+                    .auto_scroll_interval(state.auto_scroll_interval)
+                    .search_match_style(if state.selected_widget == SelectedWidget::Log {
+                        state.theme.search_match_style()
+                    } else {
+                        inactive_style
+                    })
+                    .render(area, buf, &mut state.raw_log_state);
+            }
+            SelectedTab::Summary => {
+                // Draw message counts
+                let show_colors = state.selected_widget == SelectedWidget::Log;
+                let style = if show_colors {
+                    active_style
+                } else {
+                    inactive_style
+                };
+                SummaryLog::default()
+                    .style(style)
+                    .selected_style(if show_colors {
+                        style.fg(Color::White).bg(state.theme.selection_color)
+                    } else {
+                        style
+                    })
+                    .show_colors(show_colors)
+                    .theme(state.theme.clone())
+                    .render(area, buf, &mut state.summary_state);
+            }
+        }
+    }
+
+    /// Renders a note about how the log is being followed at the end of the
+    /// tab bar, if it fits. This includes the heartbeat and how often the log
+    /// is polled, or that a snapshot is shown instead.
+    fn render_follow_note(state: &RootState<'i>, area: Rect, buf: &mut Buffer, style: Style) {
+        // Notices replace the note until the next key is pressed
+        if let Some(notice) = state.notice.as_deref() {
+            let notice_width = (notice.width() as u16).min(area.width);
+            buf.set_stringn(
+                area.right() - notice_width,
+                area.y,
+                notice,
+                notice_width as usize,
+                style,
+            );
+            return;
+        }
+
+        let heartbeat = state.heartbeat;
+        let note = match (heartbeat, state.poll_interval) {
+            _ if state.is_snapshot => "Snapshot, press B for the live log".to_string(),
+            (_, Some(poll_interval)) => format!("Polling every {}s", poll_interval.as_secs_f32()),
+            (Some(_), None) => "Following".to_string(),
+            (None, None) => return,
+        };
+        let note = match heartbeat {
+            Some(heartbeat) => format!("{} {note}", heartbeat.icon()),
+            None => note,
+        };
+        let note_width = note.width() as u16;
+        if note_width.saturating_mul(2) <= area.width {
+            buf.set_string(area.right().saturating_sub(note_width), area.y, note, style);
+        }
+    }
+}
+
+impl Root<'_> {
+    /// The width of the tab titles at the start of the tab bar.
+    const TABS_WIDTH: u16 = 22;
+
+    /// The most rows the command box grows to as long commands wrap, not
+    /// including its borders.
+    const MAX_COMMAND_ROWS: u16 = 5;
+
+    /// Renders the status of the selected tab on the bottom border of the
+    /// tabs: sources suggested for hiding on the left, and the line position
+    /// on the right if it's enabled. The position is only shown if it fits.
+    fn render_status(state: &RootState<'_>, tabs_area: Rect, buf: &mut Buffer, style: Style) {
+        if tabs_area.height <= 2 {
+            return;
+        }
+        let area = Rect::new(
+            tabs_area.x + 1,
+            tabs_area.bottom() - 1,
+            tabs_area.width.saturating_sub(2),
+            1,
+        );
+        let (noisy_sources, paragraph_state) = match state.selected_tab {
+            SelectedTab::FormattedLog => (
+                state.formatted_log_state.suggested_noisy_sources(),
+                Some(state.formatted_log_state.paragraph_state()),
+            ),
+            SelectedTab::RawLog => (&[][..], Some(state.raw_log_state.paragraph_state())),
+            SelectedTab::Summary => (&[][..], None),
+        };
+
+        let mut width = area.width;
+        if let Some(paragraph_state) = paragraph_state.filter(|_| state.show_line_count) {
+            let position = format!(" {} ", paragraph_state.position());
+            let position_width = position.width() as u16;
+            if position_width.saturating_mul(2) <= area.width {
+                width -= position_width;
+                buf.set_string(area.x + width, area.y, position, style);
+            }
+        }
+
+        if !noisy_sources.is_empty() {
+            let text = format!(" Noisy: {} (H to hide) ", noisy_sources.join(", "));
+            buf.set_stringn(area.x, area.y, text, width.into(), style);
+        }
+    }
+
+    /// Renders how long passed before the message at the top of the log
+    /// after the tab titles, leaving the other half of the tab bar for the
+    /// follow note.
+    fn render_gaps_note(gaps: Option<MessageGaps>, area: Rect, buf: &mut Buffer, style: Style) {
+        let Some(gaps) = gaps else {
+            return;
+        };
+        let width = (area.width / 2).saturating_sub(Self::TABS_WIDTH);
+        buf.set_stringn(
+            area.x + Self::TABS_WIDTH,
+            area.y,
+            gaps.to_string(),
+            width.into(),
+            style,
+        );
+    }
+}
+
 impl<'i> StatefulWidget for Root<'i> {
     type State = RootState<'i>;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         // Styles
-        let active_style = Style::default().fg(Color::White).bg(Color::Black);
-        let inactive_style = active_style.fg(Color::DarkGray);
+        let (active_style, inactive_style) = Self::styles();
 
-        // Get vertical layout
+        // Get vertical layout, growing the command box to fit long commands
+        let command_rows = state
+            .command_input_state
+            .as_ref()
+            .map(|(command_input_state, _)| {
+                CommandInput::rows(
+                    &state.command_prompt,
+                    command_input_state,
+                    area.width.saturating_sub(2),
+                )
+                .min(Self::MAX_COMMAND_ROWS)
+            });
         let mut layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints({
                 let mut constraints = Vec::with_capacity(3);
                 constraints.push(Constraint::Min(0));
-                if state.command_input_state.is_some() {
-                    constraints.push(Constraint::Length(3));
+                if let Some(command_rows) = command_rows {
+                    constraints.push(Constraint::Length(command_rows + 2));
                 }
-                constraints.push(Constraint::Length(1));
+                constraints.push(Constraint::Length(state.controls_rows));
                 constraints
             })
             .split(area);
@@ -61,7 +256,7 @@ impl<'i> StatefulWidget for Root<'i> {
             })
             .border_type(BorderType::Double);
         let log_inner_area = tabs_block.inner(log_area);
-        Tabs::new(vec!["Log".into(), "Raw".into()])
+        Tabs::new(vec!["Log".into(), "Raw".into(), "Summary".into()])
             .block(tabs_block)
             .style(active_style)
             .divider("|")
@@ -69,9 +264,25 @@ impl<'i> StatefulWidget for Root<'i> {
             .select(match state.selected_tab {
                 SelectedTab::FormattedLog => 0,
                 SelectedTab::RawLog => 1,
+                SelectedTab::Summary => 2,
             })
             .render(log_area, buf);
 
+        // Let the user know the log is being followed, and that updates may be
+        // delayed when the log is polled
+        Self::render_follow_note(state, log_inner_area, buf, inactive_style);
+
+        // Show how long passed before the message at the top of the log, to
+        // help spot stalls
+        if state.selected_tab == SelectedTab::FormattedLog {
+            Self::render_gaps_note(
+                state.formatted_log_state.current_message_gaps(),
+                log_inner_area,
+                buf,
+                inactive_style,
+            );
+        }
+
         // Draw selected tab's contents
         let log_inner_area = Rect {
             x: log_inner_area.x,
@@ -79,29 +290,11 @@ impl<'i> StatefulWidget for Root<'i> {
             width: log_inner_area.width,
             height: log_inner_area.height.saturating_sub(1),
         };
-        match state.selected_tab {
-            SelectedTab::FormattedLog => {
-                // Draw formatted log
-                FormattedLog::default()
-                    .default_style(if state.selected_widget == SelectedWidget::Log {
-                        active_style
-                    } else {
-                        inactive_style
-                    })
-                    .show_colors(state.selected_widget == SelectedWidget::Log)
-                    .render(log_inner_area, buf, &mut state.formatted_log_state);
-            }
-            SelectedTab::RawLog => {
-                // Draw raw log
-                RawLog::default()
-                    .style(if state.selected_widget == SelectedWidget::Log {
-                        active_style
-                    } else {
-                        inactive_style
-                    })
-                    .render(log_inner_area, buf, &mut state.raw_log_state);
-            }
-        }
+        Self::render_selected_tab(log_inner_area, buf, state);
+
+        // Show the status of the tab on the bottom border, out of the way of
+        // the log
+        Self::render_status(state, log_area, buf, inactive_style);
 
         // Draw command input
         if let Some((command_input_state, _)) = state.command_input_state.as_mut() {
@@ -121,6 +314,7 @@ impl<'i> StatefulWidget for Root<'i> {
                         .title("Command"),
                 )
                 .focused(focused)
+                .prompt(&state.command_prompt)
                 .render(command_input_area.unwrap(), buf, command_input_state);
         }
 
@@ -129,8 +323,13 @@ impl<'i> StatefulWidget for Root<'i> {
         state.add_controls(&mut controls);
         state.controls_state.set_controls(controls);
         Controls::default()
-            .style(Style::default().fg(Color::White).bg(Color::Blue))
+            .style(state.theme.controls_style())
             .render(controls_area, buf, &mut state.controls_state);
+
+        // Draw intro
+        if state.show_intro {
+            Intro::default().style(active_style).render(log_area, buf);
+        }
     }
 }
 
@@ -138,49 +337,348 @@ impl<'i> StatefulWidget for Root<'i> {
 pub struct RootState<'i> {
     formatted_log_state: FormattedLogState<'i>,
     raw_log_state: RawLogState<'i>,
-    command_input_state: Option<(CommandInputState, EncodedWriter<ChildStdin>)>,
+    summary_state: SummaryState<'i>,
+    command_input_state: Option<(CommandInputState, EncodedWriter<CommandPipe>)>,
     controls_state: ControlsState,
+    controls_rows: u16,
     selected_widget: SelectedWidget,
     selected_tab: SelectedTab,
+    show_intro: bool,
+    theme: Theme,
+    can_open_editor: bool,
+    open_editor_requested: bool,
+    /// Whether the user asked to save the screen to a file.
+    save_screen_requested: bool,
+    /// A notice shown in the tab bar, like where the screen was saved.
+    notice: Option<String>,
+    quit_requested: bool,
+    ctrl_c_always_quits: bool,
+    show_line_count: bool,
+    poll_interval: Option<Duration>,
+    command_prompt: String,
+    aliases: Aliases,
+    command_log: Option<File>,
+    time_offset: Option<TimeOffset>,
+    auto_scroll_interval: Duration,
+    columns: ColumnLayout,
+    max_source_width: Option<usize>,
+    max_message_width: Option<usize>,
+    histogram_buckets: usize,
+    classifiers: Rc<[Box<dyn MessageClassifier>]>,
+    bell_levels: Vec<Level>,
+    bell_count: usize,
+    last_bell: Option<Instant>,
+    bell_requested: bool,
+    heartbeat: Option<Heartbeat>,
+    /// Whether the log is followed for new messages.
+    following: bool,
+    /// Whether this is a read-only snapshot of a followed log.
+    is_snapshot: bool,
+    /// Whether there's a snapshot of the log to switch to.
+    has_snapshot: bool,
+    snapshot_request: Option<SnapshotRequest>,
 }
 
 impl<'i> RootState<'i> {
-    pub fn new(log: &'i Log, command_stdin: Option<EncodedWriter<ChildStdin>>) -> Self {
+    /// The minimum time between rings of the bell, so a crash loop doesn't
+    /// ring it constantly.
+    const BELL_INTERVAL: Duration = Duration::from_secs(10);
+
+    pub fn new(
+        log: &'i Log,
+        command_stdin: Option<EncodedWriter<CommandPipe>>,
+        options: RootOptions,
+    ) -> Self {
+        let classifiers: Rc<[_]> = options.classifiers.into();
         RootState {
-            raw_log_state: RawLogState::new(log),
-            formatted_log_state: FormattedLogState::new(log),
+            raw_log_state: RawLogState::new(log, options.following),
+            summary_state: SummaryState::new(log),
+            formatted_log_state: FormattedLogState::new(log)
+                .noisy_sources(options.noisy_sources)
+                .copy_format(options.copy_format)
+                .wrap_navigation(options.wrap_navigation)
+                .classifiers(classifiers.clone())
+                .follow(options.following)
+                .auto_expand(options.auto_expand)
+                .with_notes(options.notes)
+                .join_lines(options.join_lines)
+                .show_elapsed(options.columns.contains(&Column::Elapsed))
+                .group_sources_below(options.group_sources_below)
+                .error_highlight_duration(options.error_highlight_duration)
+                .with_saved_filters(options.saved_filters.as_ref())
+                .jump_to_first_error(options.jump_to_error),
             command_input_state: command_stdin.map(|stdin| (CommandInputState::default(), stdin)),
             controls_state: ControlsState::default(),
+            controls_rows: options.controls_rows,
             selected_widget: SelectedWidget::default(),
-            selected_tab: SelectedTab::default(),
+            selected_tab: options.tab,
+            show_intro: options.show_intro,
+            theme: options.theme,
+            can_open_editor: options.can_open_editor,
+            open_editor_requested: false,
+            save_screen_requested: false,
+            notice: None,
+            quit_requested: false,
+            ctrl_c_always_quits: options.ctrl_c_always_quits,
+            show_line_count: options.show_line_count,
+            poll_interval: options.poll_interval,
+            command_prompt: options.command_prompt,
+            aliases: options.aliases,
+            command_log: options.command_log,
+            time_offset: options.time_offset,
+            auto_scroll_interval: options.auto_scroll_interval,
+            columns: options.columns,
+            max_source_width: options.max_source_width,
+            max_message_width: options.max_message_width,
+            histogram_buckets: options.histogram_buckets,
+            classifiers,
+            bell_count: count_levels(log, &options.bell_levels),
+            bell_levels: options.bell_levels,
+            last_bell: None,
+            bell_requested: false,
+            heartbeat: options.heartbeat.then(Heartbeat::new),
+            following: options.following,
+            is_snapshot: false,
+            has_snapshot: false,
+            snapshot_request: None,
+        }
+    }
+
+    /// Creates a read-only copy of this state for a snapshot of the log. The
+    /// copy keeps the filters and scroll position, but can't send commands
+    /// and doesn't follow the log.
+    pub fn snapshot<'j>(&self, log: &'j Log) -> RootState<'j> {
+        RootState {
+            formatted_log_state: self.formatted_log_state.clone().with_log(log),
+            raw_log_state: self.raw_log_state.clone().with_log(log),
+            summary_state: self.summary_state.clone().with_log(log),
+            command_input_state: None,
+            controls_state: self.controls_state.clone(),
+            controls_rows: self.controls_rows,
+            selected_widget: SelectedWidget::Log,
+            selected_tab: self.selected_tab,
+            show_intro: false,
+            theme: self.theme.clone(),
+            can_open_editor: false,
+            open_editor_requested: false,
+            save_screen_requested: false,
+            notice: None,
+            quit_requested: false,
+            ctrl_c_always_quits: self.ctrl_c_always_quits,
+            show_line_count: self.show_line_count,
+            poll_interval: None,
+            command_prompt: self.command_prompt.clone(),
+            aliases: self.aliases.clone(),
+            command_log: None,
+            time_offset: self.time_offset,
+            auto_scroll_interval: self.auto_scroll_interval,
+            columns: self.columns.clone(),
+            max_source_width: self.max_source_width,
+            max_message_width: self.max_message_width,
+            histogram_buckets: self.histogram_buckets,
+            classifiers: self.classifiers.clone(),
+            bell_levels: Vec::new(),
+            bell_count: 0,
+            last_bell: None,
+            bell_requested: false,
+            heartbeat: None,
+            following: false,
+            is_snapshot: true,
+            has_snapshot: false,
+            snapshot_request: None,
+        }
+    }
+
+    /// Sets whether there's a snapshot of the log to switch to.
+    pub fn set_has_snapshot(&mut self, has_snapshot: bool) {
+        self.has_snapshot = has_snapshot;
+    }
+
+    /// Checks whether the user asked to take or switch to a snapshot of the
+    /// log, resetting the request.
+    pub fn take_snapshot_request(&mut self) -> Option<SnapshotRequest> {
+        self.snapshot_request.take()
+    }
+
+    /// Checks whether the view shouldn't change right now, like while the
+    /// filters are being edited. New logs are buffered until then.
+    pub fn is_frozen(&self) -> bool {
+        self.selected_tab == SelectedTab::FormattedLog
+            && self.formatted_log_state.is_editing_filters()
+    }
+
+    /// Sets the number of new messages waiting to be shown.
+    pub fn set_buffered_messages(&mut self, buffered_messages: usize) {
+        self.formatted_log_state
+            .set_buffered_messages(buffered_messages);
+    }
+
+    /// Checks whether new messages arrived that should ring the bell,
+    /// resetting the request.
+    pub fn take_bell_request(&mut self) -> bool {
+        std::mem::take(&mut self.bell_requested)
+    }
+
+    /// Checks whether the user asked to open the log file in an external
+    /// editor, resetting the request.
+    pub fn take_open_editor_request(&mut self) -> bool {
+        std::mem::take(&mut self.open_editor_requested)
+    }
+
+    /// Sends the commands submitted in the command input to SMAPI, logging
+    /// them if there is a command log.
+    fn send_commands(&mut self) {
+        if let Some((command_input_state, stdin)) = self.command_input_state.as_mut() {
+            for cmd in command_input_state.take_submitted() {
+                let cmd = self.aliases.expand(&cmd);
+                debug!(?cmd, "sending command");
+                drop(stdin.write_all(&cmd));
+                drop(stdin.write_all("\n"));
+                drop(stdin.flush());
+
+                // Log the command without interrupting sending commands if it fails
+                if let Some(command_log) = self.command_log.as_mut() {
+                    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
+                    let result = writeln!(command_log, "[{timestamp}] {cmd}")
+                        .and_then(|()| command_log.flush());
+                    if let Err(error) = result {
+                        warn!(
+                            ?error,
+                            "error writing command log, no longer logging commands"
+                        );
+                        self.command_log = None;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks whether the user asked to save the screen, resetting the
+    /// request.
+    pub fn take_save_screen_request(&mut self) -> bool {
+        std::mem::take(&mut self.save_screen_requested)
+    }
+
+    /// Shows a notice in the tab bar until the next key is pressed.
+    pub fn set_notice(&mut self, notice: String) {
+        self.notice = Some(notice);
+    }
+
+    /// Checks whether the user asked to copy text to the clipboard, resetting
+    /// the request.
+    pub fn take_copy_request(&mut self) -> Option<String> {
+        self.formatted_log_state.take_copy_request()
+    }
+
+    /// Replaces the notes attached to messages.
+    pub fn set_notes(&mut self, notes: HashMap<usize, String>) {
+        self.formatted_log_state.set_notes(notes);
+    }
+
+    /// Checks whether the notes on messages changed, resetting the check.
+    pub fn take_changed_notes(&mut self) -> Option<&HashMap<usize, String>> {
+        self.formatted_log_state.take_changed_notes()
+    }
+
+    /// Gets the level and source filters to save for the next session.
+    pub fn saved_filters(&self) -> SavedFilters {
+        self.formatted_log_state.saved_filters()
+    }
+
+    /// Checks whether the user asked to quit, resetting the request.
+    pub fn take_quit_request(&mut self) -> bool {
+        std::mem::take(&mut self.quit_requested)
+    }
+
+    /// Checks whether Ctrl+C clears the command input instead of quitting.
+    fn ctrl_c_clears_input(&self) -> bool {
+        !self.ctrl_c_always_quits && self.selected_widget == SelectedWidget::CommandInput
+    }
+
+    /// Handles Ctrl+C. While typing a command, this clears the command like
+    /// in a shell, and returns to the log if it's already empty. Otherwise,
+    /// this quits.
+    fn interrupt(&mut self) {
+        if !self.ctrl_c_clears_input() {
+            self.quit_requested = true;
+            return;
+        }
+
+        match self.command_input_state.as_mut() {
+            Some((state, _)) if !state.text().is_empty() => state.set_text(String::new()),
+            _ => self.selected_widget = SelectedWidget::Log,
         }
     }
 }
 
-impl<'i> State for RootState<'i> {
+impl State for RootState<'_> {
     fn update(&mut self, event: &AppEvent) -> bool {
-        // TODO: mouse events
-        // Update root state
+        if let AppEvent::TermEvent(Event::Key(key_event)) = event {
+            if key_event.code == KeyCode::Char('c') && key_event.modifiers == KeyModifiers::CONTROL
+            {
+                self.interrupt();
+                return true;
+            }
+        }
+
+        // Dismiss notices with any key, letting the key through
+        let dismissed_notice =
+            matches!(event, AppEvent::TermEvent(Event::Key(_))) && self.notice.take().is_some();
+
+        // Dismiss the intro with any key
+        if self.show_intro {
+            if let AppEvent::TermEvent(Event::Key(_)) = event {
+                self.show_intro = false;
+                return true;
+            }
+        }
+
+        // Animate the heartbeat. Pings still need to reach the log below.
+        let ticked =
+            matches!(event, AppEvent::Ping) && self.heartbeat.as_mut().is_some_and(Heartbeat::tick);
+
+        // Update root state, leaving keys for prompts in the log alone
+        let typing =
+            self.selected_tab == SelectedTab::FormattedLog && self.formatted_log_state.is_typing();
         let mut handled = match event {
+            AppEvent::TermEvent(Event::Key(_)) if typing => false,
             AppEvent::TermEvent(Event::Key(key_event)) => match key_event.code {
                 KeyCode::Tab if self.selected_widget == SelectedWidget::Log => {
-                    self.selected_tab = match self.selected_tab {
-                        SelectedTab::FormattedLog => SelectedTab::RawLog,
-                        SelectedTab::RawLog => SelectedTab::FormattedLog,
-                    };
+                    self.selected_tab = self.selected_tab.next();
                     true
                 }
                 KeyCode::BackTab if self.selected_widget == SelectedWidget::Log => {
-                    self.selected_tab = match self.selected_tab {
-                        SelectedTab::FormattedLog => SelectedTab::RawLog,
-                        SelectedTab::RawLog => SelectedTab::FormattedLog,
-                    };
+                    self.selected_tab = self.selected_tab.previous();
+                    true
+                }
+                KeyCode::Char('o')
+                    if self.selected_widget == SelectedWidget::Log && self.can_open_editor =>
+                {
+                    self.open_editor_requested = true;
+                    true
+                }
+                KeyCode::Char('P') if self.selected_widget == SelectedWidget::Log => {
+                    self.save_screen_requested = true;
                     true
                 }
                 KeyCode::Char('i') if self.selected_widget == SelectedWidget::Log => {
                     self.selected_widget = SelectedWidget::CommandInput;
                     true
                 }
+                KeyCode::Char('S')
+                    if self.selected_widget == SelectedWidget::Log
+                        && (self.following || self.is_snapshot) =>
+                {
+                    self.snapshot_request = Some(SnapshotRequest::Take);
+                    true
+                }
+                KeyCode::Char('B')
+                    if self.selected_widget == SelectedWidget::Log
+                        && (self.has_snapshot || self.is_snapshot) =>
+                {
+                    self.snapshot_request = Some(SnapshotRequest::Switch);
+                    true
+                }
                 KeyCode::Esc if self.selected_widget == SelectedWidget::CommandInput => {
                     self.selected_widget = SelectedWidget::Log;
                     true
@@ -192,42 +690,58 @@ impl<'i> State for RootState<'i> {
 
         // Update selected widget
         if !handled {
-            handled = match self.selected_widget {
+            // Mouse events go to the log since it's what's under the cursor, and
+            // the log needs to know about pings to catch up on new lines
+            let target = match event {
+                AppEvent::Ping | AppEvent::TermEvent(Event::Mouse(_)) => SelectedWidget::Log,
+                AppEvent::TermEvent(_) => self.selected_widget,
+            };
+            handled = match target {
                 SelectedWidget::Log => match self.selected_tab {
                     SelectedTab::FormattedLog => self.formatted_log_state.update(event),
                     SelectedTab::RawLog => self.raw_log_state.update(event),
+                    SelectedTab::Summary => self.summary_state.update(event),
                 },
                 SelectedWidget::CommandInput => self
                     .command_input_state
                     .as_mut()
-                    .map_or(false, |(state, _)| state.update(event)),
+                    .is_some_and(|(state, _)| state.update(event)),
             };
         }
 
-        // Send commands if any
-        if let Some((command_input_state, stdin)) = self.command_input_state.as_mut() {
-            for cmd in command_input_state.take_submitted() {
-                debug!(?cmd, "sending command");
-                drop(stdin.write_all(&cmd));
-                drop(stdin.write_all("\n"));
-                drop(stdin.flush());
-            }
+        // Show the source picked in the summary in the log
+        if let Some(index) = self.summary_state.take_jump_request() {
+            self.formatted_log_state.scroll_to_message(index);
+            self.selected_tab = SelectedTab::FormattedLog;
         }
 
+        // Highlight the search in both tabs
+        self.raw_log_state
+            .set_search(self.formatted_log_state.search());
+        self.send_commands();
+
         // Update controls state
         if !handled {
             handled = self.controls_state.update(event);
         }
 
-        handled
+        handled || ticked || dismissed_notice
     }
 
     fn add_controls<I: IconPack>(&self, controls: &mut IndexMap<BindingDisplay<I>, &'static str>) {
         // Root controls
         controls.insert(
             BindingDisplay::key(KeyCode::Char('c'), KeyModifiers::CONTROL),
-            "Quit",
+            if self.ctrl_c_clears_input() {
+                "Clear"
+            } else {
+                "Quit"
+            },
         );
+        if self.show_intro {
+            controls.insert(BindingDisplay::Custom("Any key"), "Dismiss");
+            return;
+        }
 
         // Selected widget controls
         match self.selected_widget {
@@ -237,10 +751,29 @@ impl<'i> State for RootState<'i> {
                 if self.command_input_state.is_some() {
                     controls.insert(BindingDisplay::simple_key(KeyCode::Char('i')), "Command");
                 }
+                if self.can_open_editor {
+                    controls.insert(BindingDisplay::simple_key(KeyCode::Char('o')), "Edit");
+                }
+                controls.insert(
+                    BindingDisplay::simple_key(KeyCode::Char('P')),
+                    "Save screen",
+                );
+                if self.following || self.is_snapshot {
+                    controls.insert(BindingDisplay::simple_key(KeyCode::Char('S')), "Snapshot");
+                }
+                if self.is_snapshot {
+                    controls.insert(BindingDisplay::simple_key(KeyCode::Char('B')), "Live log");
+                } else if self.has_snapshot {
+                    controls.insert(
+                        BindingDisplay::simple_key(KeyCode::Char('B')),
+                        "Back to snapshot",
+                    );
+                }
 
                 match self.selected_tab {
                     SelectedTab::FormattedLog => self.formatted_log_state.add_controls(controls),
                     SelectedTab::RawLog => self.raw_log_state.add_controls(controls),
+                    SelectedTab::Summary => self.summary_state.add_controls(controls),
                 }
             }
             SelectedWidget::CommandInput => {
@@ -253,41 +786,315 @@ impl<'i> State for RootState<'i> {
     }
 }
 
-impl<'i, 'j> WithLog<'j> for RootState<'i> {
+impl<'j> WithLog<'j> for RootState<'_> {
     type Result = RootState<'j>;
 
     fn with_log(self, log: &'j Log) -> Self::Result {
+        // Ring the bell for new messages, unless it rang recently
+        let bell_count = count_levels(log, &self.bell_levels);
+        let mut bell_requested = self.bell_requested;
+        let mut last_bell = self.last_bell;
+        let bell_ready =
+            last_bell.is_none_or(|last_bell| last_bell.elapsed() >= RootState::BELL_INTERVAL);
+        if bell_count > self.bell_count && bell_ready {
+            bell_requested = true;
+            last_bell = Some(Instant::now());
+        }
+
         RootState {
             formatted_log_state: self.formatted_log_state.with_log(log),
             raw_log_state: self.raw_log_state.with_log(log),
+            summary_state: self.summary_state.with_log(log),
             command_input_state: self.command_input_state,
             controls_state: self.controls_state,
+            controls_rows: self.controls_rows,
             selected_widget: self.selected_widget,
             selected_tab: self.selected_tab,
+            show_intro: self.show_intro,
+            theme: self.theme,
+            can_open_editor: self.can_open_editor,
+            open_editor_requested: self.open_editor_requested,
+            save_screen_requested: self.save_screen_requested,
+            notice: self.notice,
+            quit_requested: self.quit_requested,
+            ctrl_c_always_quits: self.ctrl_c_always_quits,
+            show_line_count: self.show_line_count,
+            poll_interval: self.poll_interval,
+            command_prompt: self.command_prompt,
+            aliases: self.aliases,
+            command_log: self.command_log,
+            time_offset: self.time_offset,
+            auto_scroll_interval: self.auto_scroll_interval,
+            columns: self.columns,
+            max_source_width: self.max_source_width,
+            max_message_width: self.max_message_width,
+            histogram_buckets: self.histogram_buckets,
+            classifiers: self.classifiers,
+            bell_levels: self.bell_levels,
+            bell_count,
+            last_bell,
+            bell_requested,
+            heartbeat: self.heartbeat,
+            following: self.following,
+            is_snapshot: self.is_snapshot,
+            has_snapshot: self.has_snapshot,
+            snapshot_request: self.snapshot_request,
         }
     }
 }
 
+/// What the user asked to do with snapshots of a followed log.
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
-enum SelectedTab {
+pub enum SnapshotRequest {
+    /// Take a new snapshot of the live log and show it.
+    Take,
+    /// Switch between the snapshot and the live log.
+    Switch,
+}
+
+/// A spinner showing that the log is still being followed.
+#[derive(Clone, Copy, Debug)]
+struct Heartbeat {
+    frame: usize,
+    last_tick: Instant,
+}
+
+impl Heartbeat {
+    const FRAMES: [&'static str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+    /// How long each frame is shown. Pings arrive much more often than this,
+    /// and redrawing on each one would be wasteful.
+    const TICK_INTERVAL: Duration = Duration::from_millis(250);
+
+    fn new() -> Self {
+        Heartbeat {
+            frame: 0,
+            last_tick: Instant::now(),
+        }
+    }
+
+    /// Advances to the next frame if it's time to, returning whether it did.
+    fn tick(&mut self) -> bool {
+        if self.last_tick.elapsed() < Self::TICK_INTERVAL {
+            return false;
+        }
+
+        self.frame = (self.frame + 1) % Self::FRAMES.len();
+        self.last_tick = Instant::now();
+        true
+    }
+
+    fn icon(self) -> &'static str {
+        Self::FRAMES[self.frame]
+    }
+}
+
+/// Counts the messages in a log with any of the given levels.
+fn count_levels(log: &Log, levels: &[Level]) -> usize {
+    levels
+        .iter()
+        .map(|&level| log.messages_by_level(level).count())
+        .sum()
+}
+
+/// Options for creating a [`RootState`].
+#[derive(Debug, Default)]
+pub struct RootOptions {
+    /// Whether to show the welcome screen.
+    pub show_intro: bool,
+    /// The theme to render the log with.
+    pub theme: Theme,
+    /// Whether the log can be opened in an external editor.
+    pub can_open_editor: bool,
+    /// Whether to show the current line and total line count.
+    pub show_line_count: bool,
+    /// How often the log is checked for changes, if it's checked by polling.
+    pub poll_interval: Option<Duration>,
+    /// The prompt displayed before commands sent to SMAPI.
+    pub command_prompt: String,
+    /// Aliases expanded in commands before they're sent to SMAPI.
+    pub aliases: Aliases,
+    /// The file to log commands sent to SMAPI to.
+    pub command_log: Option<File>,
+    /// The offset to shift displayed timestamps by.
+    pub time_offset: Option<TimeOffset>,
+    /// The minimum time between auto-scrolling to new lines.
+    pub auto_scroll_interval: Duration,
+    /// Sources hidden by the "important only" preset.
+    pub noisy_sources: Vec<String>,
+    /// What to include when copying a message.
+    pub copy_format: CopyFormat,
+    /// The separator that joins the lines of a message when it's copied, if
+    /// any.
+    pub join_lines: Option<String>,
+    /// Whether jumping past the last match wraps around to the first.
+    pub wrap_navigation: bool,
+    /// Ring the terminal bell when new messages with these levels arrive.
+    pub bell_levels: Vec<Level>,
+    /// The columns shown before each message in the formatted log.
+    pub columns: ColumnLayout,
+    /// The maximum width of the source column, if any.
+    pub max_source_width: Option<usize>,
+    /// The maximum width of each line of a message, if any.
+    pub max_message_width: Option<usize>,
+    /// The number of bars in the histogram of a source's messages.
+    pub histogram_buckets: usize,
+    /// Classifiers that tag messages with a category, checked in order.
+    pub classifiers: Vec<Box<dyn MessageClassifier>>,
+    /// Whether the log is followed for new messages. Followed logs start
+    /// scrolled to the end and keep scrolling as messages arrive.
+    pub following: bool,
+    /// Whether to show a spinner while the log is being followed.
+    pub heartbeat: bool,
+    /// The number of rows to show controls on.
+    pub controls_rows: u16,
+    /// The tab that is selected first.
+    pub tab: SelectedTab,
+    /// Whether Ctrl+C quits even while typing a command, instead of clearing
+    /// the command.
+    pub ctrl_c_always_quits: bool,
+    /// Whether to expand the message at the top of the view while the others
+    /// are collapsed.
+    pub auto_expand: bool,
+    /// Notes attached to messages, keyed by the index of the message.
+    pub notes: HashMap<usize, String>,
+    /// Sources with fewer messages than this are grouped together in the
+    /// filters.
+    pub group_sources_below: usize,
+    /// How long the source of the latest error is highlighted.
+    pub error_highlight_duration: Duration,
+    /// The filters saved in a previous session, if any.
+    pub saved_filters: Option<SavedFilters>,
+    /// Whether to start at the first error instead of the top or end of the
+    /// log.
+    pub jump_to_error: bool,
+}
+
+/// A tab showing the log.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub enum SelectedTab {
+    /// The parsed log, with filters and search.
+    #[default]
     FormattedLog,
+    /// The log exactly as it was written.
     RawLog,
+    /// How many messages each source logged.
+    Summary,
 }
 
-impl Default for SelectedTab {
-    fn default() -> Self {
-        SelectedTab::FormattedLog
+impl SelectedTab {
+    /// Gets the tab after this one, wrapping around to the first tab.
+    fn next(self) -> Self {
+        match self {
+            SelectedTab::FormattedLog => SelectedTab::RawLog,
+            SelectedTab::RawLog => SelectedTab::Summary,
+            SelectedTab::Summary => SelectedTab::FormattedLog,
+        }
+    }
+
+    /// Gets the tab before this one, wrapping around to the last tab.
+    fn previous(self) -> Self {
+        match self {
+            SelectedTab::FormattedLog => SelectedTab::Summary,
+            SelectedTab::RawLog => SelectedTab::FormattedLog,
+            SelectedTab::Summary => SelectedTab::RawLog,
+        }
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+impl FromStr for SelectedTab {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "log" | "formatted" => Ok(SelectedTab::FormattedLog),
+            "raw" => Ok(SelectedTab::RawLog),
+            "summary" => Ok(SelectedTab::Summary),
+            _ => bail!("unknown tab '{s}', expected one of: log, raw, summary"),
+        }
+    }
+}
+
+impl TryFrom<String> for SelectedTab {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
 enum SelectedWidget {
+    #[default]
     Log,
     CommandInput,
 }
 
-impl Default for SelectedWidget {
-    fn default() -> Self {
-        SelectedWidget::Log
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::test_utils::{key, render_stateful, rows};
+
+    const LOG: &str = "\
+[10:00:00 INFO  SMAPI] Starting
+[10:00:01 INFO  SMAPI] Loaded mods
+";
+
+    #[test]
+    fn shows_notice_after_saving_screen() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = RootState::new(&log, None, RootOptions::default());
+        state.update(&key(KeyCode::Char('P')));
+        assert!(state.take_save_screen_request());
+        assert!(!state.take_save_screen_request());
+
+        state.set_notice("Saved screen to screen.ans".to_string());
+        let buffer = render_stateful(Root::default(), &mut state, 60, 6);
+        assert!(rows(&buffer)[1].ends_with("Saved screen to screen.ans║"));
+
+        // Any key dismisses the notice
+        state.update(&key(KeyCode::Down));
+        let buffer = render_stateful(Root::default(), &mut state, 60, 6);
+        assert!(!rows(&buffer)[1].contains("Saved screen"));
+    }
+
+    #[test]
+    fn shows_status_on_border() {
+        let mut raw = LOG.to_string();
+        for _ in 0..30 {
+            raw.push_str("[10:00:02 TRACE SpaceCore] Patching\n");
+        }
+        let log = Log::parse(raw).unwrap();
+        let options = RootOptions {
+            show_line_count: true,
+            ..RootOptions::default()
+        };
+        let mut state = RootState::new(&log, None, options);
+        let buffer = render_stateful(Root::default(), &mut state, 60, 8);
+        assert_eq!(
+            rows(&buffer)[7],
+            "╚ Noisy: SpaceCore (H to hide) ══════════════════════ 1/32 ╝"
+        );
+
+        // The raw tab shows its position too, but suggestions are only for
+        // the log tab
+        state.update(&key(KeyCode::Tab));
+        assert_eq!(state.selected_tab, SelectedTab::RawLog);
+        let buffer = render_stateful(Root::default(), &mut state, 60, 8);
+        assert!(rows(&buffer)[7].starts_with("╚════"));
+        assert!(rows(&buffer)[7].ends_with("═ 1/32 ╝"));
+    }
+
+    #[test]
+    fn summary_shows_source_in_log() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = RootState::new(&log, None, RootOptions::default());
+        state.update(&key(KeyCode::BackTab));
+        assert_eq!(state.selected_tab, SelectedTab::Summary);
+        let buffer = render_stateful(Root::default(), &mut state, 80, 8);
+        assert!(rows(&buffer)[4].contains("SMAPI"));
+
+        state.update(&key(KeyCode::Enter));
+        assert_eq!(state.selected_tab, SelectedTab::FormattedLog);
     }
 }
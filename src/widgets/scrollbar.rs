@@ -58,3 +58,41 @@ impl Widget for Scrollbar {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::test_utils::render;
+
+    fn bar_rows(buffer: &Buffer) -> Vec<bool> {
+        (0..buffer.area.height)
+            .map(|y| buffer.get(0, y).fg == Color::White)
+            .collect()
+    }
+
+    #[test]
+    fn renders_bar_over_track() {
+        let buffer = render(Scrollbar::new(5.0..10.0, 20.0), 1, 4);
+        assert!((0..4).all(|y| buffer.get(0, y).symbol == FULL));
+        assert_eq!(bar_rows(&buffer), [false, true, false, false]);
+        assert_eq!(buffer.get(0, 0).fg, Color::DarkGray);
+    }
+
+    #[test]
+    fn keeps_bar_inside_track_at_end() {
+        let buffer = render(Scrollbar::new(18.0..20.0, 20.0), 1, 4);
+        assert_eq!(bar_rows(&buffer), [false, false, false, true]);
+    }
+
+    #[test]
+    fn fills_track_when_everything_is_visible() {
+        let buffer = render(Scrollbar::new(0.0..3.0, 3.0), 1, 4);
+        assert_eq!(bar_rows(&buffer), [true, true, true, true]);
+    }
+
+    #[test]
+    fn shows_at_least_one_row_of_bar() {
+        let buffer = render(Scrollbar::new(500.0..501.0, 1000.0), 1, 4);
+        assert_eq!(bar_rows(&buffer), [false, false, true, false]);
+    }
+}
@@ -0,0 +1,423 @@
+use crate::{
+    ast::{Level, Message},
+    events::AppEvent,
+    log::Log,
+    widgets::{fit_width, BindingDisplay, IconPack, State, Theme, WithLog},
+};
+use crossterm::event::{Event, KeyCode};
+use indexmap::IndexMap;
+use std::marker::PhantomData;
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, StatefulWidget, Widget},
+};
+use unicode_width::UnicodeWidthStr;
+
+/// A table of how many messages each source logged at each level.
+#[derive(Clone, Debug, Default)]
+pub struct SummaryLog<'i> {
+    style: Style,
+    selected_style: Style,
+    show_colors: bool,
+    theme: Theme,
+    marker: PhantomData<&'i Log>,
+}
+
+impl SummaryLog<'_> {
+    /// The width of each count column.
+    const COUNT_WIDTH: usize = 7;
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn selected_style(mut self, style: Style) -> Self {
+        self.selected_style = style;
+        self
+    }
+
+    pub fn show_colors(mut self, show_colors: bool) -> Self {
+        self.show_colors = show_colors;
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Renders a row of the table. Cells are rendered in their own styles,
+    /// or in the row's style if there is none.
+    fn render_row(
+        cells: impl IntoIterator<Item = (String, Option<Style>)>,
+        source_width: usize,
+        area: Rect,
+        buf: &mut Buffer,
+        style: Style,
+    ) {
+        let spans: Vec<_> = cells
+            .into_iter()
+            .enumerate()
+            .map(|(column, (text, cell_style))| {
+                // Sources are padded by their width in terminal cells, since
+                // wide characters take up two
+                let text = if column == 0 {
+                    let text = fit_width(&text, source_width);
+                    let padding = source_width.saturating_sub(text.width());
+                    format!("{text}{:padding$}", "")
+                } else {
+                    format!("{text:>width$}", width = Self::COUNT_WIDTH)
+                };
+                Span::styled(text, cell_style.unwrap_or(style))
+            })
+            .collect();
+        buf.set_style(area, style);
+        buf.set_spans(area.x, area.y, &Spans::from(spans), area.width);
+    }
+}
+
+impl<'i> StatefulWidget for SummaryLog<'i> {
+    type State = SummaryState<'i>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        // Render block
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(self.style)
+            .title(format!(
+                "Sources ({}), by {}",
+                state.rows.len(),
+                state.sort.description()
+            ));
+        let inner_area = block.inner(area);
+        buf.set_style(area, self.style);
+        block.render(area, buf);
+        if state.rows.is_empty() {
+            buf.set_stringn(
+                inner_area.x,
+                inner_area.y,
+                "No messages found",
+                inner_area.width.into(),
+                self.style,
+            );
+            return;
+        }
+
+        // The source column gets whatever space the counts don't need
+        let counts_width = (Level::ALL.len() + 1) * Self::COUNT_WIDTH;
+        let longest_source = state
+            .rows
+            .iter()
+            .map(|row| row.source.width())
+            .max()
+            .unwrap_or_default();
+        let source_width = longest_source
+            .max("Source".len())
+            .min(usize::from(inner_area.width).saturating_sub(counts_width));
+
+        // Render header
+        let header = std::iter::once(("Source".to_string(), None))
+            .chain(Level::ALL.iter().map(|level| (level.to_string(), None)))
+            .chain(std::iter::once(("Total".to_string(), None)));
+        let header_area = Rect::new(inner_area.x, inner_area.y, inner_area.width, 1);
+        Self::render_row(header, source_width, header_area, buf, self.style);
+
+        // Keep the selected row visible
+        let height = usize::from(inner_area.height.saturating_sub(1));
+        let start = state.selected.saturating_sub(height.saturating_sub(1));
+        let visible = state.rows.iter().enumerate().skip(start).take(height);
+        for ((index, row), y) in visible.zip(inner_area.y + 1..) {
+            let style = if index == state.selected {
+                self.selected_style
+            } else {
+                self.style
+            };
+            let level_style = |level: Level, count: usize| {
+                (self.show_colors && index != state.selected && count > 0)
+                    .then(|| self.theme.level_style(style, level))
+            };
+            let cells = std::iter::once((
+                row.source.to_string(),
+                (self.show_colors && index != state.selected)
+                    .then(|| style.fg(self.theme.source_color)),
+            ))
+            .chain(
+                Level::ALL
+                    .iter()
+                    .zip(row.counts)
+                    .map(|(&level, count)| (count.to_string(), level_style(level, count))),
+            )
+            .chain(std::iter::once((
+                row.total.to_string(),
+                (index != state.selected).then(|| style.fg(Color::White)),
+            )));
+            let row_area = Rect::new(inner_area.x, y, inner_area.width, 1);
+            Self::render_row(cells, source_width, row_area, buf, style);
+        }
+    }
+}
+
+/// How the rows of the summary are sorted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum SummarySort {
+    /// Sources with the most messages come first.
+    #[default]
+    Total,
+    /// Sources with the most errors come first.
+    Errors,
+}
+
+impl SummarySort {
+    fn description(self) -> &'static str {
+        match self {
+            SummarySort::Total => "total",
+            SummarySort::Errors => "errors",
+        }
+    }
+}
+
+/// The message counts of a source.
+#[derive(Clone, Debug)]
+struct SummaryRow<'i> {
+    source: &'i str,
+    /// The number of messages with each level in [`Level::ALL`].
+    counts: [usize; Level::ALL.len()],
+    /// The number of messages with any level, including unknown levels.
+    total: usize,
+    first_message: &'i Message<'i>,
+}
+
+impl SummaryRow<'_> {
+    fn errors(&self) -> usize {
+        Level::ALL
+            .iter()
+            .zip(self.counts)
+            .find_map(|(&level, count)| (level == Level::Error).then_some(count))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SummaryState<'i> {
+    log: &'i Log,
+    rows: Vec<SummaryRow<'i>>,
+    selected: usize,
+    sort: SummarySort,
+    /// The index of the message the user asked to see in the log, if any.
+    jump_request: Option<usize>,
+}
+
+impl<'i> SummaryState<'i> {
+    pub fn new(log: &'i Log) -> Self {
+        let sort = SummarySort::default();
+        SummaryState {
+            log,
+            rows: Self::count_messages(log, sort),
+            selected: 0,
+            sort,
+            jump_request: None,
+        }
+    }
+
+    fn count_messages(log: &'i Log, sort: SummarySort) -> Vec<SummaryRow<'i>> {
+        let mut rows: Vec<_> = log
+            .sources()
+            .filter_map(|source| {
+                let messages = log.messages_by_source(source);
+                let mut counts = [0; Level::ALL.len()];
+                for message in messages {
+                    if let Some(column) = Level::ALL.iter().position(|&l| l == message.level) {
+                        counts[column] += 1;
+                    }
+                }
+                Some(SummaryRow {
+                    source,
+                    counts,
+                    total: messages.len(),
+                    first_message: messages.first()?,
+                })
+            })
+            .collect();
+        Self::sort_rows(&mut rows, sort);
+        rows
+    }
+
+    fn sort_rows(rows: &mut [SummaryRow<'i>], sort: SummarySort) {
+        rows.sort_by(|a, b| {
+            let by_sort = match sort {
+                SummarySort::Total => b.total.cmp(&a.total),
+                SummarySort::Errors => b.errors().cmp(&a.errors()).then(b.total.cmp(&a.total)),
+            };
+            by_sort.then_with(|| a.source.cmp(b.source))
+        });
+    }
+
+    /// Gets the source of the selected row, if any.
+    fn selected_source(&self) -> Option<&'i str> {
+        self.rows.get(self.selected).map(|row| row.source)
+    }
+
+    /// Selects the row for a source, or the closest row if it's gone.
+    fn select_source(&mut self, source: Option<&str>) {
+        self.selected = source
+            .and_then(|source| self.rows.iter().position(|row| row.source == source))
+            .unwrap_or(self.selected)
+            .min(self.rows.len().saturating_sub(1));
+    }
+
+    /// Checks whether the user asked to see a source's first message in the
+    /// log, resetting the request. Returns the index of the message.
+    pub fn take_jump_request(&mut self) -> Option<usize> {
+        self.jump_request.take()
+    }
+}
+
+impl State for SummaryState<'_> {
+    fn update(&mut self, event: &AppEvent) -> bool {
+        match event {
+            AppEvent::TermEvent(Event::Key(key_event)) => match key_event.code {
+                KeyCode::Up => {
+                    self.selected = self.selected.saturating_sub(1);
+                    true
+                }
+                KeyCode::Down => {
+                    self.selected = self
+                        .selected
+                        .saturating_add(1)
+                        .min(self.rows.len().saturating_sub(1));
+                    true
+                }
+                KeyCode::Char('s') => {
+                    // Keep the same source selected after sorting
+                    let source = self.selected_source();
+                    self.sort = match self.sort {
+                        SummarySort::Total => SummarySort::Errors,
+                        SummarySort::Errors => SummarySort::Total,
+                    };
+                    Self::sort_rows(&mut self.rows, self.sort);
+                    self.select_source(source);
+                    true
+                }
+                KeyCode::Enter => {
+                    let Some(row) = self.rows.get(self.selected) else {
+                        return false;
+                    };
+                    self.jump_request = self
+                        .log
+                        .messages()
+                        .iter()
+                        .position(|message| std::ptr::eq(message, row.first_message));
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn add_controls<I: IconPack>(&self, controls: &mut IndexMap<BindingDisplay<I>, &'static str>) {
+        controls.insert(BindingDisplay::Custom(I::UP_DOWN), "Nav");
+        controls.insert(BindingDisplay::simple_key(KeyCode::Enter), "Show in log");
+        controls.insert(
+            BindingDisplay::simple_key(KeyCode::Char('s')),
+            match self.sort {
+                SummarySort::Total => "Sort by errors",
+                SummarySort::Errors => "Sort by total",
+            },
+        );
+    }
+}
+
+impl<'j> WithLog<'j> for SummaryState<'_> {
+    type Result = SummaryState<'j>;
+
+    fn with_log(self, log: &'j Log) -> Self::Result {
+        let source = self.selected_source().map(str::to_string);
+        let mut state = SummaryState {
+            log,
+            rows: SummaryState::count_messages(log, self.sort),
+            selected: self.selected,
+            sort: self.sort,
+            jump_request: None,
+        };
+        state.select_source(source.as_deref());
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::test_utils::{key, render_stateful, rows};
+
+    const LOG: &str = "\
+[10:00:00 INFO  SMAPI] Starting
+[10:00:01 TRACE SMAPI] Loading mods
+[10:00:02 DEBUG Content Patcher] Applying patches
+[10:00:03 ERROR Json Assets] Missing item
+[10:00:04 INFO  SMAPI] Done
+";
+
+    fn sources(buffer: &Buffer) -> Vec<String> {
+        rows(buffer)[2..]
+            .iter()
+            .filter_map(|row| Some(row.split("  ").next()?.trim_start_matches('│').to_string()))
+            .filter(|source| !source.is_empty() && !source.starts_with('└'))
+            .collect()
+    }
+
+    #[test]
+    fn counts_and_sorts_sources() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = SummaryState::new(&log);
+        let buffer = render_stateful(SummaryLog::default(), &mut state, 80, 7);
+        assert_eq!(
+            rows(&buffer)[1],
+            "│Source           TRACE  DEBUG   INFO  ALERT   WARN  ERROR  Total              │"
+        );
+        assert_eq!(
+            rows(&buffer)[2],
+            "│SMAPI                1      0      2      0      0      0      3              │"
+        );
+        assert_eq!(
+            sources(&buffer),
+            ["SMAPI", "Content Patcher", "Json Assets"]
+        );
+
+        // Sorting by errors keeps the same source selected
+        state.update(&key(KeyCode::Char('s')));
+        let buffer = render_stateful(SummaryLog::default(), &mut state, 80, 7);
+        assert_eq!(
+            sources(&buffer),
+            ["Json Assets", "SMAPI", "Content Patcher"]
+        );
+        assert_eq!(state.selected_source(), Some("SMAPI"));
+    }
+
+    #[test]
+    fn aligns_sources_with_wide_characters() {
+        let log = Log::parse(format!("{LOG}[10:00:05 WARN  星露谷] Wide\n")).unwrap();
+        let mut state = SummaryState::new(&log);
+        let buffer = render_stateful(SummaryLog::default(), &mut state, 80, 7);
+
+        // Each wide character takes up its own cell and a blank one after it
+        assert_eq!(
+            rows(&buffer)[5],
+            "│星 露 谷                0      0      0      0      1      0      1              │"
+        );
+    }
+
+    #[test]
+    fn jumps_to_first_message_of_source() {
+        let log = Log::parse(LOG.to_string()).unwrap();
+        let mut state = SummaryState::new(&log);
+        state.update(&key(KeyCode::Down));
+        state.update(&key(KeyCode::Enter));
+        assert_eq!(state.take_jump_request(), Some(2));
+        assert_eq!(state.take_jump_request(), None);
+    }
+}
@@ -0,0 +1,49 @@
+use crate::events::AppEvent;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use tui::{
+    backend::TestBackend,
+    buffer::Buffer,
+    widgets::{StatefulWidget, Widget},
+    Terminal,
+};
+
+/// Creates the event for pressing a key without modifiers.
+pub fn key(code: KeyCode) -> AppEvent {
+    AppEvent::TermEvent(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+}
+
+/// Renders a widget into a buffer of the given size.
+pub fn render(widget: impl Widget, width: u16, height: u16) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal
+        .draw(|frame| frame.render_widget(widget, frame.size()))
+        .unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// Renders a stateful widget into a buffer of the given size.
+pub fn render_stateful<W: StatefulWidget>(
+    widget: W,
+    state: &mut W::State,
+    width: u16,
+    height: u16,
+) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal
+        .draw(|frame| frame.render_stateful_widget(widget, frame.size(), state))
+        .unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// Gets the text of each row in a buffer, without trailing spaces.
+pub fn rows(buffer: &Buffer) -> Vec<String> {
+    let area = buffer.area;
+    (area.top()..area.bottom())
+        .map(|y| {
+            let row: String = (area.left()..area.right())
+                .map(|x| buffer.get(x, y).symbol.as_str())
+                .collect();
+            row.trim_end().to_string()
+        })
+        .collect()
+}
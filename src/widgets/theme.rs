@@ -0,0 +1,152 @@
+use crate::ast::Level;
+use tui::style::{Color, Modifier, Style};
+
+/// Colors and symbols used when rendering the log.
+#[derive(Clone, Debug)]
+pub struct Theme {
+    /// The color of each log level.
+    pub level_colors: LevelColors,
+    /// The color of message sources.
+    pub source_color: Color,
+    /// The background color of the selected item in lists.
+    pub selection_color: Color,
+    /// The text color of the controls bar at the bottom of the screen.
+    pub controls_color: Color,
+    /// The background color of the controls bar.
+    pub controls_background: Color,
+    /// The background color of enabled filters in the filters bar.
+    pub filter_enabled_color: Color,
+    /// The background color of disabled filters in the filters bar.
+    pub filter_disabled_color: Color,
+    /// The background color of text matching the search.
+    pub search_match_color: Color,
+    /// Whether to prefix each level with a symbol so levels can be told apart
+    /// without relying on color.
+    pub level_symbols: bool,
+    /// Whether errors should be rendered in bold.
+    pub bold_errors: bool,
+}
+
+impl Theme {
+    /// A theme that uses colors and symbols that are distinguishable with the
+    /// most common forms of color blindness.
+    pub fn color_blind() -> Self {
+        Theme {
+            level_colors: LevelColors {
+                trace: Color::DarkGray,
+                debug: Color::Gray,
+                info: Color::White,
+                alert: Color::Cyan,
+                warn: Color::Yellow,
+                error: Color::LightMagenta,
+                other: Color::Gray,
+            },
+            source_color: Color::LightBlue,
+            selection_color: Color::LightRed,
+            controls_color: Color::White,
+            controls_background: Color::Blue,
+            filter_enabled_color: Color::LightGreen,
+            filter_disabled_color: Color::White,
+            search_match_color: Color::Yellow,
+            level_symbols: true,
+            bold_errors: true,
+        }
+    }
+
+    /// Gets the color of a level.
+    pub fn level_color(&self, level: Level) -> Color {
+        match level {
+            Level::Trace => self.level_colors.trace,
+            Level::Debug => self.level_colors.debug,
+            Level::Info => self.level_colors.info,
+            Level::Alert => self.level_colors.alert,
+            Level::Warn => self.level_colors.warn,
+            Level::Error => self.level_colors.error,
+            Level::Other(_) => self.level_colors.other,
+        }
+    }
+
+    /// Gets the style of a level based on a base style.
+    pub fn level_style(&self, base: Style, level: Level) -> Style {
+        let style = base.fg(self.level_color(level));
+        if self.bold_errors && level == Level::Error {
+            style.add_modifier(Modifier::BOLD)
+        } else {
+            style
+        }
+    }
+
+    /// Gets the symbol displayed before a level, if level symbols are enabled.
+    pub fn level_symbol(&self, level: Level) -> Option<&'static str> {
+        self.level_symbols.then_some(match level {
+            Level::Trace => ".",
+            Level::Debug => ":",
+            Level::Info => "i",
+            Level::Alert => "*",
+            Level::Warn => "!",
+            Level::Error => "X",
+            Level::Other(_) => "?",
+        })
+    }
+
+    /// Gets the style of the controls bar.
+    pub fn controls_style(&self) -> Style {
+        Style::default()
+            .fg(self.controls_color)
+            .bg(self.controls_background)
+    }
+
+    /// Gets the style of text matching the search.
+    pub fn search_match_style(&self) -> Style {
+        Style::default()
+            .fg(Color::Black)
+            .bg(self.search_match_color)
+    }
+
+    /// Gets the width of the level column.
+    pub fn level_width(&self) -> usize {
+        if self.level_symbols {
+            7
+        } else {
+            5
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            level_colors: LevelColors {
+                trace: Color::DarkGray,
+                debug: Color::DarkGray,
+                info: Color::White,
+                alert: Color::Magenta,
+                warn: Color::Yellow,
+                error: Color::Red,
+                other: Color::Gray,
+            },
+            source_color: Color::Green,
+            selection_color: Color::LightRed,
+            controls_color: Color::White,
+            controls_background: Color::Blue,
+            filter_enabled_color: Color::LightGreen,
+            filter_disabled_color: Color::White,
+            search_match_color: Color::Yellow,
+            level_symbols: false,
+            bold_errors: false,
+        }
+    }
+}
+
+/// The color of each log level.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LevelColors {
+    pub trace: Color,
+    pub debug: Color,
+    pub info: Color,
+    pub alert: Color,
+    pub warn: Color,
+    pub error: Color,
+    /// The color of levels pufferwatch doesn't know about.
+    pub other: Color,
+}
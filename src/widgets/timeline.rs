@@ -0,0 +1,158 @@
+use crate::{
+    ast::{GameEvent, Message},
+    events::AppEvent,
+    log::Log,
+    widgets::{BindingDisplay, IconPack, State, WithLog},
+};
+use crossterm::event::{Event, KeyCode};
+use indexmap::IndexMap;
+use std::marker::PhantomData;
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::{Span, Spans},
+    widgets::{Block, Borders, StatefulWidget, Widget},
+};
+
+/// A list of the events in the game session, like loading a save or starting
+/// a new day.
+#[derive(Clone, Debug, Default)]
+pub struct TimelineList<'i> {
+    style: Style,
+    selected_style: Style,
+    marker: PhantomData<&'i Log>,
+}
+
+impl TimelineList<'_> {
+    /// The width of the panel, including its borders.
+    pub const WIDTH: u16 = 48;
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn selected_style(mut self, style: Style) -> Self {
+        self.selected_style = style;
+        self
+    }
+}
+
+impl<'i> StatefulWidget for TimelineList<'i> {
+    type State = TimelineListState<'i>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        // Render block
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(self.style)
+            .title(format!("Timeline ({})", state.events.len()));
+        let inner_area = block.inner(area);
+        buf.set_style(area, self.style);
+        block.render(area, buf);
+        if state.events.is_empty() {
+            buf.set_stringn(
+                inner_area.x,
+                inner_area.y,
+                "No events found",
+                inner_area.width.into(),
+                self.style,
+            );
+            return;
+        }
+
+        // Keep the selected event visible
+        let height = usize::from(inner_area.height);
+        let start = state.selected.saturating_sub(height.saturating_sub(1));
+        let visible = state.events.iter().enumerate().skip(start).take(height);
+        for ((index, (_, message, event)), y) in visible.zip(inner_area.y..) {
+            let style = if index == state.selected {
+                self.selected_style
+            } else {
+                self.style
+            };
+            let spans = Spans::from(vec![
+                Span::styled(format!("{} {event:17} ", message.timestamp), style),
+                Span::styled(describe(message, *event), style),
+            ]);
+            buf.set_style(Rect::new(inner_area.x, y, inner_area.width, 1), style);
+            buf.set_spans(inner_area.x, y, &spans, inner_area.width);
+        }
+    }
+}
+
+/// Gets the details of an event from its message, like which day started.
+fn describe<'i>(message: &'i Message<'i>, event: GameEvent) -> &'i str {
+    let contents = message.contents.lines().next().unwrap_or_default().trim();
+    event
+        .marker()
+        .and_then(|marker| contents.strip_prefix(marker))
+        .unwrap_or(contents)
+        .trim_start_matches([',', ' '])
+        .trim_end_matches('.')
+}
+
+#[derive(Clone, Debug)]
+pub struct TimelineListState<'i> {
+    events: Vec<(usize, &'i Message<'i>, GameEvent)>,
+    selected: usize,
+}
+
+impl<'i> TimelineListState<'i> {
+    /// Creates the timeline, selecting the latest event.
+    pub fn new(log: &'i Log) -> Self {
+        let events = Self::find_events(log);
+        let selected = events.len().saturating_sub(1);
+        TimelineListState { events, selected }
+    }
+
+    fn find_events(log: &'i Log) -> Vec<(usize, &'i Message<'i>, GameEvent)> {
+        log.messages()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, message)| Some((index, message, message.game_event()?)))
+            .collect()
+    }
+
+    /// Gets the index of the selected event's message in the log.
+    pub fn selected_message(&self) -> Option<usize> {
+        self.events.get(self.selected).map(|&(index, _, _)| index)
+    }
+}
+
+impl State for TimelineListState<'_> {
+    fn update(&mut self, event: &AppEvent) -> bool {
+        match event {
+            AppEvent::TermEvent(Event::Key(key_event)) => match key_event.code {
+                KeyCode::Up => {
+                    self.selected = self.selected.saturating_sub(1);
+                    true
+                }
+                KeyCode::Down => {
+                    self.selected = self
+                        .selected
+                        .saturating_add(1)
+                        .min(self.events.len().saturating_sub(1));
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn add_controls<I: IconPack>(&self, controls: &mut IndexMap<BindingDisplay<I>, &'static str>) {
+        controls.insert(BindingDisplay::Custom(I::UP_DOWN), "Nav");
+    }
+}
+
+impl<'j> WithLog<'j> for TimelineListState<'_> {
+    type Result = TimelineListState<'j>;
+
+    fn with_log(self, log: &'j Log) -> Self::Result {
+        let events = TimelineListState::find_events(log);
+        let selected = self.selected.min(events.len().saturating_sub(1));
+        TimelineListState { events, selected }
+    }
+}